@@ -0,0 +1,57 @@
+use antcolony_consensus::core::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// Build a node holding one pheromone trail per value
+fn node_with_values(count: usize) -> NodeState {
+    let mut node = NodeState::new(1);
+    for i in 0..count {
+        let value = ConsensusValue::from_string(&format!("value-{}", i));
+        node.emit_pheromone(value).unwrap();
+    }
+    node
+}
+
+fn bench_evaporation(c: &mut Criterion) {
+    c.bench_function("evaporate_pheromones", |b| {
+        b.iter_batched(
+            || node_with_values(100),
+            |mut node| node.evaporate_pheromones(),
+            criterion::BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_check_consensus(c: &mut Criterion) {
+    let mut node = node_with_values(100);
+
+    c.bench_function("check_consensus", |b| {
+        b.iter(|| black_box(node.check_consensus()))
+    });
+}
+
+fn bench_verify_batch(c: &mut Criterion) {
+    use antcolony_consensus::crypto::{verify_batch, verify_signature, KeyPairWrapper};
+
+    let key_pair = KeyPairWrapper::generate().unwrap();
+    let public_key = key_pair.public_key();
+    let messages: Vec<Vec<u8>> = (0..64).map(|i| format!("pheromone {}", i).into_bytes()).collect();
+    let signatures: Vec<_> = messages.iter().map(|m| key_pair.sign(m)).collect();
+    let items: Vec<_> = messages.iter()
+        .zip(&signatures)
+        .map(|(m, s)| (m.as_slice(), s, &public_key))
+        .collect();
+
+    let mut group = c.benchmark_group("verify_64_signatures");
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            items.iter()
+                .map(|(m, s, k)| verify_signature(m, s, k).unwrap_or(false))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("verify_batch", |b| b.iter(|| verify_batch(black_box(&items))));
+    group.finish();
+}
+
+criterion_group!(benches, bench_evaporation, bench_check_consensus, bench_verify_batch);
+criterion_main!(benches);
@@ -1,70 +1,24 @@
 use antcolony_consensus::*;
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use tokio::time::{sleep, Duration};
+use tokio::time::Duration;
 
 /// Simple simulation example
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     println!("🐜 AntColony Consensus - Simple Simulation");
-    
-    // Create a node
-    let node_state = Arc::new(RwLock::new(NodeState::new(1)));
-    
-    // Create network manager (simplified for example)
-    let network = NetworkManager::new(
-        "239.255.0.1:5000".parse()?,
-        5000,
-        node_state.clone(),
-    ).await?;
-    
-    // Create consensus engine
-    let consensus_engine = ConsensusEngine::new(
-        node_state.clone(),
-        network.clone(),
-    );
-    
-    // Start network
-    tokio::spawn(async move {
-        if let Err(e) = network.start().await {
-            eprintln!("Network error: {}", e);
-        }
-    });
-    
-    // Start consensus engine
-    tokio::spawn(async move {
-        if let Err(e) = consensus_engine.run().await {
-            eprintln!("Consensus error: {}", e);
-        }
-    });
-    
-    // Wait a bit
-    sleep(Duration::from_secs(5)).await;
-    
+
+    // Start a node with network and consensus engine running in the background
+    let node = QuickNode::start(QuickNodeConfig::new(1)).await?;
+
     // Propose a value
     let value = ConsensusValue::from_string("Hello, Consensus!");
-    let private_key = vec![0u8; 32]; // Dummy key
-    
-    {
-        let mut state = node_state.write().await;
-        if let Ok(pheromone) = state.emit_pheromone(value.clone(), &private_key) {
-            println!("Emitted pheromone for value: {}", value);
-            if let Err(e) = network.send_pheromone(pheromone).await {
-                eprintln!("Failed to send pheromone: {}", e);
-            }
-        }
-    }
-    
+    node.propose(value.clone()).await?;
+    println!("Proposed value: {}", value);
+
     // Wait for consensus
-    sleep(Duration::from_secs(10)).await;
-    
-    // Check consensus
-    if let Some(consensus) = consensus_engine.get_consensus().await {
-        println!("✅ Consensus reached: {}", consensus);
-    } else {
-        println!("⏳ Consensus not yet reached");
+    match node.await_consensus(Duration::from_secs(10)).await {
+        Ok(consensus) => println!("✅ Consensus reached: {}", consensus),
+        Err(_) => println!("⏳ Consensus not yet reached"),
     }
-    
+
     Ok(())
 }
-
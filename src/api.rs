@@ -0,0 +1,121 @@
+use crate::consensus::engine::ConsensusEngine;
+use crate::core::node_state::NodeStats;
+use crate::core::types::ConsensusValue;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Serialize, Deserialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tracing::{error, info};
+
+/// Body of `POST /propose`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposeRequest {
+    /// String to propose; it is hashed into a `ConsensusValue`
+    pub value: String,
+}
+
+/// Reply to `POST /propose`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposeResponse {
+    /// Hex hash of the proposed value
+    pub value: String,
+}
+
+/// Reply to `GET /consensus`; values are hex hashes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsensusReport {
+    pub value: Option<String>,
+    pub committed: Option<String>,
+    pub confidence: f64,
+}
+
+/// One value's trail in the reply to `GET /trails`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrailReport {
+    /// Hex hash of the value
+    pub value: String,
+
+    /// Distinct sources with a pheromone on the trail
+    pub sources: usize,
+
+    /// Summed strength of the trail
+    pub strength: f64,
+}
+
+/// Routes of the HTTP control API, served from `engine`
+///
+/// - `POST /propose` with `{"value": "..."}` proposes a string value
+/// - `GET /consensus` reports the current and committed values
+/// - `GET /stats` returns the node's `NodeStats`
+/// - `GET /trails` lists every value's trail, strongest first
+pub fn router(engine: Arc<ConsensusEngine>) -> Router {
+    Router::new()
+        .route("/propose", post(propose))
+        .route("/consensus", get(consensus))
+        .route("/stats", get(stats))
+        .route("/trails", get(trails))
+        .with_state(engine)
+}
+
+/// Serve the control API on `addr`, returning the bound address
+///
+/// The server runs on a background task until the runtime shuts down.
+pub async fn serve(engine: Arc<ConsensusEngine>, addr: SocketAddr) -> Result<SocketAddr, String> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Failed to bind HTTP API on {}: {}", addr, e))?;
+    let local_addr = listener.local_addr()
+        .map_err(|e| format!("Failed to read HTTP API address: {}", e))?;
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router(engine)).await {
+            error!("HTTP API error: {}", e);
+        }
+    });
+
+    info!("HTTP API listening on {}", local_addr);
+    Ok(local_addr)
+}
+
+async fn propose(
+    State(engine): State<Arc<ConsensusEngine>>,
+    Json(request): Json<ProposeRequest>,
+) -> Result<(StatusCode, Json<ProposeResponse>), (StatusCode, String)> {
+    let value = ConsensusValue::from_string(&request.value);
+    engine.propose(value.clone())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    Ok((StatusCode::ACCEPTED, Json(ProposeResponse { value: value.to_hex() })))
+}
+
+async fn consensus(State(engine): State<Arc<ConsensusEngine>>) -> Json<ConsensusReport> {
+    let state = engine.node_state().read().await;
+    Json(ConsensusReport {
+        value: state.current_value.as_ref().map(ConsensusValue::to_hex),
+        committed: state.committed_value.as_ref().map(ConsensusValue::to_hex),
+        confidence: state.consensus_confidence(),
+    })
+}
+
+async fn stats(State(engine): State<Arc<ConsensusEngine>>) -> Json<NodeStats> {
+    Json(engine.stats_snapshot().await)
+}
+
+async fn trails(State(engine): State<Arc<ConsensusEngine>>) -> Json<Vec<TrailReport>> {
+    let state = engine.node_state().read().await;
+    let mut trails: Vec<TrailReport> = state.pheromones
+        .values()
+        .map(|value| TrailReport {
+            value: value.to_hex(),
+            sources: state.distinct_sources(value),
+            strength: state.aggregate_strength(value),
+        })
+        .collect();
+    trails.sort_by(|a, b| b.strength.total_cmp(&a.strength));
+    Json(trails)
+}
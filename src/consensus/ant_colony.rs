@@ -1,39 +1,93 @@
-use crate::core::node_state::{NodeState, SharedNodeState};
-use crate::core::types::{ConsensusValue, NodeId, AntId};
+use crate::core::node_state::SharedNodeState;
 use crate::core::pheromone::Pheromone;
-use crate::core::ant_agent::AntAgent;
-use crate::network::NetworkManager;
-use std::sync::atomic::{AtomicU64, Ordering};
-use tokio::time::{interval, Duration};
-use tracing::{info, debug, warn};
+use crate::core::types::{AntId, ConsensusValue, NodeId, RoundId, DEFAULT_ROUND};
+use crate::core::ant_agent::{scoped_ant_id, AntAgent, AntRole};
+use crate::network::message::Message;
+use crate::network::Transport;
+use crate::utils::random::{RngSource, SeededRngSource};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, field, info, info_span, warn, Instrument, Span};
+
+/// What a single consensus step did
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StepReport {
+    /// Value the default round settled on, as `step` returns it
+    pub consensus: Option<ConsensusValue>,
+
+    /// Pheromones dropped by evaporation or expiry
+    pub evaporated: usize,
+
+    /// Ants that moved to a neighbor
+    pub ants_moved: usize,
+
+    /// Ants that ran out of energy and were removed
+    pub ants_died: usize,
+
+    /// Pheromones re-broadcast to neighbors by diffusion
+    #[serde(default)]
+    pub diffused: usize,
+
+    /// Pheromones stored once the step finished
+    pub pheromones_now: usize,
+}
 
 /// Ant colony consensus algorithm implementation
 pub struct AntColonyConsensus {
     node_state: SharedNodeState,
-    network: NetworkManager,
-    next_ant_id: AtomicU64,
+    network: Arc<dyn Transport>,
+    next_ant_id: AtomicU32,
+    last_step_sent: AtomicUsize,
+    last_step_deferred: AtomicUsize,
 }
 
 impl AntColonyConsensus {
     /// Create a new ant colony consensus instance
-    pub fn new(node_state: SharedNodeState, network: NetworkManager) -> Self {
+    pub fn new(node_state: SharedNodeState, network: Arc<dyn Transport>) -> Self {
         Self {
             node_state,
             network,
-            next_ant_id: AtomicU64::new(1),
+            next_ant_id: AtomicU32::new(1),
+            last_step_sent: AtomicUsize::new(0),
+            last_step_deferred: AtomicUsize::new(0),
         }
     }
 
-    /// Propose a consensus value
-    pub async fn propose_value(
+    /// Create an instance whose node draws all randomness from a source
+    /// seeded with `seed`, so a run's ant paths are reproducible
+    pub async fn with_seed(node_state: SharedNodeState, network: Arc<dyn Transport>, seed: u64) -> Self {
+        node_state.write().await.rng = Arc::new(SeededRngSource::new(seed));
+        Self::new(node_state, network)
+    }
+
+    /// Propose a consensus value in `round`, signed with the node's own key
+    ///
+    /// Rounds reach consensus independently of each other.
+    pub async fn propose_value(&self, round: RoundId, value: ConsensusValue) -> Result<(), String> {
+        self.propose_value_with_priority(round, value, 0).await
+    }
+
+    /// Propose a consensus value with a priority; higher-priority values
+    /// preempt lower-priority ones that have also reached the threshold
+    ///
+    /// A value whose payload fails the node's `value_validator` is refused
+    /// before anything is emitted.
+    pub async fn propose_value_with_priority(
         &self,
+        round: RoundId,
         value: ConsensusValue,
-        private_key: &[u8],
+        priority: u8,
     ) -> Result<(), String> {
         let mut state = self.node_state.write().await;
+        let node_id = state.id;
+
+        state.validate_payload(&value)
+            .map_err(|e| format!("Rejected proposal: {}", e))?;
         
         // Emit pheromone with the proposed value
-        let pheromone = state.emit_pheromone(value.clone(), private_key)
+        let pheromone = state.emit_round_pheromone(round, value.clone(), priority, None)
             .map_err(|e| format!("Failed to emit pheromone: {}", e))?;
 
         drop(state);
@@ -42,18 +96,64 @@ impl AntColonyConsensus {
         self.network.send_pheromone(pheromone).await?;
 
         // Create ant agents to explore the network
-        self.create_explorer_ants(value).await?;
+        self.create_explorer_ants(round, value.clone(), priority).await?;
+
+        info!(node_id, round_id = round, value = %value, priority, "Proposed consensus value");
+        Ok(())
+    }
+
+    /// Feed `pheromone` to this node as if `via` had broadcast it (or as
+    /// if it had arrived with no known neighbor), returning whether it
+    /// was stored
+    ///
+    /// The pheromone goes through the same verification, rate limiting
+    /// and storage as a received broadcast, and nothing is sent. Meant for
+    /// tests and for warm-starting a node from a trusted peer's trails.
+    pub async fn inject_pheromone(&self, pheromone: Pheromone, via: Option<NodeId>) -> bool {
+        let mut state = self.node_state.write().await;
+        match via {
+            Some(neighbor) => {
+                state.add_neighbor(neighbor);
+                state.accept_pheromone_from(pheromone, neighbor)
+            }
+            None => state.accept_pheromone(pheromone),
+        }
+    }
+
+    /// Withdraw this node's support for `value` in `round`
+    ///
+    /// The node's own pheromones for the value are dropped, ants drop any of
+    /// the node's own they carry (and are recalled once they carry nothing;
+    /// other sources' support is still delivered), and a signed
+    /// `Withdraw` is broadcast so peers discount the support they received too.
+    pub async fn withdraw_value(&self, round: RoundId, value: ConsensusValue) -> Result<(), String> {
+        let (node_id, message) = {
+            let mut state = self.node_state.write().await;
+            let (node_id, timestamp) = (state.id, state.clock.now());
+
+            state.withdraw(node_id, round, &value, timestamp);
+            state.ants.retain_mut(|ant| {
+                let carried = ant.carried_pheromones.len();
+                ant.carried_pheromones.retain(|p| p.source != node_id || p.round != round || p.value != value);
+                ant.carried_pheromones.len() == carried || !ant.carried_pheromones.is_empty()
+            });
+
+            let message = Message::Withdraw { node_id, round, value: value.clone(), timestamp, signature: None }
+                .sign(&state.key_pair);
+            (node_id, message)
+        };
 
-        info!("Proposed consensus value: {}", value);
+        self.network.broadcast(message).await?;
+
+        info!(node_id, round_id = round, value = %value, "Withdrew consensus value");
         Ok(())
     }
 
     /// Create explorer ants to spread the pheromone
-    async fn create_explorer_ants(&self, value: ConsensusValue) -> Result<(), String> {
-        let (node_id, neighbors, private_key) = {
+    async fn create_explorer_ants(&self, round: RoundId, value: ConsensusValue, priority: u8) -> Result<(), String> {
+        let (node_id, neighbors) = {
             let state = self.node_state.read().await;
-            let neighbors = state.get_neighbors();
-            (state.id, neighbors, vec![0u8; 32]) // TODO: Get actual private key
+            (state.id, state.get_neighbors())
         };
 
         if neighbors.is_empty() {
@@ -62,21 +162,27 @@ impl AntColonyConsensus {
 
         // Create multiple ants (one per neighbor initially)
         let num_ants = neighbors.len().min(5); // Limit to 5 ants
+        let scout_fraction = self.node_state.read().await.config.scout_fraction.clamp(0.0, 1.0);
+        let num_scouts = (num_ants as f64 * scout_fraction).round() as usize;
 
-        for _ in 0..num_ants {
-            let ant_id = self.next_ant_id.fetch_add(1, Ordering::Relaxed);
+        for index in 0..num_ants {
+            let ant_id = scoped_ant_id(node_id, self.next_ant_id.fetch_add(1, Ordering::Relaxed));
             
             // Create pheromone for ant to carry
             let mut state = self.node_state.write().await;
-            let pheromone = state.emit_pheromone(value.clone(), &private_key)
+            let pheromone = state.emit_round_pheromone(round, value.clone(), priority, None)
                 .map_err(|e| format!("Failed to create pheromone: {}", e))?;
             drop(state);
 
             // Create ant with pheromone
-            let ant = AntAgent::with_pheromone(ant_id, node_id, pheromone);
+            let mut state = self.node_state.write().await;
+            let mut ant = AntAgent::with_config(ant_id, node_id, &state.config);
+            ant.pick_up(pheromone);
+            if index < num_scouts {
+                ant.role = AntRole::Scout;
+            }
             
             // Add ant to node
-            let mut state = self.node_state.write().await;
             state.add_ant(ant);
         }
 
@@ -84,106 +190,691 @@ impl AntColonyConsensus {
     }
 
     /// Run the consensus algorithm step
+    ///
+    /// Every round is checked and each one with a consensus is announced;
+    /// the default round's value is returned. At most
+    /// `max_messages_per_step` messages are emitted; ants that could not
+    /// report their move stay put and move on a later step.
     pub async fn step(&self) -> Result<Option<ConsensusValue>, String> {
+        Ok(self.run_step(None).await?.consensus)
+    }
+
+    /// Run a step as `step` does, but evaporate pheromones as if exactly
+    /// `elapsed` had passed since the previous step rather than by the wall
+    /// clock, so a seeded node steps identically on every run
+    pub async fn step_elapsed(&self, elapsed: Duration) -> Result<Option<ConsensusValue>, String> {
+        Ok(self.run_step(Some(elapsed)).await?.consensus)
+    }
+
+    /// Run a step as `step` does, reporting what it did besides the
+    /// consensus reached
+    pub async fn step_report(&self) -> Result<StepReport, String> {
+        self.run_step(None).await
+    }
+
+    /// Run a step as `step_elapsed` does, reporting what it did besides
+    /// the consensus reached
+    pub async fn step_report_elapsed(&self, elapsed: Duration) -> Result<StepReport, String> {
+        self.run_step(Some(elapsed)).await
+    }
+
+    async fn run_step(&self, elapsed: Option<Duration>) -> Result<StepReport, String> {
         let mut state = self.node_state.write().await;
+        let mut budget = state.config.max_messages_per_step.unwrap_or(usize::MAX);
 
         // Evaporate pheromones
-        state.evaporate_pheromones();
+        let stored = state.pheromones.all_pheromones().count();
+        match elapsed {
+            Some(elapsed) => state.evaporate_pheromones_elapsed(elapsed),
+            None => state.evaporate_pheromones(),
+        }
+        let evaporated = stored.saturating_sub(state.pheromones.all_pheromones().count());
+
+        // Clamp any runaway intensities
+        for anomaly in state.check_intensity_anomalies() {
+            warn!(anomaly = ?anomaly, "Pheromone intensity anomaly");
+        }
 
         // Update ants
+        let ants = state.ants.len();
         state.update_ants();
+        let ants_died = ants - state.ants.len();
 
-        // Check for consensus
-        let consensus = state.check_consensus();
+        // Check for consensus in every round
+        let reached = state.check_consensus_rounds();
+        let consensus = reached.get(&DEFAULT_ROUND).cloned();
         
         drop(state);
 
-        // If consensus reached, announce it
-        if let Some(value) = &consensus {
-            self.announce_consensus(value.clone()).await?;
+        let mut sent = 0;
+        let mut deferred = 0;
+
+        // Announce each round that reached consensus
+        for (round, value) in reached {
+            if budget > 0 {
+                self.announce_consensus(round, value).await?;
+                budget -= 1;
+                sent += 1;
+            } else {
+                deferred += 1;
+            }
         }
 
         // Move ants
-        self.move_ants().await?;
+        let (moved, waiting) = self.move_ants(budget).await?;
+        budget -= moved;
+        sent += moved;
+        deferred += waiting;
+
+        // Diffuse the strongest trails that changed to neighbors
+        let candidates = self.node_state.read().await.diffusion_candidates();
+        let diffused = candidates.len().min(budget);
+        deferred += candidates.len() - diffused;
+        for pheromone in &candidates[..diffused] {
+            if let Err(e) = self.network.send_pheromone(pheromone.clone()).await {
+                warn!(error = %e, "Failed to diffuse pheromone");
+            }
+        }
+        self.node_state.write().await.mark_diffused(&candidates[..diffused]);
+        sent += diffused;
+
+        let pheromones_now = self.node_state.read().await.pheromones.all_pheromones().count();
+
+        self.last_step_sent.store(sent, Ordering::Relaxed);
+        self.last_step_deferred.store(deferred, Ordering::Relaxed);
+
+        Ok(StepReport {
+            consensus,
+            evaporated,
+            ants_moved: moved,
+            ants_died,
+            diffused,
+            pheromones_now,
+        })
+    }
+
+    /// Number of messages emitted by the most recent step
+    pub fn last_step_sent(&self) -> usize {
+        self.last_step_sent.load(Ordering::Relaxed)
+    }
 
-        Ok(consensus)
+    /// Number of emissions the most recent step deferred because of
+    /// `max_messages_per_step`
+    pub fn last_step_deferred(&self) -> usize {
+        self.last_step_deferred.load(Ordering::Relaxed)
     }
 
-    /// Move ants to neighboring nodes
-    async fn move_ants(&self) -> Result<(), String> {
-        let (ants_to_move, neighbors, node_id) = {
+    /// Move up to `budget` ants to neighboring nodes, returning how many
+    /// moved and how many were left waiting for a later step
+    async fn move_ants(&self, budget: usize) -> Result<(usize, usize), String> {
+        let (ants_to_move, neighbors, node_id, rng) = {
             let state = self.node_state.read().await;
             let ants: Vec<_> = state.ants.iter()
-                .filter(|ant| ant.is_alive())
-                .map(|ant| (ant.id, ant.current_node, ant.carried_pheromone.clone()))
+                .filter(|ant| ant.is_alive() && ant.current_node == state.id)
+                .map(|ant| ant.id)
                 .collect();
-            (ants, state.get_neighbors(), state.id)
+            (ants, state.get_neighbors(), state.id, state.rng.clone())
         };
 
-        for (ant_id, current_node, carried_pheromone) in ants_to_move {
-            if current_node != node_id {
-                continue; // Ant is not at this node
+        let mut moved = 0;
+        let deferred = ants_to_move.len().saturating_sub(budget);
+
+        for ant_id in ants_to_move.into_iter().take(budget) {
+            let span = info_span!(
+                "ant_move",
+                node_id,
+                ant_id,
+                round_id = field::Empty,
+                value = field::Empty,
+            );
+            if self.move_ant(ant_id, node_id, &neighbors, rng.as_ref()).instrument(span).await {
+                moved += 1;
             }
+        }
 
-            // Get pheromone intensities for neighbors
-            let pheromone_intensities = self.get_pheromone_intensities().await;
+        Ok((moved, deferred))
+    }
 
-            // Select next node
-            let mut state = self.node_state.write().await;
-            if let Some(ant) = state.ants.iter_mut().find(|a| a.id == ant_id) {
-                if let Some(next_node) = ant.select_next_node(&neighbors, &pheromone_intensities) {
-                    // Move ant
-                    ant.move_to(next_node);
-
-                    // Send ant movement message
-                    let message = crate::network::message::Message::AntMovement {
-                        ant_id,
-                        from_node: node_id,
-                        to_node: next_node,
-                        carried_pheromone: ant.carried_pheromone.clone(),
-                    };
-
-                    drop(state);
-
-                    if let Err(e) = self.network.broadcast(message).await {
-                        warn!("Failed to broadcast ant movement: {}", e);
-                    }
-                }
-            }
+    /// Move one ant to a neighbor chosen by trail intensity and link
+    /// latency, returning whether it moved
+    ///
+    /// An ant carrying several pheromones follows the trail of the one it
+    /// picked up first, and records that value and its round on the current
+    /// `ant_move` span.
+    async fn move_ant(&self, ant_id: AntId, node_id: NodeId, neighbors: &[NodeId], rng: &dyn RngSource) -> bool {
+        // Get pheromone intensities for neighbors
+        let carried = {
+            let state = self.node_state.read().await;
+            state.ants.iter()
+                .find(|a| a.id == ant_id)
+                .and_then(|a| a.carried_pheromones.first())
+                .map(|p| (p.round, p.value.clone()))
+        };
+        if let Some((round, value)) = &carried {
+            Span::current()
+                .record("round_id", round)
+                .record("value", field::display(value));
         }
+        let pheromone_intensities = self.get_pheromone_intensities(carried.as_ref().map(|(_, value)| value)).await;
 
-        Ok(())
-    }
+        // Select next node
+        let mut state = self.node_state.write().await;
+        let Some(ant) = state.ants.iter().find(|a| a.id == ant_id) else {
+            return false;
+        };
+        let heuristic = state.latency_heuristic();
+        let Some(next_node) = ant.select_next_node_weighted(
+            neighbors, &pheromone_intensities, &heuristic, state.config.aco_exponents, rng,
+        ) else {
+            return false;
+        };
 
-    /// Get pheromone intensities for all neighbors
-    async fn get_pheromone_intensities(&self) -> Vec<(NodeId, f64)> {
-        let state = self.node_state.read().await;
-        let mut intensities = Vec::new();
+        // Reinforce the trails at the node the ant is leaving
+        for deposit in ant.deposit_at(node_id) {
+            state.deposit_path(deposit);
+        }
 
-        for neighbor in &state.neighbors {
-            // Calculate average intensity for this neighbor's pheromones
-            // This is simplified - in reality, we'd query the neighbor
-            let avg_intensity = 0.5; // Placeholder
-            intensities.push((*neighbor, avg_intensity));
+        // Move ant
+        let Some(ant) = state.ants.iter_mut().find(|a| a.id == ant_id) else {
+            return false;
+        };
+        ant.move_to(next_node);
+
+        // Send ant movement message
+        let mut carried = ant.carried_pheromones.clone().into_iter();
+        let message = Message::AntMovement {
+            ant_id,
+            from_node: node_id,
+            to_node: next_node,
+            carried_pheromone: carried.next(),
+            ttl: ant.ttl,
+            extra_pheromones: carried.collect(),
+        };
+
+        drop(state);
+
+        debug!(to_node = next_node, "Ant moved");
+        if let Err(e) = self.network.broadcast(message).await {
+            warn!(error = %e, "Failed to broadcast ant movement");
         }
+        true
+    }
 
-        intensities
+    /// Trail intensity behind each neighbor for the value an ant carries
+    /// (or the strongest trail per neighbor when it carries nothing)
+    async fn get_pheromone_intensities(&self, carried: Option<&ConsensusValue>) -> Vec<(NodeId, f64)> {
+        let state = self.node_state.read().await;
+        state.neighbor_intensities(carried)
     }
 
-    /// Announce consensus to the network
-    async fn announce_consensus(&self, value: ConsensusValue) -> Result<(), String> {
-        let node_id = {
+    /// Announce a round's consensus to the network
+    async fn announce_consensus(&self, round: RoundId, value: ConsensusValue) -> Result<(), String> {
+        let (node_id, key_pair) = {
             let state = self.node_state.read().await;
-            state.id
+            (state.id, state.key_pair.clone())
         };
 
-        let message = crate::network::message::Message::ConsensusAnnouncement {
+        debug!(node_id, round_id = round, value = %value, "Announcing consensus");
+        let message = Message::ConsensusAnnouncement {
             node_id,
+            round,
             value,
-        };
+            signature: None,
+        }.sign(&key_pair);
 
         self.network.broadcast(message).await?;
         Ok(())
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ant_memory::AntMemory;
+    use crate::core::events::NodeEvent;
+    use crate::core::node_state::NodeState;
+    use crate::core::snapshot::StateSnapshot;
+    use crate::crypto::signing::KeyPairWrapper;
+    use crate::network::{apply_message, NoopTransport};
+    use crate::utils::timing::ManualClock;
+    use tokio::sync::RwLock;
+
+    /// Everything a seeded run left behind: each message sent, serialized
+    /// in order, each ant serialized with its visits in order, the node's
+    /// events and its final snapshot
+    #[derive(Debug, PartialEq)]
+    struct RunTrace {
+        messages: Vec<Vec<u8>>,
+        ants: Vec<Vec<u8>>,
+        events: Vec<NodeEvent>,
+        snapshot: StateSnapshot,
+    }
+
+    /// Run a proposal and a series of steps on a fixed clock and key,
+    /// drawing randomness from `seed`
+    async fn seeded_run(seed: u64, private_key: &[u8]) -> RunTrace {
+        let mut state = NodeState::new(1);
+        state.clock = Arc::new(ManualClock::new(1_000_000));
+        state.set_key_pair(KeyPairWrapper::from_private_key_bytes(private_key).unwrap());
+        for neighbor in 2..=8 {
+            state.add_neighbor(neighbor);
+        }
+        let node_state = Arc::new(RwLock::new(state));
+
+        let transport = Arc::new(NoopTransport::new(1));
+        let consensus = AntColonyConsensus::with_seed(node_state.clone(), transport.clone(), seed).await;
+
+        consensus.propose_value(DEFAULT_ROUND, ConsensusValue::from_string("seeded")).await.unwrap();
+        for _ in 0..20 {
+            consensus.step_report_elapsed(Duration::from_millis(100)).await.unwrap();
+        }
+
+        let mut state = node_state.write().await;
+        let ants = state.ants
+            .iter()
+            .map(|ant| {
+                // The visited set's hash order varies between runs, so
+                // visits are recorded in the order they were made
+                let visits: Vec<NodeId> = match &ant.visited_nodes {
+                    AntMemory::Exact(window) => window.iter().copied().collect(),
+                    AntMemory::Bloom(_) => Vec::new(),
+                };
+                let mut bare = ant.clone();
+                bare.visited_nodes = AntMemory::exact();
+                let mut bytes = serde_json::to_vec(&bare).unwrap();
+                for node in visits {
+                    bytes.extend_from_slice(&node.to_be_bytes());
+                }
+                bytes
+            })
+            .collect();
+
+        RunTrace {
+            messages: transport.drain().iter().map(|message| message.to_bytes().unwrap()).collect(),
+            ants,
+            events: state.drain_events(),
+            snapshot: state.snapshot(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_step_respects_message_cap() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        {
+            let mut state = node_state.write().await;
+            state.config.max_messages_per_step = Some(3);
+            for neighbor in 2..=8 {
+                state.add_neighbor(neighbor);
+            }
+        }
+
+        let consensus = AntColonyConsensus::new(node_state.clone(), Arc::new(NoopTransport::new(1)));
+
+        // Five explorer ants plus one consensus announcement per step
+        consensus.propose_value(DEFAULT_ROUND, ConsensusValue::from_string("capped")).await.unwrap();
+
+        let mut emitted = Vec::new();
+        for _ in 0..3 {
+            consensus.step().await.unwrap();
+            emitted.push((consensus.last_step_sent(), consensus.last_step_deferred()));
+        }
+
+        assert_eq!(emitted, vec![(3, 3), (3, 1), (2, 0)]);
+    }
+
+    #[tokio::test]
+    async fn test_rounds_reach_consensus_independently() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let consensus = AntColonyConsensus::new(node_state.clone(), Arc::new(NoopTransport::new(1)));
+
+        let first = ConsensusValue::from_string("round one");
+        let second = ConsensusValue::from_string("round two");
+        consensus.propose_value(1, first.clone()).await.unwrap();
+        consensus.propose_value(2, second.clone()).await.unwrap();
+
+        // Neither round's trail counts toward the default round
+        assert_eq!(consensus.step().await.unwrap(), None);
+
+        let state = node_state.read().await;
+        assert_eq!(state.round_value(1), Some(&first));
+        assert_eq!(state.round_value(2), Some(&second));
+        assert_eq!(state.round_value(DEFAULT_ROUND), None);
+        assert_eq!(consensus.last_step_sent(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_explorer_ants_mix_scouts_and_workers() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        {
+            let mut state = node_state.write().await;
+            state.config.scout_fraction = 0.4;
+            for neighbor in 2..=8 {
+                state.add_neighbor(neighbor);
+            }
+        }
+
+        let consensus = AntColonyConsensus::new(node_state.clone(), Arc::new(NoopTransport::new(1)));
+        consensus.propose_value(DEFAULT_ROUND, ConsensusValue::from_string("mixed")).await.unwrap();
+
+        let state = node_state.read().await;
+        let scouts = state.ants.iter().filter(|ant| ant.role == AntRole::Scout).count();
+        assert_eq!(state.ants.len(), 5);
+        assert_eq!(scouts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_seeded_run_is_reproducible() {
+        let private_key = KeyPairWrapper::generate().unwrap().private_key_bytes();
+        let first = seeded_run(1234, &private_key).await;
+        let second = seeded_run(1234, &private_key).await;
+
+        assert!(!first.messages.is_empty());
+        assert!(!first.ants.is_empty());
+        assert_eq!(first, second);
+
+        // Another seed routes the ants differently
+        assert_ne!(seeded_run(4321, &private_key).await, first);
+    }
+
+    #[tokio::test]
+    async fn test_ant_relay_stops_at_zero_ttl() {
+        use crate::network::InMemoryNetwork;
+        use tokio::time::{sleep, Duration};
+
+        let mut origin = NodeState::new(9);
+        let relay = Arc::new(RwLock::new(NodeState::new(1)));
+        let target = Arc::new(RwLock::new(NodeState::new(2)));
+        for state in [&relay, &target] {
+            state.write().await.register_public_key(9, origin.public_key());
+        }
+        relay.write().await.add_neighbor(2);
+
+        let network = InMemoryNetwork::new();
+        let relay_transport = Arc::new(network.transport(1, relay.clone()));
+        network.transport(2, target.clone()).start().await.unwrap();
+        let consensus = AntColonyConsensus::new(relay.clone(), relay_transport);
+
+        // An ant arriving with one hop to spare is processed and kept moving
+        let value = ConsensusValue::from_string("relayed");
+        let arrival = Message::AntMovement {
+            ant_id: 42,
+            from_node: 9,
+            to_node: 1,
+            carried_pheromone: Some(origin.emit_pheromone(value.clone()).unwrap()),
+            ttl: 1,
+            extra_pheromones: vec![],
+        };
+        apply_message(&arrival, &relay).await.unwrap();
+        {
+            let state = relay.read().await;
+            assert!(state.pheromones.contains_key(&value));
+            assert_eq!(state.ants.iter().map(|ant| (ant.id, ant.ttl)).collect::<Vec<_>>(), vec![(42, 0)]);
+        }
+
+        // Its forward goes out at ttl 0, so the next node keeps only the pheromone
+        consensus.step().await.unwrap();
+        assert_eq!(relay.read().await.ants[0].current_node, 2);
+        for _ in 0..50 {
+            if target.read().await.pheromones.contains_key(&value) {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        let state = target.read().await;
+        assert!(state.pheromones.contains_key(&value));
+        assert!(state.ants.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_step_report_counts_operations() {
+        use crate::core::ant_agent::AntAgent;
+
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let value = ConsensusValue::from_string("reported");
+        {
+            let mut state = node_state.write().await;
+            state.add_neighbor(2);
+
+            // One live pheromone and two already expired
+            state.receive_pheromone(Pheromone::new(value.clone(), 5, &[]).unwrap());
+            for source in 6..=7 {
+                let stale = ConsensusValue::from_string("stale");
+                state.receive_pheromone(Pheromone::with_expiry(stale, source, 0, Some(1), &[]).unwrap());
+            }
+
+            // Two ants with energy to move and one on its last breath
+            state.add_ant(AntAgent::new(10, 1));
+            state.add_ant(AntAgent::new(11, 1));
+            let mut spent = AntAgent::new(12, 1);
+            spent.energy_level = spent.energy_decay_rate / 2.0;
+            state.add_ant(spent);
+        }
+
+        let consensus = AntColonyConsensus::new(node_state.clone(), Arc::new(NoopTransport::new(1)));
+        let report = consensus.step_report_elapsed(Duration::ZERO).await.unwrap();
+
+        assert_eq!(report, StepReport {
+            consensus: Some(value),
+            evaporated: 2,
+            ants_moved: 2,
+            ants_died: 1,
+            diffused: 0,
+            pheromones_now: 1,
+        });
+        assert_eq!(node_state.read().await.ants.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_ant_delivers_every_carried_pheromone() {
+        use crate::core::ant_agent::AntAgent;
+        use crate::network::InMemoryNetwork;
+        use tokio::time::{sleep, Duration};
+
+        let origin = Arc::new(RwLock::new(NodeState::new(1)));
+        let target = Arc::new(RwLock::new(NodeState::new(2)));
+        let public_key = origin.read().await.public_key();
+        target.write().await.register_public_key(1, public_key);
+
+        let values = [ConsensusValue::from_string("first"), ConsensusValue::from_string("second")];
+        {
+            let mut state = origin.write().await;
+            state.add_neighbor(2);
+            let mut ant = AntAgent::new(5, 1);
+            for value in &values {
+                let pheromone = state.emit_pheromone(value.clone()).unwrap();
+                assert!(ant.pick_up(pheromone));
+            }
+            state.add_ant(ant);
+        }
+
+        let network = InMemoryNetwork::new();
+        let origin_transport = Arc::new(network.transport(1, origin.clone()));
+        network.transport(2, target.clone()).start().await.unwrap();
+        let consensus = AntColonyConsensus::new(origin.clone(), origin_transport);
+
+        consensus.step().await.unwrap();
+        assert_eq!(origin.read().await.ants[0].current_node, 2);
+        for _ in 0..50 {
+            let state = target.read().await;
+            if values.iter().all(|value| state.pheromones.contains_key(value)) {
+                break;
+            }
+            drop(state);
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        let state = target.read().await;
+        for value in &values {
+            assert!(state.pheromones.contains_key(value), "{} was not delivered", value);
+        }
+        assert_eq!(state.ants[0].summary().carrying, values);
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_drops_only_own_carried_support() {
+        use crate::core::ant_agent::AntAgent;
+
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let value = ConsensusValue::from_string("contested");
+        {
+            let mut state = node_state.write().await;
+            let mut peer = NodeState::new(2);
+            let mut mixed = AntAgent::new(5, 1);
+            assert!(mixed.pick_up(peer.emit_pheromone(value.clone()).unwrap()));
+            let mut own = AntAgent::new(6, 1);
+            assert!(own.pick_up(state.emit_pheromone(value.clone()).unwrap()));
+            state.add_ant(mixed);
+            state.add_ant(own);
+        }
+
+        let consensus = AntColonyConsensus::new(node_state.clone(), Arc::new(NoopTransport::new(1)));
+        consensus.withdraw_value(DEFAULT_ROUND, value).await.unwrap();
+
+        // The ant carrying node 2's support keeps it; the one left empty is recalled
+        let state = node_state.read().await;
+        assert_eq!(state.ants.len(), 1);
+        assert_eq!(state.ants[0].id, 5);
+        assert_eq!(state.ants[0].carried_pheromones[0].source, 2);
+    }
+
+    #[tokio::test]
+    async fn test_ant_ids_are_scoped_per_node() {
+        use crate::core::ant_agent::ant_origin;
+        use std::collections::HashSet;
+
+        let mut ids = Vec::new();
+        for node_id in [1, 2] {
+            let node_state = Arc::new(RwLock::new(NodeState::new(node_id)));
+            for neighbor in 3..=8 {
+                node_state.write().await.add_neighbor(neighbor);
+            }
+            let consensus = AntColonyConsensus::new(node_state.clone(), Arc::new(NoopTransport::new(node_id)));
+            consensus.propose_value(DEFAULT_ROUND, ConsensusValue::from_string("scoped")).await.unwrap();
+
+            let state = node_state.read().await;
+            assert!(state.ants.iter().all(|ant| ant_origin(ant.id) == node_id));
+            ids.extend(state.ants.iter().map(|ant| ant.id));
+        }
+
+        // Both nodes count from 1, yet no id repeats
+        assert_eq!(ids.len(), 10);
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len());
+    }
+
+    #[tokio::test]
+    async fn test_injected_pheromones_reach_consensus() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        node_state.write().await.config.min_quorum = 3;
+        let consensus = AntColonyConsensus::new(node_state.clone(), Arc::new(NoopTransport::new(1)));
+
+        let value = ConsensusValue::from_string("bootstrapped");
+        let mut peers: Vec<NodeState> = (2..=4).map(NodeState::new).collect();
+        for peer in &peers {
+            node_state.write().await.register_public_key(peer.id, peer.public_key());
+        }
+
+        // A forgery is rejected just as it would be off the wire
+        let mut forged = peers[0].emit_pheromone(value.clone()).unwrap();
+        forged.source = 9;
+        assert!(!consensus.inject_pheromone(forged, None).await);
+
+        for (index, peer) in peers.iter_mut().enumerate() {
+            assert_eq!(consensus.step().await.unwrap(), None);
+            let via = (index == 0).then_some(2);
+            assert!(consensus.inject_pheromone(peer.emit_pheromone(value.clone()).unwrap(), via).await);
+        }
+        assert_eq!(consensus.step().await.unwrap(), Some(value.clone()));
+
+        let state = node_state.read().await;
+        assert_eq!(state.stats.messages_received, 0);
+        assert!(state.intensity_via(2, &value) > 0.0);
+    }
+
+    /// Propose a value at one end of the line 1 - 2 - 3 and step every
+    /// node until the far end holds it, returning how many steps it took
+    async fn steps_to_far_end(diffusion_rate: f64) -> Option<usize> {
+
+        let links: [(NodeId, Vec<NodeId>); 3] = [(1, vec![2]), (2, vec![1, 3]), (3, vec![2])];
+        let states: Vec<_> = (1..=3).map(|id| Arc::new(RwLock::new(NodeState::new(id)))).collect();
+        let mut keys = Vec::new();
+        for state in &states {
+            keys.push(state.read().await.public_key());
+        }
+        let mut nodes = Vec::new();
+        for ((id, neighbors), state) in links.iter().zip(&states) {
+            {
+                let mut state = state.write().await;
+                state.config.diffusion_rate = diffusion_rate;
+                for (other, key) in (1..=3).zip(&keys) {
+                    state.register_public_key(other, key.clone());
+                }
+                for neighbor in neighbors {
+                    state.add_neighbor(*neighbor);
+                }
+            }
+            let transport = Arc::new(NoopTransport::new(*id));
+            let consensus = AntColonyConsensus::with_seed(state.clone(), transport.clone(), 7).await;
+            nodes.push((transport, consensus));
+        }
+
+        // Hand each node's outbox to its neighbors on the line only
+        let deliver = || async {
+            for ((_, neighbors), (transport, _)) in links.iter().zip(&nodes) {
+                for message in transport.drain() {
+                    for neighbor in neighbors {
+                        apply_message(&message, &states[*neighbor as usize - 1]).await.unwrap();
+                    }
+                }
+            }
+        };
+
+        let value = ConsensusValue::from_string("diffused");
+        nodes[0].1.propose_value(DEFAULT_ROUND, value.clone()).await.unwrap();
+        deliver().await;
+
+        for step in 1..=10 {
+            for (_, consensus) in &nodes {
+                consensus.step().await.unwrap();
+            }
+            deliver().await;
+            if states[2].read().await.pheromones.contains_key(&value) {
+                return Some(step);
+            }
+        }
+        None
+    }
+
+    #[tokio::test]
+    async fn test_diffusion_speeds_propagation_along_a_line() {
+        let with = steps_to_far_end(0.5).await;
+        let without = steps_to_far_end(0.0).await;
+
+        assert_eq!(with, Some(1));
+        assert!(without.is_none_or(|steps| steps > 1), "without diffusion: {:?}", without);
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_trails_not_rediffused() {
+
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let value = ConsensusValue::from_string("steady");
+        let mut peer = NodeState::new(2);
+        {
+            let mut state = node_state.write().await;
+            state.config.diffusion_rate = 1.0;
+            state.register_public_key(2, peer.public_key());
+            assert!(state.accept_pheromone(peer.emit_pheromone(value.clone()).unwrap()));
+        }
+        let consensus = AntColonyConsensus::new(node_state.clone(), Arc::new(NoopTransport::new(1)));
+
+        assert_eq!(consensus.step_report_elapsed(Duration::ZERO).await.unwrap().diffused, 1);
+        assert_eq!(consensus.step_report_elapsed(Duration::ZERO).await.unwrap().diffused, 0);
+
+        // Fresh support gives the trail a new strongest pheromone to pass on
+        let fresh = peer.emit_pheromone(value).unwrap();
+        assert!(node_state.write().await.accept_pheromone(fresh));
+        assert_eq!(consensus.step_report_elapsed(Duration::ZERO).await.unwrap().diffused, 1);
+    }
+}
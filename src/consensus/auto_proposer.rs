@@ -0,0 +1,211 @@
+use crate::consensus::engine::ConsensusEngine;
+use crate::core::types::ConsensusValue;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::fs::File;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+use tokio::time::{interval, sleep, Duration, MissedTickBehavior};
+use tracing::{debug, info};
+
+/// Default interval between automatic proposals
+pub const DEFAULT_PROPOSE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default number of proposals that may be pending at once
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 4;
+
+/// Proposes values read line by line from a source, one per interval
+///
+/// A proposal stays in flight while its trail is still alive at this node
+/// and it has not become the agreed value; once `max_in_flight` proposals
+/// are pending, reading pauses until one settles or evaporates.
+pub struct AutoProposer<R> {
+    engine: Arc<ConsensusEngine>,
+    source: R,
+    interval: Duration,
+    max_in_flight: usize,
+}
+
+impl<R: AsyncBufRead + Unpin> AutoProposer<R> {
+    /// Create a proposer reading newline-separated values from `source`
+    pub fn new(engine: Arc<ConsensusEngine>, source: R) -> Self {
+        Self {
+            engine,
+            source,
+            interval: DEFAULT_PROPOSE_INTERVAL,
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+        }
+    }
+
+    /// Set the interval between proposals
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set how many proposals may be pending at once
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// Propose every non-empty line of the source, returning the values
+    /// proposed once the source is exhausted
+    pub async fn run(mut self) -> Result<Vec<ConsensusValue>, String> {
+        let mut proposed = Vec::new();
+        let mut ticker = interval(self.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            let read = self.source.read_line(&mut line).await
+                .map_err(|e| format!("Failed to read proposal source: {}", e))?;
+            if read == 0 {
+                break;
+            }
+
+            let text = line.trim();
+            if text.is_empty() {
+                continue;
+            }
+
+            ticker.tick().await;
+            while self.in_flight(&proposed).await >= self.max_in_flight {
+                debug!("Proposal cap of {} reached, waiting", self.max_in_flight);
+                sleep(self.interval).await;
+            }
+
+            let value = ConsensusValue::from_string(text);
+            self.engine.propose(value.clone()).await?;
+            proposed.push(value);
+        }
+
+        info!("Auto-proposer finished after {} proposals", proposed.len());
+        Ok(proposed)
+    }
+
+    /// Number of proposals whose trail is alive but not yet agreed on
+    async fn in_flight(&self, proposed: &[ConsensusValue]) -> usize {
+        let state = self.engine.node_state().read().await;
+        proposed
+            .iter()
+            .filter(|value| {
+                state.pheromones.contains_key(value)
+                    && state.current_value.as_ref() != Some(value)
+            })
+            .count()
+    }
+}
+
+impl AutoProposer<BufReader<File>> {
+    /// Create a proposer reading values from a file, one per line
+    pub async fn from_file(engine: Arc<ConsensusEngine>, path: impl AsRef<Path>) -> Result<Self, String> {
+        let file = File::open(path.as_ref()).await
+            .map_err(|e| format!("Failed to open {}: {}", path.as_ref().display(), e))?;
+        Ok(Self::new(engine, BufReader::new(file)))
+    }
+}
+
+impl AutoProposer<BufReader<tokio::io::Stdin>> {
+    /// Create a proposer reading values from standard input, one per line
+    pub fn from_stdin(engine: Arc<ConsensusEngine>) -> Self {
+        Self::new(engine, BufReader::new(tokio::io::stdin()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::ConsensusEvent;
+    use crate::core::node_state::NodeState;
+    use crate::network::InMemoryNetwork;
+    use tokio::sync::RwLock;
+    use tokio::task::JoinHandle;
+    use tokio::time::Instant;
+
+    fn engine(node_state: &Arc<RwLock<NodeState>>) -> Arc<ConsensusEngine> {
+        let transport = InMemoryNetwork::new().transport(1, node_state.clone());
+        Arc::new(ConsensusEngine::new(node_state.clone(), Arc::new(transport)))
+    }
+
+    /// Record when each value is proposed on `engine`, until it is dropped
+    fn proposal_times(engine: &ConsensusEngine) -> JoinHandle<Vec<(ConsensusValue, Instant)>> {
+        let mut events = engine.subscribe();
+        tokio::spawn(async move {
+            let mut times = Vec::new();
+            while let Ok(event) = events.recv().await {
+                if let ConsensusEvent::ValueProposed { value } = event {
+                    times.push((value, Instant::now()));
+                }
+            }
+            times
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_each_value_proposed_once_at_cadence() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let engine = engine(&node_state);
+        let times = proposal_times(&engine);
+
+        let cadence = Duration::from_millis(30);
+        let source: &[u8] = b"alpha\nbeta\n\ngamma\n";
+
+        let proposed = AutoProposer::new(engine, source)
+            .with_interval(cadence)
+            .with_max_in_flight(8)
+            .run()
+            .await
+            .unwrap();
+
+        let expected: Vec<ConsensusValue> = ["alpha", "beta", "gamma"]
+            .iter()
+            .map(|v| ConsensusValue::from_string(v))
+            .collect();
+        assert_eq!(proposed, expected);
+
+        // One proposal per interval, never two in a burst
+        let times = times.await.unwrap();
+        assert_eq!(times.iter().map(|(value, _)| value.clone()).collect::<Vec<_>>(), expected);
+        for pair in times.windows(2) {
+            assert!(pair[1].1 - pair[0].1 >= cadence, "proposed {:?} apart", pair[1].1 - pair[0].1);
+        }
+
+        let state = node_state.read().await;
+        assert_eq!(state.stats.pheromones_emitted, 3);
+        for value in &expected {
+            assert_eq!(state.pheromones[value].len(), 1);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_proposal_waits_for_in_flight_cap() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let engine = engine(&node_state);
+        let times = proposal_times(&engine);
+
+        let cadence = Duration::from_millis(30);
+        let source: &[u8] = b"first\nsecond\n";
+        let proposer = tokio::spawn(
+            AutoProposer::new(engine, source)
+                .with_interval(cadence)
+                .with_max_in_flight(1)
+                .run(),
+        );
+
+        // The first proposal's trail is alive and unsettled, so the second waits
+        sleep(cadence * 10).await;
+        assert_eq!(node_state.read().await.stats.pheromones_emitted, 1);
+
+        // Once the first trail evaporates, the second goes out
+        let first = ConsensusValue::from_string("first");
+        let released = Instant::now();
+        node_state.write().await.pheromones.remove(&first);
+
+        let proposed = proposer.await.unwrap().unwrap();
+        assert_eq!(proposed, vec![first, ConsensusValue::from_string("second")]);
+        let times = times.await.unwrap();
+        assert_eq!(times.len(), 2);
+        assert!(times[1].1 >= released);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::core::types::ConsensusValue;
+use std::collections::VecDeque;
+
+/// Default number of recent steps used to estimate the convergence rate
+pub const DEFAULT_CONVERGENCE_WINDOW: usize = 20;
+
+/// Consecutive steps a node's value must hold before the cluster can be
+/// considered converged
+pub const DEFAULT_STABLE_STEPS: u64 = 5;
+
+/// Tracks consensus confidence over a sliding window of steps and
+/// estimates how fast it is rising, along with how long the node's value
+/// has held
+#[derive(Debug, Clone)]
+pub struct ConvergenceTracker {
+    samples: VecDeque<f64>,
+    window: usize,
+    held: Option<ConsensusValue>,
+    stable_steps: u64,
+}
+
+impl ConvergenceTracker {
+    /// Create a tracker over the last `window` steps
+    pub fn new(window: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(window),
+            window: window.max(2),
+            held: None,
+            stable_steps: 0,
+        }
+    }
+
+    /// Record the value the node held at a step
+    pub fn record_value(&mut self, value: Option<&ConsensusValue>) {
+        if self.held.as_ref() == value {
+            self.stable_steps += 1;
+        } else {
+            self.held = value.cloned();
+            self.stable_steps = 0;
+        }
+    }
+
+    /// Value held at the last recorded step and for how many steps
+    /// since it last changed
+    pub fn stable(&self) -> (Option<&ConsensusValue>, u64) {
+        (self.held.as_ref(), self.stable_steps)
+    }
+
+    /// Record the confidence observed at a step
+    pub fn record(&mut self, confidence: f64) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(confidence);
+    }
+
+    /// Estimated change in confidence per step (least-squares slope)
+    ///
+    /// Positive while the network converges, ~0 when it is stuck or settled.
+    pub fn rate(&self) -> f64 {
+        let n = self.samples.len();
+        if n < 2 {
+            return 0.0;
+        }
+
+        let mean_x = (n - 1) as f64 / 2.0;
+        let mean_y = self.samples.iter().sum::<f64>() / n as f64;
+
+        let mut covariance = 0.0;
+        let mut variance = 0.0;
+        for (i, y) in self.samples.iter().enumerate() {
+            let dx = i as f64 - mean_x;
+            covariance += dx * (y - mean_y);
+            variance += dx * dx;
+        }
+
+        covariance / variance
+    }
+}
+
+impl Default for ConvergenceTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_CONVERGENCE_WINDOW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rising_confidence_has_positive_rate() {
+        let mut tracker = ConvergenceTracker::new(10);
+        for step in 0..10 {
+            tracker.record(step as f64 * 0.05);
+        }
+
+        assert!((tracker.rate() - 0.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_stability_resets_when_value_changes() {
+        let mut tracker = ConvergenceTracker::default();
+        let (a, b) = (ConsensusValue::from_string("a"), ConsensusValue::from_string("b"));
+        tracker.record_value(None);
+        tracker.record_value(Some(&a));
+        tracker.record_value(Some(&a));
+        tracker.record_value(Some(&a));
+        assert_eq!(tracker.stable(), (Some(&a), 2));
+
+        tracker.record_value(Some(&b));
+        assert_eq!(tracker.stable(), (Some(&b), 0));
+    }
+
+    #[test]
+    fn test_flat_confidence_has_zero_rate() {
+        let mut tracker = ConvergenceTracker::new(10);
+        for _ in 0..15 {
+            tracker.record(0.4);
+        }
+
+        assert!(tracker.rate().abs() < 1e-9);
+    }
+}
@@ -1,59 +1,507 @@
-use crate::core::node_state::SharedNodeState;
-use crate::core::types::ConsensusValue;
+use crate::core::config::ConsensusConfig;
+use crate::core::events::NodeEvent;
+use crate::core::node_state::{NodeStats, SharedNodeState};
+use crate::core::types::{ConsensusError, ConsensusValue, NodeId, RoundId, DEFAULT_ROUND};
 use crate::consensus::ant_colony::AntColonyConsensus;
-use crate::network::NetworkManager;
-use tokio::time::{interval, Duration};
-use tracing::{info, error};
+use crate::consensus::convergence::{ConvergenceTracker, DEFAULT_STABLE_STEPS};
+use crate::consensus::scheduler::StepScheduler;
+use crate::core::pheromone::Pheromone;
+use crate::core::validator::ValueValidator;
+use crate::network::{NoopTransport, Transport};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tokio::time::{interval, sleep, Duration, Instant};
+use tracing::{error, info, info_span, Instrument, Span};
+
+/// How often the engine prunes neighbors that have gone silent
+const NEIGHBOR_PRUNE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of events buffered for each subscriber before the slowest one
+/// starts missing them
+pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Progress notifications published by a `ConsensusEngine`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsensusEvent {
+    /// This node proposed a value
+    ValueProposed { value: ConsensusValue },
+
+    /// This node reached consensus on a new value
+    ConsensusReached { value: ConsensusValue },
+
+    /// This node's consensus value became final and will no longer change
+    Committed { value: ConsensusValue },
+
+    /// A node became a neighbor
+    NeighborAdded { node_id: NodeId },
+
+    /// A pheromone was accepted from the network
+    PheromoneReceived { value: ConsensusValue, source: NodeId },
+
+    /// Any other node event (intensity anomalies, dissent)
+    Node(NodeEvent),
+}
+
+impl From<NodeEvent> for ConsensusEvent {
+    fn from(event: NodeEvent) -> Self {
+        match event {
+            NodeEvent::Committed { value } => ConsensusEvent::Committed { value },
+            NodeEvent::NeighborAdded { node_id } => ConsensusEvent::NeighborAdded { node_id },
+            NodeEvent::PheromoneReceived { value, source } => {
+                ConsensusEvent::PheromoneReceived { value, source }
+            }
+            other => ConsensusEvent::Node(other),
+        }
+    }
+}
+
+/// How a `run_job` ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobOutcome {
+    /// The proposed value became the consensus
+    Reached,
+
+    /// No consensus was reached before the timeout
+    Timeout,
+
+    /// The node settled on a different value
+    Conflict { value: ConsensusValue },
+}
+
+/// Result and telemetry of a `run_job`
+#[derive(Debug, Clone)]
+pub struct JobResult {
+    /// Caller-supplied key identifying the job
+    pub key: String,
+
+    /// The proposed value
+    pub value: ConsensusValue,
+
+    /// How the job ended
+    pub outcome: JobOutcome,
+
+    /// Consensus confidence when the job ended
+    pub confidence: f64,
+
+    /// Engine steps run during the job
+    pub steps: u64,
+
+    /// Messages this node sent during the job
+    pub messages_sent: u64,
+
+    /// Messages this node received during the job
+    pub messages_received: u64,
+
+    /// Sources backing the proposed value when the job ended
+    pub supporting_sources: HashSet<NodeId>,
+}
 
 /// Consensus engine - main coordinator for consensus operations
 pub struct ConsensusEngine {
     ant_colony: AntColonyConsensus,
     node_state: SharedNodeState,
+    convergence: Mutex<ConvergenceTracker>,
+    scheduler: Mutex<StepScheduler>,
+    steps: AtomicU64,
+    events: broadcast::Sender<ConsensusEvent>,
 }
 
 impl ConsensusEngine {
     /// Create a new consensus engine
-    pub fn new(node_state: SharedNodeState, network: NetworkManager) -> Self {
+    pub fn new(node_state: SharedNodeState, network: Arc<dyn Transport>) -> Self {
         let ant_colony = AntColonyConsensus::new(node_state.clone(), network);
-        
+        Self::with_colony(ant_colony, node_state)
+    }
+
+    /// Create an engine that never touches the network, for driving a
+    /// node's decision logic from tests
+    ///
+    /// Messages go to a `NoopTransport` and the node draws all randomness
+    /// from `seed`. Feed it with `inject_pheromone` and advance it with
+    /// `step_once`.
+    pub async fn dry_run(node_state: SharedNodeState, seed: u64) -> Self {
+        let node_id = node_state.read().await.id;
+        let network = Arc::new(NoopTransport::new(node_id));
+        let ant_colony = AntColonyConsensus::with_seed(node_state.clone(), network, seed).await;
+        Self::with_colony(ant_colony, node_state)
+    }
+
+    fn with_colony(ant_colony: AntColonyConsensus, node_state: SharedNodeState) -> Self {
         Self {
             ant_colony,
             node_state,
+            convergence: Mutex::new(ConvergenceTracker::default()),
+            scheduler: Mutex::new(StepScheduler::new()),
+            steps: AtomicU64::new(0),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
         }
     }
 
+    /// Create a new consensus engine, applying `config` to the node state
+    pub async fn with_config(
+        node_state: SharedNodeState,
+        network: Arc<dyn Transport>,
+        config: ConsensusConfig,
+    ) -> Self {
+        node_state.write().await.config = config;
+        Self::new(node_state, network)
+    }
+
     /// Run the consensus engine
+    ///
+    /// Steps are spaced by `step_interval`, which adapts to activity when
+    /// `adaptive_step_interval` is configured.
     pub async fn run(&self) -> Result<(), String> {
         info!("Consensus engine started");
 
-        let mut interval = interval(Duration::from_millis(100)); // 10 steps per second
+        let mut prune_interval = interval(NEIGHBOR_PRUNE_INTERVAL);
+        let next_step = sleep(Duration::ZERO);
+        tokio::pin!(next_step);
 
         loop {
-            interval.tick().await;
-
-            match self.ant_colony.step().await {
-                Ok(Some(value)) => {
-                    info!("🎉 Consensus reached: {}", value);
-                    
-                    // Update node state with consensus value
-                    {
-                        let mut state = self.node_state.write().await;
-                        state.current_value = Some(value);
+            tokio::select! {
+                _ = &mut next_step => {}
+                _ = prune_interval.tick() => {
+                    let mut state = self.node_state.write().await;
+                    let timeout_secs = state.config.neighbor_timeout_secs;
+                    for neighbor in state.prune_stale_neighbors(timeout_secs) {
+                        info!(neighbor, "Pruned stale neighbor");
                     }
+                    continue;
                 }
-                Ok(None) => {
-                    // No consensus yet, continue
+            }
+
+            if let Err(e) = self.step().await {
+                error!(error = %e, "Consensus step error");
+            }
+            let delay = self.step_interval().await;
+            next_step.as_mut().reset(Instant::now() + delay);
+        }
+    }
+
+    /// Run a single consensus step and record the resulting confidence
+    ///
+    /// The step runs in a `consensus_step` span carrying the `node_id` and
+    /// the engine's `step` count.
+    pub async fn step(&self) -> Result<Option<ConsensusValue>, String> {
+        let (previous, span) = self.begin_step().await;
+        async {
+            let result = self.ant_colony.step().await;
+            self.record_step(previous, result).await
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// Run a single deterministic step, returning the default round's
+    /// decision, if any
+    ///
+    /// Unlike `step`, pheromones evaporate by exactly the configured
+    /// `step_interval_ms` rather than by the time since the last step, so
+    /// with a seeded node (see `dry_run`) the same inputs decide at the
+    /// same step on every run.
+    pub async fn step_once(&self) -> Result<Option<ConsensusValue>, String> {
+        let (previous, span) = self.begin_step().await;
+        let elapsed = self.node_state.read().await.config.step_interval();
+        async {
+            let result = self.ant_colony.step_elapsed(elapsed).await;
+            self.record_step(previous, result).await
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// Value held before a step, and the span the step runs in
+    async fn begin_step(&self) -> (Option<ConsensusValue>, Span) {
+        let state = self.node_state.read().await;
+        let span = info_span!("consensus_step", node_id = state.id, step = self.steps() + 1);
+        (state.current_value.clone(), span)
+    }
+
+    /// Record a finished step's confidence, events and activity
+    async fn record_step(
+        &self,
+        previous: Option<ConsensusValue>,
+        result: Result<Option<ConsensusValue>, String>,
+    ) -> Result<Option<ConsensusValue>, String> {
+        self.steps.fetch_add(1, Ordering::Relaxed);
+
+        let mut state = self.node_state.write().await;
+        if let Ok(Some(value)) = &result {
+            // Update node state with consensus value
+            state.current_value = Some(value.clone());
+        }
+
+        {
+            let mut convergence = self.convergence.lock().unwrap_or_else(|e| e.into_inner());
+            convergence.record(state.consensus_confidence());
+            convergence.record_value(state.settled_value());
+        }
+
+        // Publish what the network did to this node since the last step
+        for event in state.drain_events() {
+            self.publish(event.into());
+        }
+
+        let activity = state.stats.pheromones_received
+            + state.stats.pheromones_emitted
+            + state.stats.messages_received;
+        self.scheduler
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .record(&state.config, activity, !state.ants.is_empty());
+
+        if let Ok(Some(value)) = &result {
+            if previous.as_ref() != Some(value) {
+                info!(value = %value, "🎉 Consensus reached");
+                self.publish(ConsensusEvent::ConsensusReached { value: value.clone() });
+            }
+        }
+
+        result
+    }
+
+    /// Delay before the next step: the fixed `step_interval_ms`, or with
+    /// `adaptive_step_interval` set, an interval that shrinks while
+    /// pheromones arrive or ants move and grows while the node is idle
+    pub async fn step_interval(&self) -> Duration {
+        let state = self.node_state.read().await;
+        self.scheduler
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .interval(&state.config)
+    }
+
+    /// Step at `step_interval` until consensus is reached, returning
+    /// `None` if `timeout` elapses first
+    pub async fn run_until_consensus(&self, timeout: Duration) -> Result<Option<ConsensusValue>, String> {
+        let stepping = async {
+            loop {
+                if let Some(value) = self.step().await? {
+                    return Ok(value);
                 }
-                Err(e) => {
-                    error!("Consensus step error: {}", e);
+                sleep(self.step_interval().await).await;
+            }
+        };
+
+        match tokio::time::timeout(timeout, stepping).await {
+            Ok(result) => result.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Step at `step_interval` until `is_converged`, returning the value
+    /// the cluster converged on, or `None` if `timeout` elapses first
+    pub async fn run_until_converged(&self, timeout: Duration) -> Result<Option<ConsensusValue>, String> {
+        let stepping = async {
+            loop {
+                self.step().await?;
+                if self.is_converged().await {
+                    return Ok(self.node_state.read().await.settled_value().cloned());
                 }
+                sleep(self.step_interval().await).await;
             }
+        };
+
+        match tokio::time::timeout(timeout, stepping).await {
+            Ok(result) => result,
+            Err(_) => Ok(None),
         }
     }
 
+    /// Whether the cluster looks converged from this node: the node's
+    /// settled value (committed, or else current) has held for
+    /// `DEFAULT_STABLE_STEPS` steps and every neighbor recently announced
+    /// consensus on the same value
+    pub async fn is_converged(&self) -> bool {
+        let state = self.node_state.read().await;
+        let Some(value) = state.settled_value() else {
+            return false;
+        };
+
+        let (held, stable_steps) = {
+            let convergence = self.convergence.lock().unwrap_or_else(|e| e.into_inner());
+            let (held, stable_steps) = convergence.stable();
+            (held.cloned(), stable_steps)
+        };
+
+        held.as_ref() == Some(value)
+            && stable_steps >= DEFAULT_STABLE_STEPS
+            && state.agreeing_neighbors(value) == state.neighbors.len()
+    }
+
+    /// Propose `value` and step until consensus or `timeout`, reporting the
+    /// outcome along with telemetry sampled over the job
+    pub async fn run_job(
+        &self,
+        value: ConsensusValue,
+        key: impl Into<String>,
+        timeout: Duration,
+    ) -> Result<JobResult, String> {
+        let (sent_before, received_before) = {
+            let state = self.node_state.read().await;
+            (state.stats.messages_sent, state.stats.messages_received)
+        };
+        let steps_before = self.steps();
+
+        self.propose(value.clone()).await?;
+        let outcome = match self.run_until_consensus(timeout).await? {
+            Some(reached) if reached == value => JobOutcome::Reached,
+            Some(other) => JobOutcome::Conflict { value: other },
+            None => JobOutcome::Timeout,
+        };
+
+        let state = self.node_state.read().await;
+        Ok(JobResult {
+            key: key.into(),
+            outcome,
+            confidence: state.consensus_confidence(),
+            steps: self.steps() - steps_before,
+            messages_sent: state.stats.messages_sent - sent_before,
+            messages_received: state.stats.messages_received - received_before,
+            supporting_sources: state.supporting_sources(&value),
+            value,
+        })
+    }
+
+    /// Propose `value` and wait until this node reaches consensus on it
+    ///
+    /// The engine must be running (see `run`) for consensus to progress.
+    /// Returns `ConsensusError::Timeout` if `timeout` elapses first; nothing
+    /// is left running in that case, the proposal simply stays in the
+    /// network. `key` labels the request in logs.
+    pub async fn propose_and_wait(
+        &self,
+        value: ConsensusValue,
+        key: &str,
+        timeout: Duration,
+    ) -> crate::core::types::Result<ConsensusValue> {
+        // Subscribe first so a fast consensus can't slip past unseen
+        let mut events = self.subscribe();
+        self.propose(value.clone()).await.map_err(ConsensusError::Network)?;
+
+        if self.get_consensus().await.as_ref() == Some(&value) {
+            return Ok(value);
+        }
+
+        let reached = tokio::time::timeout(timeout, async {
+            loop {
+                match events.recv().await {
+                    Ok(ConsensusEvent::ConsensusReached { value: reached }) if reached == value => {
+                        return Ok(reached);
+                    }
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => {
+                        return Err(ConsensusError::Internal("Event channel closed".to_string()));
+                    }
+                }
+            }
+        })
+        .await;
+
+        match reached {
+            Ok(result) => {
+                if result.is_ok() {
+                    info!(key = %key, "Proposal reached consensus");
+                }
+                result
+            }
+            Err(_) => {
+                info!(key = %key, timeout = ?timeout, "Proposal timed out");
+                Err(ConsensusError::Timeout)
+            }
+        }
+    }
+
+    /// Total consensus steps this engine has run
+    pub fn steps(&self) -> u64 {
+        self.steps.load(Ordering::Relaxed)
+    }
+
     /// Propose a value for consensus
-    pub async fn propose(&self, value: ConsensusValue, private_key: &[u8]) -> Result<(), String> {
-        self.ant_colony.propose_value(value, private_key).await
+    pub async fn propose(&self, value: ConsensusValue) -> Result<(), String> {
+        self.propose_with_priority(value, 0).await
+    }
+
+    /// Propose a value with a priority that can preempt lower-priority values
+    pub async fn propose_with_priority(&self, value: ConsensusValue, priority: u8) -> Result<(), String> {
+        self.ant_colony.propose_value_with_priority(DEFAULT_ROUND, value.clone(), priority).await?;
+        self.publish(ConsensusEvent::ValueProposed { value });
+        Ok(())
+    }
+
+    /// Propose a value in an independent consensus round; its outcome is
+    /// read with `get_round_consensus`
+    pub async fn propose_in_round(&self, round: RoundId, value: ConsensusValue) -> Result<(), String> {
+        self.ant_colony.propose_value(round, value.clone()).await?;
+        self.publish(ConsensusEvent::ValueProposed { value });
+        Ok(())
+    }
+
+    /// Check the payload of proposed and received values with `validator`,
+    /// refusing proposals and dropping pheromones whose payload fails it
+    pub async fn set_value_validator(&self, validator: ValueValidator) {
+        self.node_state.write().await.value_validator = Some(validator);
+    }
+
+    /// Store `pheromone` as if it had been received, optionally via
+    /// `neighbor`, returning whether it was accepted; see
+    /// `AntColonyConsensus::inject_pheromone`
+    pub async fn inject_pheromone(&self, pheromone: Pheromone, via: Option<NodeId>) -> bool {
+        self.ant_colony.inject_pheromone(pheromone, via).await
+    }
+
+    /// Withdraw this node's support for a value it proposed
+    ///
+    /// Peers drop the pheromones this node signed for the value, so it only
+    /// reaches consensus if enough other nodes still back it.
+    pub async fn withdraw(&self, value: ConsensusValue) -> Result<(), String> {
+        self.withdraw_from_round(DEFAULT_ROUND, value).await
+    }
+
+    /// Withdraw this node's support for a value proposed in `round`
+    pub async fn withdraw_from_round(&self, round: RoundId, value: ConsensusValue) -> Result<(), String> {
+        self.ant_colony.withdraw_value(round, value).await
+    }
+
+    /// Receive events published from now on
+    ///
+    /// Events are delivered to every subscriber; one that falls more than
+    /// `EVENT_CHANNEL_CAPACITY` events behind skips the oldest ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<ConsensusEvent> {
+        self.events.subscribe()
+    }
+
+    /// Send an event to current subscribers, if any
+    fn publish(&self, event: ConsensusEvent) {
+        let _ = self.events.send(event);
+    }
+
+    /// Rate at which consensus confidence is rising per step, estimated over
+    /// a sliding window of recent steps (~0 when stuck or settled)
+    pub fn convergence_rate(&self) -> f64 {
+        self.convergence
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .rate()
+    }
+
+    /// How close `value` is to consensus in the default round, in
+    /// [0.0, 1.0], e.g. for a progress bar
+    pub async fn consensus_progress(&self, value: &ConsensusValue) -> f64 {
+        self.node_state.read().await.consensus_progress(value)
+    }
+
+    /// Copy of the node's counters, e.g. for `NodeStats::to_json`
+    pub async fn stats_snapshot(&self) -> NodeStats {
+        self.node_state.read().await.stats.clone()
+    }
+
+    /// Get the node state the engine drives
+    pub fn node_state(&self) -> &SharedNodeState {
+        &self.node_state
     }
 
     /// Get current consensus value (if any)
@@ -61,5 +509,11 @@ impl ConsensusEngine {
         let state = self.node_state.read().await;
         state.current_value.clone()
     }
+
+    /// Get the consensus value reached in `round` (if any)
+    pub async fn get_round_consensus(&self, round: RoundId) -> Option<ConsensusValue> {
+        let state = self.node_state.read().await;
+        state.round_value(round).cloned()
+    }
 }
 
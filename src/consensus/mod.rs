@@ -1,6 +1,15 @@
 pub mod engine;
 pub mod ant_colony;
+pub mod quick_node;
+pub mod convergence;
+pub mod auto_proposer;
+pub mod simulation;
+pub mod scheduler;
 
-pub use engine::ConsensusEngine;
-pub use ant_colony::AntColonyConsensus;
-
+pub use engine::{ConsensusEngine, ConsensusEvent, JobOutcome, JobResult};
+pub use ant_colony::{AntColonyConsensus, StepReport};
+pub use quick_node::{QuickNode, QuickNodeConfig};
+pub use convergence::ConvergenceTracker;
+pub use auto_proposer::AutoProposer;
+pub use simulation::{Simulation, SimNode};
+pub use scheduler::StepScheduler;
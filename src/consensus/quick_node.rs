@@ -0,0 +1,163 @@
+use crate::consensus::engine::ConsensusEngine;
+use crate::core::node_state::{NodeState, SharedNodeState};
+use crate::core::types::{ConsensusError, ConsensusValue, NodeId, Result};
+use crate::network::NetworkManager;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout, Duration};
+use tracing::error;
+
+/// How often `await_consensus` polls the node state
+const CONSENSUS_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Configuration for a `QuickNode`
+#[derive(Debug, Clone)]
+pub struct QuickNodeConfig {
+    /// Node ID (unique identifier)
+    pub node_id: NodeId,
+
+    /// Multicast group to join
+    pub multicast_addr: SocketAddr,
+
+    /// Port for receiving messages (0 picks a free port)
+    pub port: u16,
+}
+
+impl QuickNodeConfig {
+    /// Create a config for `node_id` with the default multicast settings
+    pub fn new(node_id: NodeId) -> Self {
+        Self {
+            node_id,
+            ..Self::default()
+        }
+    }
+
+    /// Set the multicast group
+    pub fn with_multicast_addr(mut self, multicast_addr: SocketAddr) -> Self {
+        self.multicast_addr = multicast_addr;
+        self
+    }
+
+    /// Set the receive port
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+}
+
+impl Default for QuickNodeConfig {
+    fn default() -> Self {
+        Self {
+            node_id: 1,
+            multicast_addr: SocketAddr::from(([239, 255, 0, 1], 5000)),
+            port: 5000,
+        }
+    }
+}
+
+/// A fully wired node (state, network and consensus engine) running in
+/// background tasks, for embedders that just want to propose and wait
+pub struct QuickNode {
+    node_state: SharedNodeState,
+    network: NetworkManager,
+    engine: Arc<ConsensusEngine>,
+    engine_handle: JoinHandle<()>,
+}
+
+impl QuickNode {
+    /// Create and start a node
+    pub async fn start(config: QuickNodeConfig) -> std::result::Result<Self, String> {
+        let node_state = Arc::new(RwLock::new(NodeState::new(config.node_id)));
+
+        let network = NetworkManager::new(
+            config.multicast_addr,
+            config.port,
+            node_state.clone(),
+        ).await?;
+        network.start().await?;
+
+        let engine = Arc::new(ConsensusEngine::new(node_state.clone(), Arc::new(network.clone())));
+
+        let engine_task = engine.clone();
+        let engine_handle = tokio::spawn(async move {
+            if let Err(e) = engine_task.run().await {
+                error!("Consensus error: {}", e);
+            }
+        });
+
+        Ok(Self {
+            node_state,
+            network,
+            engine,
+            engine_handle,
+        })
+    }
+
+    /// Propose a value for consensus
+    pub async fn propose(&self, value: ConsensusValue) -> std::result::Result<(), String> {
+        self.engine.propose(value).await
+    }
+
+    /// Wait until the node reaches consensus, or fail with
+    /// `ConsensusError::Timeout` once `limit` elapses
+    pub async fn await_consensus(&self, limit: Duration) -> Result<ConsensusValue> {
+        timeout(limit, async {
+            loop {
+                if let Some(value) = self.engine.get_consensus().await {
+                    return value;
+                }
+                sleep(CONSENSUS_POLL_INTERVAL).await;
+            }
+        })
+        .await
+        .map_err(|_| ConsensusError::Timeout)
+    }
+
+    /// Get the node's shared state
+    pub fn node_state(&self) -> &SharedNodeState {
+        &self.node_state
+    }
+
+    /// Get the node's network manager
+    pub fn network(&self) -> &NetworkManager {
+        &self.network
+    }
+
+    /// Get the node's consensus engine
+    pub fn engine(&self) -> &Arc<ConsensusEngine> {
+        &self.engine
+    }
+}
+
+impl Drop for QuickNode {
+    fn drop(&mut self) {
+        self.engine_handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_quick_node_reaches_consensus() {
+        let node = QuickNode::start(QuickNodeConfig::new(1).with_port(0)).await.unwrap();
+        let value = ConsensusValue::from_string("quick");
+
+        node.propose(value.clone()).await.unwrap();
+        let reached = node.await_consensus(Duration::from_secs(5)).await.unwrap();
+
+        assert_eq!(reached, value);
+        assert_eq!(node.node_state().read().await.current_value, Some(value));
+    }
+
+    #[tokio::test]
+    async fn test_quick_node_times_out_without_proposal() {
+        let node = QuickNode::start(QuickNodeConfig::new(2).with_port(0)).await.unwrap();
+
+        let result = node.await_consensus(Duration::from_millis(200)).await;
+        assert!(matches!(result, Err(ConsensusError::Timeout)));
+    }
+}
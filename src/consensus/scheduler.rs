@@ -0,0 +1,105 @@
+use crate::core::config::ConsensusConfig;
+use std::time::Duration;
+
+/// Factor the step interval grows by after each idle step
+pub const STEP_INTERVAL_BACKOFF: u32 = 2;
+
+/// Chooses the delay before the engine's next step
+///
+/// With `adaptive_step_interval` configured, any activity since the
+/// previous step (pheromones or messages arriving, pheromones emitted, ants
+/// still moving) drops the interval to the minimum so bursts are handled
+/// promptly, and each idle step multiplies it by `STEP_INTERVAL_BACKOFF` up
+/// to the maximum. Otherwise the fixed `step_interval_ms` is used.
+#[derive(Debug, Clone, Default)]
+pub struct StepScheduler {
+    current: Option<Duration>,
+    last_activity: u64,
+}
+
+impl StepScheduler {
+    /// Create a scheduler that starts at the configured minimum
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the node's activity after a step and return the delay before
+    /// the next one
+    ///
+    /// `activity` is a counter that rises whenever something happens at the
+    /// node; only changes since the previous call matter.
+    pub fn record(&mut self, config: &ConsensusConfig, activity: u64, ants_moving: bool) -> Duration {
+        let active = activity != self.last_activity || ants_moving;
+        self.last_activity = activity;
+
+        let Some(bounds) = config.adaptive_step_interval else {
+            self.current = None;
+            return config.step_interval();
+        };
+
+        let next = match self.current {
+            Some(current) if !active => (current * STEP_INTERVAL_BACKOFF).clamp(bounds.min(), bounds.max()),
+            _ => bounds.min(),
+        };
+        self.current = Some(next);
+        next
+    }
+
+    /// Delay chosen by the last `record`, or the configured starting
+    /// interval if there hasn't been one
+    pub fn interval(&self, config: &ConsensusConfig) -> Duration {
+        match (self.current, config.adaptive_step_interval) {
+            (Some(current), Some(_)) => current,
+            (None, Some(bounds)) => bounds.min(),
+            (_, None) => config.step_interval(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::StepIntervalBounds;
+
+    fn adaptive() -> ConsensusConfig {
+        ConsensusConfig {
+            adaptive_step_interval: Some(StepIntervalBounds { min_ms: 10, max_ms: 100 }),
+            ..ConsensusConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_idle_steps_back_off_to_max() {
+        let config = adaptive();
+        let mut scheduler = StepScheduler::new();
+        assert_eq!(scheduler.interval(&config), Duration::from_millis(10));
+
+        let delays: Vec<u64> = (0..6)
+            .map(|_| scheduler.record(&config, 0, false).as_millis() as u64)
+            .collect();
+        assert_eq!(delays, [10, 20, 40, 80, 100, 100]);
+    }
+
+    #[test]
+    fn test_activity_resets_to_min() {
+        let config = adaptive();
+        let mut scheduler = StepScheduler::new();
+        for _ in 0..5 {
+            scheduler.record(&config, 0, false);
+        }
+        assert_eq!(scheduler.interval(&config), Duration::from_millis(100));
+
+        assert_eq!(scheduler.record(&config, 3, false), Duration::from_millis(10));
+        assert_eq!(scheduler.record(&config, 3, false), Duration::from_millis(20));
+        assert_eq!(scheduler.record(&config, 3, true), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_fixed_interval_without_bounds() {
+        let config = ConsensusConfig { step_interval_ms: 50, ..ConsensusConfig::default() };
+        let mut scheduler = StepScheduler::new();
+        assert_eq!(scheduler.record(&config, 0, false), Duration::from_millis(50));
+        assert_eq!(scheduler.record(&config, 9, true), Duration::from_millis(50));
+        assert_eq!(scheduler.interval(&config), Duration::from_millis(50));
+    }
+}
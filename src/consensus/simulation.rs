@@ -0,0 +1,279 @@
+use crate::consensus::engine::ConsensusEngine;
+use crate::core::config::ConsensusConfig;
+use crate::core::node_state::{NodeState, SharedNodeState};
+use crate::core::types::NodeId;
+use crate::network::{InMemoryNetwork, Transport};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// A node in a `Simulation`
+pub struct SimNode {
+    /// The node's shared state
+    pub state: SharedNodeState,
+
+    /// The node's consensus engine, stepped by the simulation
+    pub engine: Arc<ConsensusEngine>,
+}
+
+/// In-process cluster of nodes connected by an `InMemoryNetwork`
+///
+/// Nodes know each other's keys from the start, but only become neighbors
+/// through `connect`, so tests can build a specific topology. Engines are
+/// not run in the background; `step` advances every node once.
+pub struct Simulation {
+    network: InMemoryNetwork,
+    nodes: BTreeMap<NodeId, SimNode>,
+}
+
+impl Simulation {
+    /// Create `node_count` nodes with IDs `1..=node_count`
+    pub async fn new(node_count: usize, config: ConsensusConfig) -> Result<Self, String> {
+        let network = InMemoryNetwork::new();
+        let mut nodes = BTreeMap::new();
+
+        for id in 1..=node_count as NodeId {
+            let state = Arc::new(RwLock::new(NodeState::with_config(id, config.clone())));
+            let transport = network.transport(id, state.clone());
+            transport.start().await?;
+
+            let engine = Arc::new(ConsensusEngine::new(state.clone(), Arc::new(transport)));
+            nodes.insert(id, SimNode { state, engine });
+        }
+
+        // Every node can verify every other node's pheromones
+        for node in nodes.values() {
+            let (id, key) = {
+                let state = node.state.read().await;
+                (state.id, state.public_key())
+            };
+            for other in nodes.values() {
+                other.state.write().await.register_public_key(id, key.clone());
+            }
+        }
+
+        Ok(Self { network, nodes })
+    }
+
+    /// The in-memory network connecting the nodes
+    pub fn network(&self) -> &InMemoryNetwork {
+        &self.network
+    }
+
+    /// IDs of all nodes, in ascending order
+    pub fn node_ids(&self) -> Vec<NodeId> {
+        self.nodes.keys().copied().collect()
+    }
+
+    /// Get a node by ID
+    pub fn node(&self, id: NodeId) -> Option<&SimNode> {
+        self.nodes.get(&id)
+    }
+
+    /// Make `a` and `b` neighbors of each other
+    pub async fn connect(&self, a: NodeId, b: NodeId) -> Result<(), String> {
+        let (node_a, node_b) = (self.require(a)?, self.require(b)?);
+        node_a.state.write().await.add_neighbor(b);
+        node_b.state.write().await.add_neighbor(a);
+        Ok(())
+    }
+
+    /// Run one consensus step on every node
+    pub async fn step(&self) -> Result<(), String> {
+        for node in self.nodes.values() {
+            node.engine.step().await?;
+        }
+        Ok(())
+    }
+
+    /// Jaccard similarity of the neighbor sets of nodes `a` and `b`
+    pub async fn neighbor_overlap(&self, a: NodeId, b: NodeId) -> Result<f64, String> {
+        let (node_a, node_b) = (self.require(a)?, self.require(b)?);
+        let other_neighbors = node_b.state.read().await.neighbors.clone();
+        let overlap = node_a.state.read().await.neighbor_jaccard(&other_neighbors);
+        Ok(overlap)
+    }
+
+    fn require(&self, id: NodeId) -> Result<&SimNode, String> {
+        self.nodes.get(&id).ok_or_else(|| format!("Unknown simulation node {}", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::engine::JobOutcome;
+    use crate::core::config::StepIntervalBounds;
+    use crate::core::types::{ConsensusError, ConsensusValue};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_neighbor_overlap() {
+        let sim = Simulation::new(4, ConsensusConfig::default()).await.unwrap();
+        for (a, b) in [(1, 2), (1, 3), (2, 3), (2, 4)] {
+            sim.connect(a, b).await.unwrap();
+        }
+
+        // N(1) = {2,3}, N(2) = {1,3,4}: intersection {3}, union {1,2,3,4}
+        assert!((sim.neighbor_overlap(1, 2).await.unwrap() - 0.25).abs() < 1e-12);
+        // N(3) = {1,2}, N(4) = {2}: intersection {2}, union {1,2}
+        assert!((sim.neighbor_overlap(3, 4).await.unwrap() - 0.5).abs() < 1e-12);
+        assert!(sim.neighbor_overlap(1, 9).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_propose_and_wait_times_out_without_quorum() {
+        let config = ConsensusConfig { step_interval_ms: 10, min_quorum: 2, ..ConsensusConfig::default() };
+        let sim = Simulation::new(1, config).await.unwrap();
+        let engine = sim.node(1).unwrap().engine.clone();
+
+        let runner = engine.clone();
+        let running = tokio::spawn(async move { runner.run().await });
+
+        let value = ConsensusValue::from_string("lonely");
+        let result = engine.propose_and_wait(value, "lonely-1", Duration::from_millis(200)).await;
+        assert!(matches!(result, Err(ConsensusError::Timeout)));
+        assert_eq!(engine.get_consensus().await, None);
+
+        running.abort();
+    }
+
+    #[tokio::test]
+    async fn test_run_job_reaches_consensus() {
+        let config = ConsensusConfig { step_interval_ms: 10, min_quorum: 2, ..ConsensusConfig::default() };
+        let sim = Simulation::new(3, config).await.unwrap();
+        for (a, b) in [(1, 2), (1, 3), (2, 3)] {
+            sim.connect(a, b).await.unwrap();
+        }
+
+        // Node 2 backs the same value, so node 1 can reach the quorum of two
+        let value = ConsensusValue::from_string("job");
+        sim.node(2).unwrap().engine.propose(value.clone()).await.unwrap();
+
+        let engine = sim.node(1).unwrap().engine.clone();
+        let result = engine.run_job(value.clone(), "job-1", Duration::from_secs(5)).await.unwrap();
+
+        assert_eq!(result.outcome, JobOutcome::Reached);
+        assert_eq!(result.key, "job-1");
+        assert_eq!(result.value, value);
+        assert!(result.confidence > 0.0);
+        assert!(result.steps >= 1);
+        assert!(result.messages_sent > 0);
+        assert!(result.messages_received > 0);
+        assert_eq!(result.supporting_sources, [1, 2].into_iter().collect());
+    }
+
+    #[tokio::test]
+    async fn test_structured_value_decodes_after_consensus() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Lease {
+            holder: String,
+            term: u32,
+        }
+
+        let config = ConsensusConfig { step_interval_ms: 10, ..ConsensusConfig::default() };
+        let sim = Simulation::new(2, config).await.unwrap();
+        sim.connect(1, 2).await.unwrap();
+
+        let lease = Lease { holder: "node-1".to_string(), term: 7 };
+        let value = ConsensusValue::from_serializable(&lease).unwrap();
+        sim.node(1).unwrap().engine.propose(value.clone()).await.unwrap();
+
+        // Node 2 learns the value only from the network
+        let peer = sim.node(2).unwrap().engine.clone();
+        let reached = peer.run_until_consensus(Duration::from_secs(5)).await.unwrap().unwrap();
+        assert_eq!(reached, value);
+        assert_eq!(reached.try_decode::<Lease>().unwrap(), lease);
+
+        let mut tampered = reached.clone();
+        tampered.payload = Some(br#"{"holder":"mallory","term":7}"#.to_vec());
+        assert!(tampered.try_decode::<Lease>().is_err());
+        assert!(ConsensusValue::from_string("plain").try_decode::<Lease>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_withdrawal_drops_support_on_peers() {
+        let config = ConsensusConfig { min_quorum: 2, ..ConsensusConfig::default() };
+        let sim = Simulation::new(3, config).await.unwrap();
+        for (a, b) in [(1, 2), (1, 3), (2, 3)] {
+            sim.connect(a, b).await.unwrap();
+        }
+
+        let value = ConsensusValue::from_string("retracted");
+        sim.node(1).unwrap().engine.propose(value.clone()).await.unwrap();
+        sim.node(3).unwrap().engine.propose(value.clone()).await.unwrap();
+
+        let peer = sim.node(2).unwrap().state.clone();
+        let mut backed = 0.0;
+        for _ in 0..100 {
+            if peer.read().await.distinct_sources(&value) == 2 {
+                backed = peer.read().await.aggregate_strength(&value);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(backed > 0.0, "peer never saw both proposals");
+        assert_eq!(peer.write().await.check_consensus(), Some(value.clone()));
+        let signed_before = peer.read().await.pheromones[&value]
+            .iter()
+            .find(|p| p.source == 1)
+            .cloned()
+            .unwrap();
+
+        sim.node(1).unwrap().engine.withdraw(value.clone()).await.unwrap();
+        for _ in 0..100 {
+            if peer.read().await.distinct_sources(&value) == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // Only node 3 still backs the value, short of the quorum of two
+        let mut state = peer.write().await;
+        assert!(state.aggregate_strength(&value) < backed);
+        assert_eq!(state.supporting_sources(&value), [3].into_iter().collect());
+        assert_eq!(state.check_consensus(), None);
+        assert_eq!(state.current_value, None);
+
+        // A pheromone node 1 signed before withdrawing no longer counts
+        state.accept_pheromone(signed_before);
+        assert_eq!(state.distinct_sources(&value), 1);
+        assert!(sim.node(1).unwrap().state.try_read().unwrap().ants.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_step_interval_adapts_to_activity() {
+        let config = ConsensusConfig {
+            adaptive_step_interval: Some(StepIntervalBounds { min_ms: 10, max_ms: 1000 }),
+            ..ConsensusConfig::default()
+        };
+        let sim = Simulation::new(2, config).await.unwrap();
+        sim.connect(1, 2).await.unwrap();
+        let engine = sim.node(2).unwrap().engine.clone();
+
+        // Idle steps back off to the maximum
+        let mut idle_steps = 0;
+        while engine.step_interval().await < Duration::from_secs(1) {
+            engine.step().await.unwrap();
+            idle_steps += 1;
+        }
+        assert!(idle_steps > 1);
+
+        // A pheromone arriving from node 1 brings the rate back up
+        sim.node(1).unwrap().engine.propose(ConsensusValue::from_string("busy")).await.unwrap();
+        let peer = sim.node(2).unwrap().state.clone();
+        for _ in 0..100 {
+            if peer.read().await.stats.pheromones_received > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        engine.step().await.unwrap();
+        assert_eq!(engine.step_interval().await, Duration::from_millis(10));
+
+        // ...and it decays again once the node goes quiet
+        engine.step().await.unwrap();
+        engine.step().await.unwrap();
+        assert_eq!(engine.step_interval().await, Duration::from_millis(40));
+    }
+}
@@ -1,7 +1,9 @@
-use crate::core::types::{NodeId, AntId, ConsensusValue};
+use crate::core::types::{AntId, ConsensusValue, NodeId};
+use crate::core::ant_memory::AntMemory;
+use crate::core::config::ConsensusConfig;
 use crate::core::pheromone::Pheromone;
-use rand::Rng;
-use std::collections::HashSet;
+use crate::utils::random::RngSource;
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// Initial energy level for ants
 pub const INITIAL_ANT_ENERGY: f64 = 100.0;
@@ -12,11 +14,141 @@ pub const ENERGY_DECAY_RATE: f64 = 0.1;
 /// Minimum energy to stay alive
 pub const MIN_ANT_ENERGY: f64 = 0.0;
 
+/// Energy within which an ant counts as spent, absorbing the rounding
+/// error that repeated decay leaves behind (100.0 less 0.1 a thousand
+/// times is about 1e-12, not 0.0)
+pub const ENERGY_EPSILON: f64 = 1e-9;
+
+/// Further nodes an ant may be relayed to after its first move
+pub const DEFAULT_ANT_TTL: u8 = 4;
+
 /// Maximum number of nodes an ant can remember
 pub const ANT_MEMORY_SIZE: usize = 256;
 
+/// Maximum number of nodes a scout can remember
+pub const SCOUT_MEMORY_SIZE: usize = 4 * ANT_MEMORY_SIZE;
+
+/// Pheromones an ant can carry at once by default
+pub const DEFAULT_ANT_CAPACITY: usize = 4;
+
+/// Intensity a full-energy ant deposits at each node it passes through
+pub const PATH_DEPOSIT_INTENSITY: f64 = 0.1;
+
+/// Intensity assumed for a neighbor with no trail
+pub const UNEXPLORED_INTENSITY: f64 = 0.1;
+
+/// Power a worker raises trail intensities to, sharpening its preference
+/// for the strongest neighbor
+pub const WORKER_TRAIL_EXPONENT: f64 = 2.0;
+
+/// Default power a neighbor's heuristic desirability is raised to
+pub const DEFAULT_HEURISTIC_EXPONENT: f64 = 1.0;
+
+/// Low bits of an `AntId` holding the spawning node's counter; the
+/// node's id occupies the bits above
+pub const ANT_COUNTER_BITS: u32 = 32;
+
+/// Id of the `counter`-th ant spawned on `node`, unique across nodes
+pub fn scoped_ant_id(node: NodeId, counter: u32) -> AntId {
+    (u64::from(node) << ANT_COUNTER_BITS) | u64::from(counter)
+}
+
+/// Node that spawned the ant with id `id`
+pub fn ant_origin(id: AntId) -> NodeId {
+    (id >> ANT_COUNTER_BITS) as NodeId
+}
+
+/// Exponents of the classic ACO transition rule, under which a neighbor
+/// is chosen with probability proportional to τ^α · η^β for trail
+/// intensity τ and heuristic desirability η (e.g. inverse latency)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AcoExponents {
+    /// Weight of the pheromone trail (α); shapes workers' preference, while
+    /// scouts keep favoring weak trails
+    pub alpha: f64,
+
+    /// Weight of the heuristic (β)
+    pub beta: f64,
+}
+
+impl Default for AcoExponents {
+    fn default() -> Self {
+        Self {
+            alpha: WORKER_TRAIL_EXPONENT,
+            beta: DEFAULT_HEURISTIC_EXPONENT,
+        }
+    }
+}
+
+/// How an ant chooses among neighbors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AntRole {
+    /// Explores: favors weak or unexplored trails and remembers more of
+    /// its path
+    Scout,
+
+    /// Exploits: strongly favors the highest-intensity neighbor (default)
+    #[default]
+    Worker,
+}
+
+impl AntRole {
+    /// Nodes an ant of this role remembers before forgetting the oldest
+    pub fn memory_size(self) -> usize {
+        match self {
+            AntRole::Scout => SCOUT_MEMORY_SIZE,
+            AntRole::Worker => ANT_MEMORY_SIZE,
+        }
+    }
+
+    /// Selection weight of a neighbor whose trail has `intensity`, with
+    /// workers raising it to `alpha`
+    fn weight(self, intensity: f64, alpha: f64) -> f64 {
+        match self {
+            AntRole::Scout => 1.0 / (intensity + UNEXPLORED_INTENSITY),
+            AntRole::Worker => intensity.powf(alpha),
+        }
+    }
+}
+
+/// Point-in-time view of an ant, for inspecting a node's ants
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AntSummary {
+    pub id: AntId,
+    pub current_node: NodeId,
+    pub energy: f64,
+    pub hops: u32,
+
+    /// Further moves the ant can make before its energy runs out
+    pub hops_left: u32,
+    pub role: AntRole,
+
+    /// Values of the pheromones the ant carries, in pick-up order
+    pub carrying: Vec<ConsensusValue>,
+}
+
+/// Carried pheromones as written by this version (a list) or by earlier
+/// ones (a single optional pheromone)
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CarriedPheromones {
+    Many(Vec<Pheromone>),
+    One(Option<Pheromone>),
+}
+
+fn deserialize_carried<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Pheromone>, D::Error> {
+    Ok(match CarriedPheromones::deserialize(deserializer)? {
+        CarriedPheromones::Many(pheromones) => pheromones,
+        CarriedPheromones::One(pheromone) => pheromone.into_iter().collect(),
+    })
+}
+
+fn default_capacity() -> usize {
+    DEFAULT_ANT_CAPACITY
+}
+
 /// Ant agent - represents a mobile agent in the network
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AntAgent {
     /// Unique identifier for this ant
     pub id: AntId,
@@ -24,58 +156,154 @@ pub struct AntAgent {
     /// Current node the ant is on
     pub current_node: NodeId,
     
-    /// Pheromone the ant is carrying
-    pub carried_pheromone: Option<Pheromone>,
+    /// Pheromones the ant is carrying, oldest pick-up first; never more
+    /// than `capacity`
+    #[serde(alias = "carried_pheromone", deserialize_with = "deserialize_carried", default)]
+    pub carried_pheromones: Vec<Pheromone>,
+
+    /// Most pheromones the ant carries at once
+    #[serde(default = "default_capacity")]
+    pub capacity: usize,
     
     /// Memory of visited nodes (to avoid loops)
-    pub visited_nodes: HashSet<NodeId>,
+    pub visited_nodes: AntMemory,
     
     /// Current energy level
     pub energy_level: f64,
+
+    /// Energy lost per step
+    pub energy_decay_rate: f64,
     
     /// Starting node
     pub start_node: NodeId,
+
+    /// Number of moves made so far
+    #[serde(default)]
+    pub hops: u32,
+
+    /// Exploring scout or exploiting worker
+    #[serde(default)]
+    pub role: AntRole,
+
+    /// Further nodes the ant may be relayed to once it leaves this one;
+    /// a node receiving the ant at 0 keeps its pheromone but not the ant
+    #[serde(default)]
+    pub ttl: u8,
 }
 
 impl AntAgent {
-    /// Create a new ant agent
+    /// Create a new ant agent with exact visited-node memory
     pub fn new(id: AntId, start_node: NodeId) -> Self {
-        let mut visited = HashSet::new();
+        Self::with_memory(id, start_node, AntMemory::exact())
+    }
+
+    /// Create a new ant agent with the given visited-node memory
+    /// (e.g. `AntMemory::bloom` to bound memory size on large networks)
+    pub fn with_memory(id: AntId, start_node: NodeId, memory: AntMemory) -> Self {
+        let mut visited = memory;
         visited.insert(start_node);
 
         Self {
             id,
             current_node: start_node,
-            carried_pheromone: None,
+            carried_pheromones: Vec::new(),
+            capacity: DEFAULT_ANT_CAPACITY,
             visited_nodes: visited,
             energy_level: INITIAL_ANT_ENERGY,
+            energy_decay_rate: ENERGY_DECAY_RATE,
             start_node,
+            hops: 0,
+            role: AntRole::default(),
+            ttl: DEFAULT_ANT_TTL,
+        }
+    }
+
+    /// Create a new ant agent using the energy parameters from `config`
+    pub fn with_config(id: AntId, start_node: NodeId, config: &ConsensusConfig) -> Self {
+        let mut ant = Self::new(id, start_node);
+        ant.energy_level = config.initial_ant_energy;
+        ant.energy_decay_rate = config.energy_decay_rate;
+        ant.ttl = config.ant_ttl;
+        ant.capacity = config.ant_capacity;
+        ant
+    }
+
+    /// Summarize the ant's current state
+    pub fn summary(&self) -> AntSummary {
+        AntSummary {
+            id: self.id,
+            current_node: self.current_node,
+            energy: self.energy_level,
+            hops: self.hops,
+            hops_left: self.max_hops(),
+            role: self.role,
+            carrying: self.carried_pheromones.iter().map(|p| p.value.clone()).collect(),
         }
     }
 
     /// Create an ant with a pheromone to carry
     pub fn with_pheromone(id: AntId, start_node: NodeId, pheromone: Pheromone) -> Self {
         let mut ant = Self::new(id, start_node);
-        ant.carried_pheromone = Some(pheromone);
+        ant.pick_up(pheromone);
         ant
     }
 
     /// Update ant's energy (decreases over time)
     pub fn update_energy(&mut self) {
-        self.energy_level -= ENERGY_DECAY_RATE;
+        self.energy_level -= self.energy_decay_rate;
     }
 
     /// Check if ant is still alive
     pub fn is_alive(&self) -> bool {
-        self.energy_level > MIN_ANT_ENERGY
+        self.energy_level > MIN_ANT_ENERGY + ENERGY_EPSILON
+    }
+
+    /// Moves the ant can still make: the number of further `update_energy`
+    /// calls it survives with its current energy and decay rate
+    ///
+    /// An ant that doesn't lose energy can move indefinitely (`u32::MAX`).
+    pub fn max_hops(&self) -> u32 {
+        let spare = self.energy_level - MIN_ANT_ENERGY - ENERGY_EPSILON;
+        if spare <= 0.0 {
+            return 0;
+        }
+        if self.energy_decay_rate <= 0.0 {
+            return u32::MAX;
+        }
+
+        let hops = (spare / self.energy_decay_rate).ceil() - 1.0;
+        hops.min(u32::MAX as f64) as u32
     }
 
     /// Select next node based on pheromone intensities
-    /// Uses probabilistic selection (roulette wheel)
+    /// Uses probabilistic selection (roulette wheel) drawing from `rng`
+    ///
+    /// Nodes the memory reports as (possibly) visited are skipped. Each
+    /// neighbor is weighted by the ant's `role`: workers by the square of
+    /// its trail intensity, scouts by its inverse.
     pub fn select_next_node(
         &self,
         neighbors: &[NodeId],
         pheromone_intensities: &[(NodeId, f64)],
+        rng: &dyn RngSource,
+    ) -> Option<NodeId> {
+        self.select_next_node_weighted(neighbors, pheromone_intensities, &[], AcoExponents::default(), rng)
+    }
+
+    /// Select the next node as `select_next_node` does, additionally
+    /// weighting each neighbor by its `heuristic` desirability
+    ///
+    /// A neighbor's trail weight (workers' intensity raised to
+    /// `exponents.alpha`) is multiplied by its heuristic raised to
+    /// `exponents.beta`. Neighbors missing from `heuristic` count as 1.0,
+    /// so an empty heuristic leaves the choice to the trails alone.
+    pub fn select_next_node_weighted(
+        &self,
+        neighbors: &[NodeId],
+        pheromone_intensities: &[(NodeId, f64)],
+        heuristic: &[(NodeId, f64)],
+        exponents: AcoExponents,
+        rng: &dyn RngSource,
     ) -> Option<NodeId> {
         if neighbors.is_empty() {
             return None;
@@ -102,21 +330,25 @@ impl AntAgent {
                 .iter()
                 .find(|(id, _)| *id == neighbor)
                 .map(|(_, intensity)| *intensity)
-                .unwrap_or(0.1); // Default low intensity for unexplored paths
+                .unwrap_or(UNEXPLORED_INTENSITY);
+            let desirability = heuristic
+                .iter()
+                .find(|(id, _)| *id == neighbor)
+                .map(|(_, eta)| eta.max(0.0).powf(exponents.beta))
+                .unwrap_or(1.0);
+            let weight = self.role.weight(intensity, exponents.alpha) * desirability;
 
-            probabilities.push((neighbor, intensity));
-            total_intensity += intensity;
+            probabilities.push((neighbor, weight));
+            total_intensity += weight;
         }
 
         if total_intensity == 0.0 {
             // No pheromone trail, random selection
-            let mut rng = rand::thread_rng();
-            return available_neighbors.get(rng.gen_range(0..available_neighbors.len())).copied();
+            return available_neighbors.get(rng.gen_index(available_neighbors.len())).copied();
         }
 
         // Roulette wheel selection
-        let mut rng = rand::thread_rng();
-        let random_value = rng.gen::<f64>() * total_intensity;
+        let random_value = rng.next_f64() * total_intensity;
         let mut cumulative = 0.0;
 
         for (node, intensity) in probabilities {
@@ -134,30 +366,85 @@ impl AntAgent {
     pub fn move_to(&mut self, node: NodeId) {
         self.visited_nodes.insert(node);
         self.current_node = node;
+        self.hops += 1;
 
-        // Limit memory size
-        if self.visited_nodes.len() > ANT_MEMORY_SIZE {
-            // Remove oldest entries (simple: remove start_node if present)
-            self.visited_nodes.remove(&self.start_node);
+        // Slide the memory window forward by forgetting the oldest visits
+        while self.visited_nodes.len() > self.role.memory_size() {
+            if self.visited_nodes.evict_oldest().is_none() {
+                break;
+            }
         }
     }
 
-    /// Drop pheromone at current location
-    pub fn drop_pheromone(&mut self) -> Option<Pheromone> {
-        self.carried_pheromone.take()
+    /// Pheromones the ant lays down at `current_node` as it passes through,
+    /// one per pheromone it carries
+    ///
+    /// Each deposit is a copy of a carried pheromone (so it still verifies)
+    /// whose intensity shrinks with the ant's remaining energy; the carried
+    /// pheromones themselves are kept for the final target. Empty when the
+    /// ant carries nothing, is not at `current_node`, or is out of energy.
+    pub fn deposit_at(&self, current_node: NodeId) -> Vec<Pheromone> {
+        if current_node != self.current_node {
+            return Vec::new();
+        }
+
+        let energy_fraction = (self.energy_level / INITIAL_ANT_ENERGY).clamp(0.0, 1.0);
+        let amount = PATH_DEPOSIT_INTENSITY * energy_fraction;
+        if amount <= 0.0 {
+            return Vec::new();
+        }
+
+        self.carried_pheromones
+            .iter()
+            .cloned()
+            .map(|mut deposit| {
+                deposit.intensity = amount;
+                deposit
+            })
+            .collect()
     }
 
-    /// Pick up a pheromone
-    pub fn pick_up_pheromone(&mut self, pheromone: Pheromone) {
-        self.carried_pheromone = Some(pheromone);
+    /// Whether the ant carries as many pheromones as it can
+    pub fn is_full(&self) -> bool {
+        self.carried_pheromones.len() >= self.capacity
+    }
+
+    /// Pick up a pheromone, replacing any carried one for the same value
+    /// and round; false (and the pheromone left behind) if the ant is full
+    pub fn pick_up(&mut self, pheromone: Pheromone) -> bool {
+        if let Some(carried) = self.carried_pheromones
+            .iter_mut()
+            .find(|p| p.round == pheromone.round && p.value == pheromone.value)
+        {
+            *carried = pheromone;
+            return true;
+        }
+        if self.is_full() {
+            return false;
+        }
+
+        self.carried_pheromones.push(pheromone);
+        true
+    }
+
+    /// Drop the pheromone picked up earliest
+    pub fn deposit_one(&mut self) -> Option<Pheromone> {
+        if self.carried_pheromones.is_empty() {
+            return None;
+        }
+        Some(self.carried_pheromones.remove(0))
+    }
+
+    /// Drop every carried pheromone at the current location
+    pub fn drop_all(&mut self) -> Vec<Pheromone> {
+        std::mem::take(&mut self.carried_pheromones)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::pheromone::Pheromone;
-    use crate::core::types::ConsensusValue;
+    use crate::utils::random::ThreadRngSource;
 
     #[test]
     fn test_ant_creation() {
@@ -169,6 +456,33 @@ mod tests {
         assert!(ant.is_alive());
     }
 
+    #[test]
+    fn test_max_hops_matches_survival() {
+        fn survived(mut ant: AntAgent) -> u32 {
+            let mut moves = 0;
+            loop {
+                ant.update_energy();
+                if !ant.is_alive() {
+                    return moves;
+                }
+                moves += 1;
+            }
+        }
+
+        let default = AntAgent::new(1, 10);
+        let config = ConsensusConfig { initial_ant_energy: 500.0, energy_decay_rate: 0.3, ..ConsensusConfig::default() };
+        let hardy = AntAgent::with_config(2, 10, &config);
+
+        assert_eq!(default.max_hops(), 999);
+        assert_eq!(hardy.max_hops(), 1666);
+        assert_eq!(survived(default.clone()), default.max_hops());
+        assert_eq!(survived(hardy.clone()), hardy.max_hops());
+        assert!(hardy.max_hops() > default.max_hops());
+
+        let config = ConsensusConfig { energy_decay_rate: 0.0, ..ConsensusConfig::default() };
+        assert_eq!(AntAgent::with_config(3, 10, &config).max_hops(), u32::MAX);
+    }
+
     #[test]
     fn test_ant_energy_decay() {
         let mut ant = AntAgent::new(1, 10);
@@ -184,9 +498,167 @@ mod tests {
         let neighbors = vec![11, 12, 13];
         let intensities = vec![(11, 0.5), (12, 0.3), (13, 0.2)];
         
-        let next = ant.select_next_node(&neighbors, &intensities);
+        let next = ant.select_next_node(&neighbors, &intensities, &ThreadRngSource);
         assert!(next.is_some());
         assert!(neighbors.contains(&next.unwrap()));
     }
-}
 
+    #[test]
+    fn test_deposit_diminishes_with_energy() {
+        let value = crate::core::types::ConsensusValue::from_string("path");
+        let mut ant = AntAgent::with_pheromone(1, 10, Pheromone::new(value, 10, &[]).unwrap());
+
+        let full = ant.deposit_at(10);
+        assert_eq!(full[0].intensity, PATH_DEPOSIT_INTENSITY);
+        assert!(ant.deposit_at(11).is_empty());
+
+        ant.energy_level = INITIAL_ANT_ENERGY / 2.0;
+        assert_eq!(ant.deposit_at(10)[0].intensity, PATH_DEPOSIT_INTENSITY / 2.0);
+        assert_eq!(ant.carried_pheromones.len(), 1);
+
+        ant.energy_level = 0.0;
+        assert!(ant.deposit_at(10).is_empty());
+    }
+
+    #[test]
+    fn test_carrying_is_bounded_by_capacity() {
+        let pheromone = |name: &str| Pheromone::new(ConsensusValue::from_string(name), 10, &[]).unwrap();
+        let mut ant = AntAgent::new(1, 10);
+        ant.capacity = 2;
+
+        assert!(ant.pick_up(pheromone("a")));
+        assert!(ant.pick_up(pheromone("b")));
+        assert!(ant.is_full());
+        assert!(!ant.pick_up(pheromone("c")));
+
+        // A fresher pheromone for a carried value takes its place
+        assert!(ant.pick_up(pheromone("a")));
+        assert_eq!(ant.summary().carrying.len(), 2);
+        assert_eq!(ant.deposit_at(10).len(), 2);
+
+        assert_eq!(ant.deposit_one().unwrap().value, ConsensusValue::from_string("a"));
+        assert_eq!(ant.drop_all().len(), 1);
+        assert!(ant.deposit_one().is_none());
+    }
+
+    #[test]
+    fn test_deserializes_single_carried_pheromone() {
+        let pheromone = Pheromone::new(ConsensusValue::from_string("legacy"), 10, &[]).unwrap();
+        let mut json = serde_json::to_value(AntAgent::new(1, 10)).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("carried_pheromones");
+        fields.remove("capacity");
+
+        fields.insert("carried_pheromone".to_string(), serde_json::to_value(&pheromone).unwrap());
+        let ant: AntAgent = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(ant.carried_pheromones.len(), 1);
+        assert_eq!(ant.capacity, DEFAULT_ANT_CAPACITY);
+
+        json["carried_pheromone"] = serde_json::Value::Null;
+        let ant: AntAgent = serde_json::from_value(json).unwrap();
+        assert!(ant.carried_pheromones.is_empty());
+    }
+
+    #[test]
+    fn test_memory_evicts_earliest_visit() {
+        let mut ant = AntAgent::new(1, 0);
+        for node in 1..ANT_MEMORY_SIZE as NodeId {
+            ant.move_to(node);
+        }
+        assert_eq!(ant.visited_nodes.len(), ANT_MEMORY_SIZE);
+
+        // Returning to the start node makes it a recent visit, so node 1 is
+        // now the earliest one and is evicted when memory overflows
+        ant.move_to(0);
+        ant.move_to(1000);
+
+        assert_eq!(ant.visited_nodes.len(), ANT_MEMORY_SIZE);
+        assert!(!ant.visited_nodes.contains(&1));
+        assert!(ant.visited_nodes.contains(&0));
+        assert!(ant.visited_nodes.contains(&2));
+        assert!(ant.visited_nodes.contains(&1000));
+    }
+
+    #[test]
+    fn test_bloom_memory_avoids_visited_nodes() {
+        let mut ant = AntAgent::with_memory(1, 10, AntMemory::bloom(64, 0.01));
+        ant.move_to(11);
+        ant.move_to(12);
+
+        let neighbors = vec![11, 12, 13];
+        for _ in 0..20 {
+            let next = ant.select_next_node(&neighbors, &[], &ThreadRngSource);
+            assert_eq!(next, Some(13));
+        }
+    }
+
+    /// Times each of a strong-trail and an unexplored neighbor is chosen
+    /// over `trials` selections
+    fn choices(role: AntRole, trials: usize) -> (usize, usize) {
+        let mut ant = AntAgent::new(1, 10);
+        ant.role = role;
+        let rng = crate::utils::random::SeededRngSource::new(7);
+
+        let mut strong = 0;
+        let mut unexplored = 0;
+        for _ in 0..trials {
+            match ant.select_next_node(&[11, 12], &[(11, 1.0)], &rng) {
+                Some(11) => strong += 1,
+                Some(12) => unexplored += 1,
+                other => panic!("Unexpected choice {:?}", other),
+            }
+        }
+        (strong, unexplored)
+    }
+
+    #[test]
+    fn test_scouts_prefer_unexplored_neighbors() {
+        let (strong, unexplored) = choices(AntRole::Scout, 1000);
+        assert!(unexplored > strong * 3, "{} unexplored vs {} strong", unexplored, strong);
+    }
+
+    #[test]
+    fn test_workers_prefer_strongest_neighbor() {
+        let (strong, unexplored) = choices(AntRole::Worker, 1000);
+        assert!(strong > unexplored * 20, "{} strong vs {} unexplored", strong, unexplored);
+        assert!(strong > choices(AntRole::Scout, 1000).0);
+    }
+
+    #[test]
+    fn test_heuristic_favors_low_latency_neighbors() {
+        let ant = AntAgent::new(1, 10);
+        let rng = crate::utils::random::SeededRngSource::new(7);
+        let intensities = [(11, 0.5), (12, 0.5)];
+        // Inverse latency: node 11 answers in 10ms, node 12 in 40ms
+        let heuristic = [(11, 1.0 / 0.010), (12, 1.0 / 0.040)];
+
+        let mut near = 0;
+        for _ in 0..1000 {
+            if ant.select_next_node_weighted(&[11, 12], &intensities, &heuristic, AcoExponents::default(), &rng) == Some(11) {
+                near += 1;
+            }
+        }
+        assert!(near > 700, "{} of 1000 picks went to the nearer neighbor", near);
+
+        // Without a heuristic, equal trails split the choice evenly
+        let mut near = 0;
+        for _ in 0..1000 {
+            if ant.select_next_node_weighted(&[11, 12], &intensities, &[], AcoExponents::default(), &rng) == Some(11) {
+                near += 1;
+            }
+        }
+        assert!((400..600).contains(&near), "{} of 1000 picks", near);
+    }
+
+    #[test]
+    fn test_scouts_remember_longer_paths() {
+        let mut scout = AntAgent::new(1, 0);
+        scout.role = AntRole::Scout;
+        for node in 1..=SCOUT_MEMORY_SIZE as NodeId {
+            scout.move_to(node);
+        }
+
+        assert_eq!(scout.visited_nodes.len(), SCOUT_MEMORY_SIZE);
+        assert!(scout.visited_nodes.contains(&(ANT_MEMORY_SIZE as NodeId)));
+    }
+}
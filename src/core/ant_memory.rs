@@ -0,0 +1,261 @@
+use crate::core::types::NodeId;
+use serde::{Serialize, Deserialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+
+/// Default false-positive rate for Bloom-filter ant memory
+pub const DEFAULT_BLOOM_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// Memory of the nodes an ant has visited
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AntMemory {
+    /// Exact visited nodes, remembered in visit order
+    Exact(VisitedWindow),
+
+    /// Bounded-size Bloom filter; never forgets a visited node but may
+    /// report an unvisited node as visited
+    Bloom(BloomFilter),
+}
+
+impl AntMemory {
+    /// Create an empty exact memory
+    pub fn exact() -> Self {
+        AntMemory::Exact(VisitedWindow::default())
+    }
+
+    /// Create an empty Bloom memory sized for `capacity` nodes at the given
+    /// false-positive rate
+    pub fn bloom(capacity: usize, false_positive_rate: f64) -> Self {
+        AntMemory::Bloom(BloomFilter::new(capacity, false_positive_rate))
+    }
+
+    /// Record a visited node
+    pub fn insert(&mut self, node: NodeId) {
+        match self {
+            AntMemory::Exact(window) => window.insert(node),
+            AntMemory::Bloom(filter) => filter.insert(node),
+        }
+    }
+
+    /// Check whether a node has (or, for Bloom memory, may have) been visited
+    pub fn contains(&self, node: &NodeId) -> bool {
+        match self {
+            AntMemory::Exact(window) => window.contains(node),
+            AntMemory::Bloom(filter) => filter.contains(*node),
+        }
+    }
+
+    /// Forget a visited node; Bloom memory cannot forget and returns `false`
+    pub fn remove(&mut self, node: &NodeId) -> bool {
+        match self {
+            AntMemory::Exact(window) => window.remove(node),
+            AntMemory::Bloom(_) => false,
+        }
+    }
+
+    /// Forget the least recently visited node, returning it; Bloom memory
+    /// cannot forget and returns `None`
+    pub fn evict_oldest(&mut self) -> Option<NodeId> {
+        match self {
+            AntMemory::Exact(window) => window.evict_oldest(),
+            AntMemory::Bloom(_) => None,
+        }
+    }
+
+    /// Number of nodes recorded
+    pub fn len(&self) -> usize {
+        match self {
+            AntMemory::Exact(window) => window.len(),
+            AntMemory::Bloom(filter) => filter.len(),
+        }
+    }
+
+    /// Check whether no nodes have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The exact visited set, if this memory is exact
+    pub fn exact_nodes(&self) -> Option<&HashSet<NodeId>> {
+        match self {
+            AntMemory::Exact(window) => Some(&window.nodes),
+            AntMemory::Bloom(_) => None,
+        }
+    }
+}
+
+/// Visited nodes in visit order, with a set for constant-time lookups
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VisitedWindow {
+    order: VecDeque<NodeId>,
+    nodes: HashSet<NodeId>,
+}
+
+impl VisitedWindow {
+    /// Record a visit; revisiting a node makes it the most recent again
+    pub fn insert(&mut self, node: NodeId) {
+        if !self.nodes.insert(node) {
+            self.order.retain(|&visited| visited != node);
+        }
+        self.order.push_back(node);
+    }
+
+    /// Check whether a node is in the window
+    pub fn contains(&self, node: &NodeId) -> bool {
+        self.nodes.contains(node)
+    }
+
+    /// Forget a node
+    pub fn remove(&mut self, node: &NodeId) -> bool {
+        let removed = self.nodes.remove(node);
+        if removed {
+            self.order.retain(|visited| visited != node);
+        }
+        removed
+    }
+
+    /// Forget the least recently visited node, returning it
+    pub fn evict_oldest(&mut self) -> Option<NodeId> {
+        let oldest = self.order.pop_front()?;
+        self.nodes.remove(&oldest);
+        Some(oldest)
+    }
+
+    /// Visited nodes, least recent first
+    pub fn iter(&self) -> impl Iterator<Item = &NodeId> {
+        self.order.iter()
+    }
+
+    /// Number of nodes in the window
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Check whether the window is empty
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+/// Fixed-size Bloom filter over node IDs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: usize,
+    num_hashes: u32,
+    items: usize,
+}
+
+impl BloomFilter {
+    /// Create a filter sized for `capacity` items at the given false-positive rate
+    pub fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        let capacity = capacity.max(1) as f64;
+        let rate = false_positive_rate.clamp(f64::MIN_POSITIVE, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+
+        let num_bits = ((-capacity * rate.ln()) / (ln2 * ln2)).ceil().max(64.0) as usize;
+        let num_hashes = ((num_bits as f64 / capacity) * ln2).round().max(1.0) as u32;
+
+        Self {
+            bits: vec![0; num_bits.div_ceil(64)],
+            num_bits,
+            num_hashes,
+            items: 0,
+        }
+    }
+
+    /// Add a node to the filter
+    pub fn insert(&mut self, node: NodeId) {
+        let indexes: Vec<usize> = self.indexes(node).collect();
+        for index in indexes {
+            self.bits[index / 64] |= 1 << (index % 64);
+        }
+        self.items += 1;
+    }
+
+    /// Check whether a node may have been added
+    pub fn contains(&self, node: NodeId) -> bool {
+        self.indexes(node)
+            .all(|index| self.bits[index / 64] & (1 << (index % 64)) != 0)
+    }
+
+    /// Number of insertions made
+    pub fn len(&self) -> usize {
+        self.items
+    }
+
+    /// Check whether nothing has been inserted
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    /// Size of the filter in bits
+    pub fn num_bits(&self) -> usize {
+        self.num_bits
+    }
+
+    /// Bit positions for a node (double hashing)
+    fn indexes(&self, node: NodeId) -> impl Iterator<Item = usize> + '_ {
+        let h1 = Self::hash(node, 0);
+        let h2 = Self::hash(node, 1) | 1;
+        (0..self.num_hashes as u64)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % self.num_bits as u64) as usize)
+    }
+
+    fn hash(node: NodeId, seed: u64) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+        node.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_memory_evicts_in_visit_order() {
+        let mut memory = AntMemory::exact();
+        for node in [1, 2, 3] {
+            memory.insert(node);
+        }
+        // Revisiting 1 makes 2 the oldest
+        memory.insert(1);
+
+        assert_eq!(memory.evict_oldest(), Some(2));
+        assert_eq!(memory.evict_oldest(), Some(3));
+        assert!(memory.contains(&1));
+        assert_eq!(memory.len(), 1);
+        assert_eq!(AntMemory::bloom(8, 0.01).evict_oldest(), None);
+    }
+
+    #[test]
+    fn test_bloom_memory_has_no_false_negatives() {
+        let capacity = 1000;
+        let mut memory = AntMemory::bloom(capacity, DEFAULT_BLOOM_FALSE_POSITIVE_RATE);
+
+        for node in 0..capacity as NodeId {
+            memory.insert(node * 7919);
+        }
+
+        for node in 0..capacity as NodeId {
+            assert!(memory.contains(&(node * 7919)));
+        }
+    }
+
+    #[test]
+    fn test_bloom_false_positive_rate_is_bounded() {
+        let capacity = 1000;
+        let mut filter = BloomFilter::new(capacity, 0.01);
+        for node in 0..capacity as NodeId {
+            filter.insert(node);
+        }
+
+        let false_positives = (100_000..110_000)
+            .filter(|node| filter.contains(*node))
+            .count();
+        assert!(false_positives < 500, "too many false positives: {}", false_positives);
+    }
+}
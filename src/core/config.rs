@@ -0,0 +1,338 @@
+use crate::core::ant_agent::{AcoExponents, DEFAULT_ANT_CAPACITY, DEFAULT_ANT_TTL, ENERGY_DECAY_RATE, INITIAL_ANT_ENERGY};
+use crate::core::node_state::{DEFAULT_EVAPORATION_RATE, DEFAULT_NEIGHBOR_TIMEOUT_SECS, MAX_NEIGHBORS};
+use crate::core::persistence::AntPersistence;
+use crate::core::pheromone::{EvaporationModel, CONSENSUS_THRESHOLD, MAX_PHEROMONE_INTENSITY};
+use crate::core::rate_limit::RateLimit;
+use crate::core::types::{NodeId, ValueKind};
+use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Default interval between consensus engine steps, in milliseconds
+pub const DEFAULT_STEP_INTERVAL_MS: u64 = 100;
+
+/// Default minimum number of distinct sources a winning value needs
+pub const DEFAULT_MIN_QUORUM: usize = 1;
+
+/// Default seconds a node counts toward the cluster-size estimate after
+/// it was last observed
+pub const DEFAULT_CLUSTER_WINDOW_SECS: u64 = 60;
+
+/// Default hysteresis bonus added to the incumbent value's trail score
+pub const DEFAULT_INCUMBENT_BIAS: f64 = 0.02;
+
+/// Default fraction of explorer ants spawned as scouts
+pub const DEFAULT_SCOUT_FRACTION: f64 = 0.2;
+
+/// Default maximum number of entries kept on a single value's trail
+pub const DEFAULT_MAX_PHEROMONES_PER_VALUE: usize = 1024;
+
+/// Default upper bound on a single value's summed pheromone intensity
+pub const DEFAULT_MAX_AGGREGATE_INTENSITY: f64 = 1000.0;
+
+/// Default seconds a received pheromone may be dated ahead of this node's clock
+pub const DEFAULT_MAX_PHEROMONE_SKEW_SECS: u64 = 60;
+
+/// Default age, in seconds, beyond which a received pheromone is refused
+pub const DEFAULT_MAX_PHEROMONE_AGE_SECS: u64 = 3600;
+
+/// How a node responds when a peer announces consensus on a value this
+/// node's validator rejects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DissentPolicy {
+    /// Log the disagreement and carry on (default)
+    #[default]
+    Ignore,
+
+    /// Broadcast a signed `Dissent` message so the rest of the network
+    /// learns of the disagreement
+    Broadcast,
+}
+
+/// Range the engine's step interval adapts within, in milliseconds
+///
+/// The engine steps every `min_ms` while pheromones arrive or ants move,
+/// and backs off toward `max_ms` while the node is quiescent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepIntervalBounds {
+    pub min_ms: u64,
+    pub max_ms: u64,
+}
+
+impl StepIntervalBounds {
+    /// Shortest interval, used while the node is busy
+    pub fn min(&self) -> Duration {
+        Duration::from_millis(self.min_ms)
+    }
+
+    /// Longest interval, reached after the node has been idle for a while
+    pub fn max(&self) -> Duration {
+        Duration::from_millis(self.max_ms.max(self.min_ms))
+    }
+}
+
+/// Tunable parameters of the consensus algorithm
+///
+/// The module-level constants (`CONSENSUS_THRESHOLD`, `DEFAULT_EVAPORATION_RATE`,
+/// `INITIAL_ANT_ENERGY`, `ENERGY_DECAY_RATE`) are the defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsensusConfig {
+    /// Pheromone intensity required for consensus
+    pub consensus_threshold: f64,
+
+    /// Minimum number of distinct sources whose pheromones must back a
+    /// value before it can be declared the consensus
+    #[serde(default = "default_min_quorum")]
+    pub min_quorum: usize,
+
+    /// Fraction of the estimated cluster size (see
+    /// `NodeState::estimated_cluster_size`) whose sources must back a
+    /// value; the quorum is the larger of this share, rounded up, and
+    /// `min_quorum` (None uses `min_quorum` alone)
+    #[serde(default)]
+    pub quorum_fraction: Option<f64>,
+
+    /// Seconds a node counts toward the cluster-size estimate after it was
+    /// last heard from or a pheromone it signed was accepted
+    #[serde(default = "default_cluster_window_secs")]
+    pub cluster_window_secs: u64,
+
+    /// Consecutive steps a value must lead the default round, above the
+    /// threshold and with quorum, before it is committed and stops
+    /// changing (None never commits)
+    #[serde(default)]
+    pub finality_steps: Option<u32>,
+
+    /// Bonus added to the currently held value's average intensity when
+    /// choosing between values, so near-ties don't flip the outcome
+    #[serde(default = "default_incumbent_bias")]
+    pub incumbent_bias: f64,
+
+    /// Fraction of pheromone intensity lost per second of wall-clock time
+    /// (under `EvaporationModel::Linear`, the intensity lost per second)
+    pub evaporation_rate: f64,
+
+    /// How intensity decays between evaporations
+    #[serde(default)]
+    pub evaporation_model: EvaporationModel,
+
+    /// Seconds after a pheromone's timestamp during which it holds its
+    /// intensity before evaporation begins
+    #[serde(default)]
+    pub evaporation_grace_secs: u64,
+
+    /// Fraction of this node's trails, strongest first, re-broadcast to
+    /// its neighbors every step independently of ant movement (0 disables
+    /// diffusion)
+    #[serde(default)]
+    pub diffusion_rate: f64,
+
+    /// Starting energy for ant agents
+    pub initial_ant_energy: f64,
+
+    /// Energy an ant loses per step
+    pub energy_decay_rate: f64,
+
+    /// Fraction of the explorer ants spawned for a proposal that are
+    /// scouts; the rest are workers
+    #[serde(default = "default_scout_fraction")]
+    pub scout_fraction: f64,
+
+    /// Further nodes an explorer ant may be relayed to after its first move
+    #[serde(default = "default_ant_ttl")]
+    pub ant_ttl: u8,
+
+    /// Most pheromones an ant carries at once
+    #[serde(default = "default_ant_capacity")]
+    pub ant_capacity: usize,
+
+    /// Exponents weighing trail intensity (α) against the neighbor
+    /// heuristic (β) when an ant picks its next node; the heuristic is the
+    /// best link's measured latency over each link's (see
+    /// `NodeState::latency_heuristic`)
+    #[serde(default)]
+    pub aco_exponents: AcoExponents,
+
+    /// Maximum difference, in seconds, between this node's clock and a
+    /// pheromone's timestamp for it to count toward consensus (None disables)
+    pub max_timestamp_drift: Option<u64>,
+
+    /// Seconds a received pheromone's timestamp may lie ahead of this
+    /// node's clock; later-dated ones would dodge evaporation and are
+    /// refused on receipt (None accepts any future date)
+    #[serde(default = "default_max_pheromone_skew_secs")]
+    pub max_pheromone_skew_secs: Option<u64>,
+
+    /// Age, in seconds, beyond which a received pheromone is refused as a
+    /// replay (None accepts any age)
+    #[serde(default = "default_max_pheromone_age_secs")]
+    pub max_pheromone_age_secs: Option<u64>,
+
+    /// Seconds of silence after which a neighbor is pruned
+    pub neighbor_timeout_secs: u64,
+
+    /// Neighbors kept at once; adding one beyond this evicts the neighbor
+    /// heard from least recently
+    #[serde(default = "default_max_neighbors")]
+    pub max_neighbors: usize,
+
+    /// Interval between consensus engine steps, in milliseconds
+    pub step_interval_ms: u64,
+
+    /// Bounds within which the step interval adapts to activity; when set,
+    /// `step_interval_ms` is ignored (None steps at the fixed interval)
+    #[serde(default)]
+    pub adaptive_step_interval: Option<StepIntervalBounds>,
+
+    /// Maximum messages a single step may emit; emissions beyond the cap are
+    /// deferred to the next step (None means unlimited)
+    #[serde(default)]
+    pub max_messages_per_step: Option<usize>,
+
+    /// Whether live ants are kept when node state is persisted
+    pub ant_persistence: AntPersistence,
+
+    /// Entries kept on a single value's trail; beyond this the oldest are
+    /// folded into a newer entry from the same source, or else evicted
+    #[serde(default = "default_max_pheromones_per_value")]
+    pub max_pheromones_per_value: usize,
+
+    /// Intensity a stored pheromone is capped at however often it is
+    /// reinforced or re-broadcast; values above `MAX_PHEROMONE_INTENSITY`
+    /// are treated as that hard cap
+    #[serde(default = "default_max_pheromone_intensity")]
+    pub max_pheromone_intensity: f64,
+
+    /// Summed intensity above which a value's trail is considered to have
+    /// exploded and is scaled back down by the intensity watchdog
+    pub max_aggregate_intensity: f64,
+
+    /// Value kind this node's topic accepts; pheromones carrying any other
+    /// kind are rejected (None accepts every kind)
+    #[serde(default)]
+    pub value_kind: Option<ValueKind>,
+
+    /// Sources whose pheromones are stored WITHOUT signature verification
+    ///
+    /// This trades security for CPU: anyone who can put packets on the
+    /// network can forge a pheromone claiming to come from a trusted source.
+    /// Only use it when every sender on the network is trusted. Empty (every
+    /// pheromone verified) by default.
+    #[serde(default)]
+    pub trusted_sources: HashSet<NodeId>,
+
+    /// Reject heartbeats, neighbor discovery and consensus announcements
+    /// that are unsigned or whose signature doesn't match the sender's key
+    #[serde(default)]
+    pub authenticate_messages: bool,
+
+    /// Response to a consensus announcement for a value this node rejects
+    #[serde(default)]
+    pub dissent_policy: DissentPolicy,
+
+    /// Rate and burst of pheromones accepted from each source; verified
+    /// pheromones beyond it are dropped (None disables rate limiting)
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl ConsensusConfig {
+    /// Interval between consensus engine steps
+    pub fn step_interval(&self) -> Duration {
+        Duration::from_millis(self.step_interval_ms)
+    }
+
+    /// Cap on a stored pheromone's intensity, within `MAX_PHEROMONE_INTENSITY`
+    pub fn intensity_cap(&self) -> f64 {
+        self.max_pheromone_intensity.clamp(0.0, MAX_PHEROMONE_INTENSITY)
+    }
+
+    /// Time a fresh pheromone holds its intensity before evaporating
+    pub fn evaporation_grace(&self) -> Duration {
+        Duration::from_secs(self.evaporation_grace_secs)
+    }
+}
+
+fn default_min_quorum() -> usize {
+    DEFAULT_MIN_QUORUM
+}
+
+fn default_cluster_window_secs() -> u64 {
+    DEFAULT_CLUSTER_WINDOW_SECS
+}
+
+fn default_incumbent_bias() -> f64 {
+    DEFAULT_INCUMBENT_BIAS
+}
+
+fn default_scout_fraction() -> f64 {
+    DEFAULT_SCOUT_FRACTION
+}
+
+fn default_ant_ttl() -> u8 {
+    DEFAULT_ANT_TTL
+}
+
+fn default_ant_capacity() -> usize {
+    DEFAULT_ANT_CAPACITY
+}
+
+fn default_max_pheromone_skew_secs() -> Option<u64> {
+    Some(DEFAULT_MAX_PHEROMONE_SKEW_SECS)
+}
+
+fn default_max_pheromone_age_secs() -> Option<u64> {
+    Some(DEFAULT_MAX_PHEROMONE_AGE_SECS)
+}
+
+fn default_max_neighbors() -> usize {
+    MAX_NEIGHBORS
+}
+
+fn default_max_pheromones_per_value() -> usize {
+    DEFAULT_MAX_PHEROMONES_PER_VALUE
+}
+
+fn default_max_pheromone_intensity() -> f64 {
+    MAX_PHEROMONE_INTENSITY
+}
+
+impl Default for ConsensusConfig {
+    fn default() -> Self {
+        Self {
+            consensus_threshold: CONSENSUS_THRESHOLD,
+            min_quorum: DEFAULT_MIN_QUORUM,
+            quorum_fraction: None,
+            cluster_window_secs: DEFAULT_CLUSTER_WINDOW_SECS,
+            finality_steps: None,
+            incumbent_bias: DEFAULT_INCUMBENT_BIAS,
+            evaporation_rate: DEFAULT_EVAPORATION_RATE,
+            evaporation_model: EvaporationModel::default(),
+            evaporation_grace_secs: 0,
+            diffusion_rate: 0.0,
+            initial_ant_energy: INITIAL_ANT_ENERGY,
+            energy_decay_rate: ENERGY_DECAY_RATE,
+            scout_fraction: DEFAULT_SCOUT_FRACTION,
+            ant_ttl: DEFAULT_ANT_TTL,
+            ant_capacity: DEFAULT_ANT_CAPACITY,
+            aco_exponents: AcoExponents::default(),
+            max_timestamp_drift: None,
+            max_pheromone_skew_secs: Some(DEFAULT_MAX_PHEROMONE_SKEW_SECS),
+            max_pheromone_age_secs: Some(DEFAULT_MAX_PHEROMONE_AGE_SECS),
+            neighbor_timeout_secs: DEFAULT_NEIGHBOR_TIMEOUT_SECS,
+            max_neighbors: MAX_NEIGHBORS,
+            step_interval_ms: DEFAULT_STEP_INTERVAL_MS,
+            adaptive_step_interval: None,
+            max_messages_per_step: None,
+            ant_persistence: AntPersistence::default(),
+            max_pheromones_per_value: DEFAULT_MAX_PHEROMONES_PER_VALUE,
+            max_pheromone_intensity: MAX_PHEROMONE_INTENSITY,
+            max_aggregate_intensity: DEFAULT_MAX_AGGREGATE_INTENSITY,
+            value_kind: None,
+            trusted_sources: HashSet::new(),
+            authenticate_messages: false,
+            dissent_policy: DissentPolicy::default(),
+            rate_limit: None,
+        }
+    }
+}
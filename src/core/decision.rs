@@ -0,0 +1,258 @@
+use crate::core::types::ConsensusValue;
+use std::fmt::Debug;
+
+/// Aggregated trail of one value in a round
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrailScore {
+    pub value: ConsensusValue,
+
+    /// Highest priority any counted pheromone carries
+    pub priority: u8,
+
+    /// Weighted intensity averaged over the counted entries
+    pub average: f64,
+
+    /// Summed weighted intensity of the counted entries
+    pub total: f64,
+
+    /// Distinct sources backing the value
+    pub sources: usize,
+}
+
+/// Everything a `DecisionStrategy` sees when a round is checked
+#[derive(Debug, Clone, Default)]
+pub struct TrailSummary {
+    /// Counted trail of each value in the round, in no particular order
+    pub scores: Vec<TrailScore>,
+
+    /// Distinct sources backing any value in the round
+    pub participants: usize,
+
+    /// Configured `consensus_threshold`
+    pub threshold: f64,
+
+    /// Configured `min_quorum`
+    pub min_quorum: usize,
+
+    /// Value the round currently holds, if any
+    pub incumbent: Option<ConsensusValue>,
+
+    /// Configured `incumbent_bias`, added to the incumbent's score
+    pub incumbent_bias: f64,
+}
+
+impl TrailSummary {
+    /// Bias `score` upward if `value` is the incumbent
+    pub fn biased(&self, value: &ConsensusValue, score: f64) -> f64 {
+        if self.incumbent.as_ref() == Some(value) {
+            score + self.incumbent_bias
+        } else {
+            score
+        }
+    }
+
+    /// Summed intensity of every value in the round
+    pub fn total_intensity(&self) -> f64 {
+        self.scores.iter().map(|score| score.total).sum()
+    }
+
+    /// Counted trail of `value`, if it has one
+    pub fn score(&self, value: &ConsensusValue) -> Option<&TrailScore> {
+        self.scores.iter().find(|score| score.value == *value)
+    }
+
+    /// Fraction of `min_quorum` that `score`'s sources make up, capped at 1.0
+    pub fn quorum_progress(&self, score: &TrailScore) -> f64 {
+        ratio(score.sources as f64, self.min_quorum as f64)
+    }
+}
+
+/// Rule that turns a round's trails into a consensus decision
+pub trait DecisionStrategy: Debug + Send + Sync {
+    /// Value the round settles on, or `None` if no value qualifies yet
+    fn decide(&self, trails: &TrailSummary) -> Option<ConsensusValue>;
+
+    /// How close `value` is to qualifying, in [0.0, 1.0]: 1.0 exactly when
+    /// it meets the rule's criteria (a higher-priority rival may still win)
+    ///
+    /// Defaults to the lesser of its average against the threshold and
+    /// its sources against the quorum.
+    fn progress(&self, trails: &TrailSummary, value: &ConsensusValue) -> f64 {
+        trails.score(value).map_or(0.0, |score| threshold_progress(trails, score, score.average))
+    }
+}
+
+/// Highest-priority value whose average intensity meets the threshold
+/// with quorum; intensity (incumbent biased) breaks priority ties (default)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AverageThreshold;
+
+impl DecisionStrategy for AverageThreshold {
+    fn decide(&self, trails: &TrailSummary) -> Option<ConsensusValue> {
+        best_by_priority(trails, |score| score.average)
+    }
+}
+
+/// Like `AverageThreshold`, but each value's average is scaled by the
+/// share of the round's participants backing it, so a strong trail from
+/// a small faction loses to a broadly supported one
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuorumWeighted;
+
+impl DecisionStrategy for QuorumWeighted {
+    fn decide(&self, trails: &TrailSummary) -> Option<ConsensusValue> {
+        let participants = trails.participants.max(1) as f64;
+        best_by_priority(trails, |score| score.average * score.sources as f64 / participants)
+    }
+
+    fn progress(&self, trails: &TrailSummary, value: &ConsensusValue) -> f64 {
+        let participants = trails.participants.max(1) as f64;
+        trails.score(value).map_or(0.0, |score| {
+            threshold_progress(trails, score, score.average * score.sources as f64 / participants)
+        })
+    }
+}
+
+/// Value holding more than half of the round's total intensity, with
+/// quorum; ignores priority and the threshold
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MajorityIntensity;
+
+impl DecisionStrategy for MajorityIntensity {
+    fn decide(&self, trails: &TrailSummary) -> Option<ConsensusValue> {
+        let half = trails.total_intensity() / 2.0;
+        trails.scores
+            .iter()
+            .find(|score| score.sources >= trails.min_quorum && score.total > half)
+            .map(|score| score.value.clone())
+    }
+
+    fn progress(&self, trails: &TrailSummary, value: &ConsensusValue) -> f64 {
+        let Some(score) = trails.score(value) else {
+            return 0.0;
+        };
+
+        let half = trails.total_intensity() / 2.0;
+        if score.sources >= trails.min_quorum && score.total > half {
+            return 1.0;
+        }
+
+        // An exact half is not a majority, so stop just short of 1.0
+        ratio(score.total, half)
+            .min(trails.quorum_progress(score))
+            .min(1.0 - f64::EPSILON)
+    }
+}
+
+/// `measured` against `required`, in [0.0, 1.0]; nothing required is met
+fn ratio(measured: f64, required: f64) -> f64 {
+    if required <= 0.0 {
+        return 1.0;
+    }
+    (measured / required).clamp(0.0, 1.0)
+}
+
+/// Lesser of `measured` against the threshold and `score`'s quorum progress
+fn threshold_progress(trails: &TrailSummary, score: &TrailScore, measured: f64) -> f64 {
+    ratio(measured, trails.threshold).min(trails.quorum_progress(score))
+}
+
+/// Highest-priority value whose `measure` meets the threshold with quorum,
+/// ties between priorities going to the larger (incumbent-biased) measure
+fn best_by_priority(trails: &TrailSummary, measure: impl Fn(&TrailScore) -> f64) -> Option<ConsensusValue> {
+    let mut best: Option<(&ConsensusValue, u8, f64)> = None;
+
+    for score in &trails.scores {
+        let measured = measure(score);
+        if measured < trails.threshold || score.sources < trails.min_quorum {
+            continue;
+        }
+
+        let measured = trails.biased(&score.value, measured);
+        let better = match best {
+            Some((_, priority, best_measured)) => (score.priority, measured) > (priority, best_measured),
+            None => true,
+        };
+        if better {
+            best = Some((&score.value, score.priority, measured));
+        }
+    }
+
+    best.map(|(value, _, _)| value.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(name: &str, sources: usize, each: f64) -> TrailScore {
+        TrailScore {
+            value: ConsensusValue::from_string(name),
+            priority: 0,
+            average: each,
+            total: each * sources as f64,
+            sources,
+        }
+    }
+
+    /// One source strongly backing `loud` against three lukewarm backers of `broad`
+    fn contested() -> TrailSummary {
+        TrailSummary {
+            scores: vec![score("loud", 1, 1.0), score("broad", 3, 0.5)],
+            participants: 4,
+            threshold: 0.35,
+            min_quorum: 1,
+            incumbent: None,
+            incumbent_bias: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_strategies_disagree_on_same_trails() {
+        let trails = contested();
+        let (loud, broad) = (ConsensusValue::from_string("loud"), ConsensusValue::from_string("broad"));
+
+        // Averages 1.0 vs 0.5; weighted by share 0.25 vs 0.375; totals 1.0 vs 1.5
+        assert_eq!(AverageThreshold.decide(&trails), Some(loud));
+        assert_eq!(QuorumWeighted.decide(&trails), Some(broad.clone()));
+        assert_eq!(MajorityIntensity.decide(&trails), Some(broad));
+    }
+
+    #[test]
+    fn test_quorum_and_threshold_apply() {
+        let mut trails = contested();
+        trails.min_quorum = 2;
+        assert_eq!(AverageThreshold.decide(&trails), Some(ConsensusValue::from_string("broad")));
+
+        trails.threshold = 0.8;
+        assert_eq!(AverageThreshold.decide(&trails), None);
+        assert_eq!(QuorumWeighted.decide(&trails), None);
+        assert_eq!(MajorityIntensity.decide(&trails), Some(ConsensusValue::from_string("broad")));
+
+        // An even split has no majority
+        trails.scores = vec![score("left", 2, 0.5), score("right", 2, 0.5)];
+        assert_eq!(MajorityIntensity.decide(&trails), None);
+        assert!(MajorityIntensity.progress(&trails, &ConsensusValue::from_string("left")) < 1.0);
+    }
+
+    #[test]
+    fn test_progress_is_full_only_when_criteria_met() {
+        let trails = contested();
+        let (loud, broad) = (ConsensusValue::from_string("loud"), ConsensusValue::from_string("broad"));
+
+        // Both averages clear 0.35, though only one value can win
+        assert_eq!(AverageThreshold.progress(&trails, &loud), 1.0);
+        assert_eq!(AverageThreshold.progress(&trails, &broad), 1.0);
+
+        // Weighted 0.25 falls short of 0.35
+        assert!((QuorumWeighted.progress(&trails, &loud) - 0.25 / 0.35).abs() < 1e-9);
+        assert_eq!(QuorumWeighted.progress(&trails, &broad), 1.0);
+
+        // 1.0 of a 1.25 half against a majority
+        assert!((MajorityIntensity.progress(&trails, &loud) - 0.8).abs() < 1e-9);
+        assert_eq!(MajorityIntensity.progress(&trails, &broad), 1.0);
+
+        let absent = ConsensusValue::from_string("absent");
+        assert_eq!(AverageThreshold.progress(&trails, &absent), 0.0);
+    }
+}
@@ -0,0 +1,48 @@
+use crate::core::types::{ConsensusValue, NodeId, RoundId, Timestamp};
+
+/// Events raised by a node while it runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeEvent {
+    /// A pheromone (when `source` is set) or a value's aggregate trail
+    /// (when `source` is `None`) exceeded its sane bound and was clamped
+    IntensityAnomaly {
+        value: ConsensusValue,
+        source: Option<NodeId>,
+        observed: f64,
+        clamped_to: f64,
+    },
+
+    /// A peer disputed a consensus value announced on the network, which
+    /// may indicate a split or an attack
+    DissentReceived {
+        node_id: NodeId,
+        value: ConsensusValue,
+        reason: String,
+    },
+
+    /// A source signed conflicting values for the same round and sequence
+    /// number and is now excluded from consensus
+    ByzantineDetected {
+        source: NodeId,
+        round: RoundId,
+        seq: u64,
+        timestamp: Timestamp,
+        values: (ConsensusValue, ConsensusValue),
+    },
+
+    /// The default round's value held long enough to become final
+    Committed { value: ConsensusValue },
+
+    /// A source withdrew its support for a value in a round
+    Withdrawn {
+        source: NodeId,
+        round: RoundId,
+        value: ConsensusValue,
+    },
+
+    /// A node became a neighbor
+    NeighborAdded { node_id: NodeId },
+
+    /// A pheromone was stored after passing every acceptance check
+    PheromoneReceived { value: ConsensusValue, source: NodeId },
+}
@@ -0,0 +1,130 @@
+use crate::core::node_state::NodeState;
+use crate::core::pheromone::Pheromone;
+use crate::core::types::NodeId;
+use std::collections::{HashMap, HashSet};
+
+/// Maximum number of pheromones accepted from a single source in one response
+pub const MAX_INGEST_PER_SOURCE: usize = 64;
+
+/// Outcome of ingesting a batch of pheromones
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IngestReport {
+    /// Pheromones verified and stored
+    pub accepted: usize,
+
+    /// Pheromones dropped for an unknown source, bad signature or wrong kind
+    pub rejected: usize,
+
+    /// Pheromones already stored, or repeated within the batch
+    pub duplicate: usize,
+
+    /// Pheromones dropped because their source exceeded `MAX_INGEST_PER_SOURCE`
+    /// or the configured `rate_limit`
+    pub rate_limited: usize,
+}
+
+impl NodeState {
+    /// Verify and store a batch of pheromones, e.g. from a `ConsensusResponse`
+    ///
+    /// Duplicates are skipped before verification and each source is capped
+    /// at `MAX_INGEST_PER_SOURCE` pheromones, so a large or padded response
+    /// cannot force unbounded signature checks.
+    pub fn ingest_response(&mut self, pheromones: Vec<Pheromone>) -> IngestReport {
+        let mut report = IngestReport::default();
+        let mut seen: HashSet<Vec<u8>> = HashSet::new();
+        let mut per_source: HashMap<NodeId, usize> = HashMap::new();
+
+        for pheromone in pheromones {
+            if !seen.insert(pheromone.signature.clone()) || self.is_stored(&pheromone) {
+                report.duplicate += 1;
+                continue;
+            }
+
+            let count = per_source.entry(pheromone.source).or_default();
+            if *count >= MAX_INGEST_PER_SOURCE {
+                report.rate_limited += 1;
+                continue;
+            }
+            *count += 1;
+
+            let rate_limited = self.stats.rate_limited;
+            if self.accept_pheromone(pheromone) {
+                report.accepted += 1;
+            } else if self.stats.rate_limited > rate_limited {
+                report.rate_limited += 1;
+            } else {
+                report.rejected += 1;
+            }
+        }
+
+        report
+    }
+
+    /// Check whether this exact pheromone is already on its value's trail
+    fn is_stored(&self, pheromone: &Pheromone) -> bool {
+        self.pheromones
+            .get(&pheromone.value)
+            .is_some_and(|trail| trail.iter().any(|p| {
+                p.source == pheromone.source
+                    && p.timestamp == pheromone.timestamp
+                    && p.signature == pheromone.signature
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::ConsensusValue;
+
+    #[test]
+    fn test_ingest_mixed_batch() {
+        let mut peer = NodeState::new(2);
+        let mut forger = NodeState::new(3);
+        let mut node = NodeState::new(1);
+        node.register_public_key(2, peer.public_key());
+
+        let valid: Vec<Pheromone> = ["a", "b", "c"]
+            .iter()
+            .map(|v| peer.emit_pheromone(ConsensusValue::from_string(v)).unwrap())
+            .collect();
+
+        // Signed by node 3 but claiming to come from node 2
+        let forged: Vec<Pheromone> = ["d", "e"]
+            .iter()
+            .map(|v| {
+                let mut p = forger.emit_pheromone(ConsensusValue::from_string(v)).unwrap();
+                p.source = 2;
+                p
+            })
+            .collect();
+
+        let mut batch = valid.clone();
+        batch.extend(forged);
+        batch.push(valid[0].clone());
+        batch.push(valid[1].clone());
+
+        let report = node.ingest_response(batch);
+        assert_eq!(report, IngestReport { accepted: 3, rejected: 2, duplicate: 2, rate_limited: 0 });
+
+        // Re-ingesting stored pheromones only yields duplicates
+        let again = node.ingest_response(valid);
+        assert_eq!(again.duplicate, 3);
+        assert_eq!(again.accepted, 0);
+    }
+
+    #[test]
+    fn test_ingest_rate_limits_per_source() {
+        let mut peer = NodeState::new(2);
+        let mut node = NodeState::new(1);
+        node.register_public_key(2, peer.public_key());
+
+        let batch: Vec<Pheromone> = (0..MAX_INGEST_PER_SOURCE + 5)
+            .map(|i| peer.emit_pheromone(ConsensusValue::from_string(&i.to_string())).unwrap())
+            .collect();
+
+        let report = node.ingest_response(batch);
+        assert_eq!(report.accepted, MAX_INGEST_PER_SOURCE);
+        assert_eq!(report.rate_limited, 5);
+    }
+}
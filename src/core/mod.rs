@@ -1,10 +1,33 @@
 pub mod pheromone;
 pub mod ant_agent;
+pub mod ant_memory;
 pub mod node_state;
 pub mod types;
+pub mod config;
+pub mod persistence;
+pub mod wal;
+pub mod events;
+pub mod ingest;
+pub mod rate_limit;
+pub mod replay;
+pub mod decision;
+pub mod store;
+pub mod snapshot;
+pub mod validator;
 
 pub use pheromone::Pheromone;
-pub use ant_agent::AntAgent;
+pub use ant_agent::{ant_origin, scoped_ant_id, AcoExponents, AntAgent, AntRole, AntSummary};
+pub use ant_memory::{AntMemory, BloomFilter, VisitedWindow};
 pub use node_state::{NodeState, SharedNodeState, NodeStats};
 pub use types::*;
-
+pub use config::{ConsensusConfig, DissentPolicy, StepIntervalBounds};
+pub use persistence::{AntPersistence, PersistedState};
+pub use wal::{WalRecord, WriteAheadLog};
+pub use events::NodeEvent;
+pub use ingest::IngestReport;
+pub use rate_limit::{RateLimit, RateLimiter};
+pub use replay::{ReplayGuard, REPLAY_WINDOW};
+pub use decision::{AverageThreshold, DecisionStrategy, MajorityIntensity, QuorumWeighted, TrailScore, TrailSummary};
+pub use store::{BoundedStore, InMemoryStore, PheromoneStore};
+pub use snapshot::{StateDiff, StateSnapshot};
+pub use validator::ValueValidator;
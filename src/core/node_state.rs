@@ -1,27 +1,88 @@
-use crate::core::types::{NodeId, ConsensusValue, Result, ConsensusError};
-use crate::core::pheromone::{Pheromone, CONSENSUS_THRESHOLD};
-use crate::core::ant_agent::AntAgent;
-use std::collections::{HashMap, HashSet};
+use crate::core::types::{AntId, NodeId, ConsensusValue, ConsensusError, Result, RoundId, Timestamp, DEFAULT_ROUND};
+use crate::core::pheromone::Pheromone;
+use crate::core::events::NodeEvent;
+use crate::core::ant_agent::{AntAgent, AntSummary};
+use crate::core::config::ConsensusConfig;
+use crate::core::decision::{AverageThreshold, DecisionStrategy, TrailScore, TrailSummary};
+use crate::core::rate_limit::RateLimiter;
+use crate::core::replay::ReplayGuard;
+use crate::core::store::{InMemoryStore, PheromoneStore};
+use crate::core::validator::ValueValidator;
+use crate::core::wal::{WalRecord, WriteAheadLog};
+use crate::crypto::merkle::{merkle_root, MerkleProof};
+use crate::crypto::registry::KeyRegistry;
+use crate::crypto::signing::{verify_batch, KeyPairWrapper, PublicKey, SignatureScheme};
+use crate::utils::random::{SharedRng, ThreadRngSource};
+use crate::utils::timing::{SharedClock, SystemClock};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use tracing::warn;
 
-/// Default evaporation rate for pheromones
+/// Default evaporation rate for pheromones (fraction lost per second)
 pub const DEFAULT_EVAPORATION_RATE: f64 = 0.01;
 
-/// Maximum number of neighbors
+/// Default maximum number of neighbors
 pub const MAX_NEIGHBORS: usize = 32;
 
+/// Voting weight of a node without an explicit weight
+pub const DEFAULT_NODE_WEIGHT: f64 = 1.0;
+
+/// Reputation of a source with no record of misbehavior
+pub const DEFAULT_REPUTATION: f64 = 1.0;
+
+/// Sequence numbers reserved per second of clock time when seeding a
+/// node's emission sequence from its clock
+const SEQ_PER_SECOND: u64 = 1000;
+
+/// Factor a source's reputation is multiplied by for each pheromone it
+/// provably misbehaved with (signed but over its rate limit, or equivocating)
+pub const REPUTATION_PENALTY: f64 = 0.5;
+
+/// Reputation a source regains for each valid pheromone, up to
+/// `DEFAULT_REPUTATION`
+pub const REPUTATION_RECOVERY: f64 = 0.01;
+
+/// Seconds for which a source's signed votes are remembered to detect
+/// equivocation
+pub const EQUIVOCATION_WINDOW_SECS: u64 = 300;
+
+/// Value a source signed per `(round, seq)` slot, and when
+type Votes = HashMap<(RoundId, u64), (ConsensusValue, Timestamp)>;
+
+/// Default time, in seconds, after which a silent neighbor is considered gone
+pub const DEFAULT_NEIGHBOR_TIMEOUT_SECS: u64 = 30;
+
+/// Most nodes remembered for the cluster-size estimate; beyond this the
+/// one heard from least recently is forgotten
+pub const MAX_OBSERVED_NODES: usize = 4096;
+
 /// Node state - manages the state of a single node in the network
 #[derive(Debug)]
 pub struct NodeState {
     /// Unique identifier for this node
     pub id: NodeId,
 
-    /// Current consensus value (if consensus reached)
+    /// Current consensus value (if consensus reached) in the default round
     pub current_value: Option<ConsensusValue>,
 
-    /// Pheromones stored at this node (grouped by value)
-    pub pheromones: HashMap<ConsensusValue, Vec<Pheromone>>,
+    /// Value committed in the default round once it led for
+    /// `config.finality_steps` consecutive checks; final once set
+    pub committed_value: Option<ConsensusValue>,
+
+    /// Every value committed in the default round, oldest first; the
+    /// leaves of `committed_root`
+    pub committed_log: Vec<ConsensusValue>,
+
+    /// Consensus reached in rounds other than `DEFAULT_ROUND`
+    pub round_values: HashMap<RoundId, ConsensusValue>,
+
+    /// Pheromones stored at this node (grouped by value); an
+    /// `InMemoryStore` unless another `PheromoneStore` is swapped in
+    pub pheromones: Box<dyn PheromoneStore>,
 
     /// Active ant agents at this node
     pub ants: Vec<AntAgent>,
@@ -29,198 +90,2525 @@ pub struct NodeState {
     /// Known neighbor nodes
     pub neighbors: HashSet<NodeId>,
 
-    /// Pheromone evaporation rate
-    pub evaporation_rate: f64,
+    /// When each neighbor was last heard from
+    pub last_seen: HashMap<NodeId, Timestamp>,
+
+    /// Neighbors known only from another node's advertisement, not yet
+    /// heard from directly; they are evicted before any other
+    advertised: HashSet<NodeId>,
+
+    /// When each node, neighbor or not, last proved it was alive with a
+    /// verified pheromone or signed message; entries older than
+    /// `config.cluster_window_secs` no longer count toward
+    /// `estimated_cluster_size`
+    observed_nodes: HashMap<NodeId, Timestamp>,
+
+    /// Unicast (TCP) addresses of nodes, learned from neighbor discovery
+    pub peer_addrs: HashMap<NodeId, SocketAddr>,
+
+    /// Latest measured latency of the link to each neighbor, used to steer
+    /// ants toward nearby nodes
+    pub link_latency: HashMap<NodeId, Duration>,
+
+    /// The `sent_ms` of the latest heartbeat heard from each neighbor and
+    /// when it arrived (ms), to echo back in this node's next heartbeat
+    heard_heartbeats: HashMap<NodeId, (u64, u64)>,
+
+    /// Algorithm parameters
+    pub config: ConsensusConfig,
+
+    /// Voting weight (stake or reputation) of each source; unknown sources
+    /// weigh `DEFAULT_NODE_WEIGHT`
+    pub weights: HashMap<NodeId, f64>,
+
+    /// Reputation of registered sources caught misbehaving, scaling their
+    /// weight; sources not listed have `DEFAULT_REPUTATION`
+    pub reputation: HashMap<NodeId, f64>,
+
+    /// When pheromones were last evaporated
+    last_evaporation: Instant,
+
+    /// Value each source signed per `(round, seq)`, and when, for spotting
+    /// equivocation
+    votes: HashMap<NodeId, Votes>,
+
+    /// Value leading the default round and for how many consecutive
+    /// checks, toward `config.finality_steps`
+    leader_streak: Option<(ConsensusValue, u32)>,
+
+    /// Sources caught equivocating, whose pheromones no longer count
+    byzantine: HashSet<NodeId>,
+
+    /// When each source withdrew its support for a value in a round;
+    /// its pheromones for that value timestamped up to then are discarded.
+    /// Entries older than `config.max_pheromone_age_secs` are forgotten,
+    /// since that bound already refuses the pheromones they cover
+    withdrawals: HashMap<(NodeId, RoundId, ConsensusValue), Timestamp>,
+
+    /// Token buckets enforcing `config.rate_limit` per source
+    rate_limiter: RateLimiter,
+
+    /// Sequence numbers recently accepted from each source; a pheromone
+    /// repeating one is refused as a replay
+    replays: ReplayGuard,
+
+    /// Sequence number of the last pheromone this node emitted
+    emitted_seq: u64,
+
+    /// Pheromones already diffused to neighbors, by source, round, value,
+    /// timestamp and seq; sending one again would only be refused as a
+    /// replay, so a trail diffuses again only once its strongest changes
+    diffused: HashSet<(NodeId, RoundId, ConsensusValue, Timestamp, u64)>,
+
+    /// Latest default-round value each peer announced consensus on, and
+    /// when the announcement arrived
+    announcements: HashMap<NodeId, (ConsensusValue, Timestamp)>,
+
+    /// Rule deciding which value, if any, a round settles on
+    pub decision_strategy: Box<dyn DecisionStrategy>,
+
+    /// Schema check run on the payload of proposed and received values
+    /// (None accepts any payload)
+    pub value_validator: Option<ValueValidator>,
+
+    /// Log that commits and round values are appended to, if attached
+    wal: Option<WriteAheadLog>,
 
     /// Statistics
     pub stats: NodeStats,
+
+    /// Events not yet collected with `drain_events`
+    pub events: Vec<NodeEvent>,
+
+    /// Randomness source for every random decision made on behalf of this node
+    pub rng: SharedRng,
+
+    /// Wall-clock time source for timestamp comparisons
+    pub clock: SharedClock,
+
+    /// This node's signing identity
+    pub key_pair: KeyPairWrapper,
+
+    /// Public keys of known nodes (including this one)
+    pub key_registry: KeyRegistry,
 }
 
 /// Node statistics
-#[derive(Debug, Default, Clone)]
+///
+/// Serializes to a JSON object of counters for monitoring, with traffic
+/// also broken down by message kind; counters missing from older snapshots
+/// read as zero.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NodeStats {
     pub pheromones_received: u64,
+    pub pheromones_rejected: u64,
     pub pheromones_emitted: u64,
     pub ants_created: u64,
     pub consensus_reached: u64,
     pub messages_sent: u64,
     pub messages_received: u64,
+    pub messages_rejected: u64,
+    pub messages_dropped: u64,
+    pub outbound_dropped: u64,
+    pub byzantine_detected: u64,
+    pub rate_limited: u64,
+    pub timestamp_rejected: u64,
+    pub replays_rejected: u64,
+    pub datagrams_truncated: u64,
+    pub datagrams_off_topic: u64,
+
+    /// Messages sent, keyed by message kind (e.g. "Heartbeat")
+    pub sent_by_kind: BTreeMap<String, u64>,
+
+    /// Messages received from other nodes, keyed by message kind
+    pub received_by_kind: BTreeMap<String, u64>,
+}
+
+impl NodeStats {
+    /// Count one message of `kind` handed to the network, in
+    /// `messages_sent` and `sent_by_kind` alike
+    pub fn record_sent(&mut self, kind: &str) {
+        self.messages_sent += 1;
+        *self.sent_by_kind.entry(kind.to_string()).or_default() += 1;
+    }
+
+    /// Count one message of `kind` received from another node
+    pub fn record_received(&mut self, kind: &str) {
+        *self.received_by_kind.entry(kind.to_string()).or_default() += 1;
+    }
+
+    /// Serialize the counters as JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self)
+            .map_err(|e| ConsensusError::Internal(format!("Serialization error: {}", e)))
+    }
+
+    /// Write the counters as JSON to `writer`
+    pub fn write_json<W: std::io::Write>(&self, writer: W) -> Result<()> {
+        serde_json::to_writer(writer, self)
+            .map_err(|e| ConsensusError::Internal(format!("Serialization error: {}", e)))
+    }
+
+    /// Parse counters previously written with `to_json` or `write_json`
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| ConsensusError::Internal(format!("Deserialization error: {}", e)))
+    }
 }
 
 impl NodeState {
-    /// Create a new node state
+    /// Create a new node state with the default configuration
     pub fn new(id: NodeId) -> Self {
+        Self::with_config(id, ConsensusConfig::default())
+    }
+
+    /// Create a new node state with the given configuration
+    pub fn with_config(id: NodeId, config: ConsensusConfig) -> Self {
+        Self::build(id, config, Arc::new(ThreadRngSource))
+    }
+
+    /// Create a new node state drawing randomness from `rng`
+    pub fn with_rng(id: NodeId, rng: SharedRng) -> Self {
+        Self::build(id, ConsensusConfig::default(), rng)
+    }
+
+    /// Assemble a node state
+    ///
+    /// A fresh key pair is generated; use `set_key_pair` to load a
+    /// persisted identity instead.
+    fn build(id: NodeId, config: ConsensusConfig, rng: SharedRng) -> Self {
+        let key_pair = KeyPairWrapper::generate()
+            .expect("system RNG failed to generate a node key pair");
+        let mut key_registry = KeyRegistry::new();
+        key_registry.register(id, key_pair.public_key());
+
         Self {
             id,
             current_value: None,
-            pheromones: HashMap::new(),
+            committed_value: None,
+            committed_log: Vec::new(),
+            round_values: HashMap::new(),
+            pheromones: Box::new(InMemoryStore::new()),
             ants: Vec::new(),
             neighbors: HashSet::new(),
-            evaporation_rate: DEFAULT_EVAPORATION_RATE,
+            last_seen: HashMap::new(),
+            advertised: HashSet::new(),
+            observed_nodes: HashMap::new(),
+            peer_addrs: HashMap::new(),
+            link_latency: HashMap::new(),
+            heard_heartbeats: HashMap::new(),
+            config,
+            weights: HashMap::new(),
+            reputation: HashMap::new(),
+            last_evaporation: Instant::now(),
+            votes: HashMap::new(),
+            leader_streak: None,
+            byzantine: HashSet::new(),
+            withdrawals: HashMap::new(),
+            rate_limiter: RateLimiter::new(),
+            replays: ReplayGuard::new(),
+            emitted_seq: 0,
+            diffused: HashSet::new(),
+            announcements: HashMap::new(),
+            decision_strategy: Box::new(AverageThreshold),
+            value_validator: None,
+            wal: None,
             stats: NodeStats::default(),
+            events: Vec::new(),
+            rng,
+            clock: Arc::new(SystemClock),
+            key_pair,
+            key_registry,
         }
     }
 
+    /// Replace this node's signing identity
+    pub fn set_key_pair(&mut self, key_pair: KeyPairWrapper) {
+        self.key_registry.register(self.id, key_pair.public_key());
+        self.key_pair = key_pair;
+    }
+
+    /// Get this node's public key
+    pub fn public_key(&self) -> PublicKey {
+        self.key_pair.public_key()
+    }
+
+    /// Register the public key of another node
+    pub fn register_public_key(&mut self, node_id: NodeId, public_key: PublicKey) {
+        self.key_registry.register(node_id, public_key);
+    }
+
+    /// Verify a pheromone against the registered key of its claimed source
+    pub fn verify_pheromone(&self, pheromone: &Pheromone) -> bool {
+        self.key_registry
+            .get(pheromone.source)
+            .map(|public_key| pheromone.verify(public_key))
+            .unwrap_or(false)
+    }
+
     /// Add a neighbor node
+    ///
+    /// A newly added neighbor is treated as seen now, so it gets a full
+    /// timeout window before it can be pruned.
     pub fn add_neighbor(&mut self, neighbor: NodeId) {
-        if neighbor != self.id {
+        self.advertised.remove(&neighbor);
+        if neighbor != self.id && !self.neighbors.contains(&neighbor) {
+            self.make_room_for_neighbor();
             self.neighbors.insert(neighbor);
+            let now = self.clock.now();
+            self.last_seen.entry(neighbor).or_insert(now);
+            self.events.push(NodeEvent::NeighborAdded { node_id: neighbor });
+        }
+    }
+
+    /// Add a neighbor that another node advertised, returning whether it
+    /// was added
+    ///
+    /// Advertisements are hearsay, so they only fill free room: a full
+    /// neighbor set is left alone, and advertised neighbors are the first
+    /// evicted until they are heard from directly.
+    pub fn add_advertised_neighbor(&mut self, neighbor: NodeId) -> bool {
+        if neighbor == self.id
+            || self.neighbors.contains(&neighbor)
+            || self.neighbors.len() >= self.config.max_neighbors.max(1)
+        {
+            return false;
+        }
+
+        self.neighbors.insert(neighbor);
+        self.advertised.insert(neighbor);
+        let now = self.clock.now();
+        self.last_seen.entry(neighbor).or_insert(now);
+        self.events.push(NodeEvent::NeighborAdded { node_id: neighbor });
+        true
+    }
+
+    /// Evict neighbors while the neighbor set is at `config.max_neighbors`:
+    /// advertised ones not yet heard from first, then the one heard from
+    /// least recently
+    fn make_room_for_neighbor(&mut self) {
+        while !self.neighbors.is_empty() && self.neighbors.len() >= self.config.max_neighbors.max(1) {
+            let stalest = self.neighbors
+                .iter()
+                .min_by_key(|neighbor| (
+                    !self.advertised.contains(neighbor),
+                    self.last_seen.get(neighbor).copied().unwrap_or(0),
+                    **neighbor,
+                ))
+                .copied();
+            if let Some(stalest) = stalest {
+                self.remove_neighbor(stalest);
+            }
         }
     }
 
     /// Remove a neighbor node
     pub fn remove_neighbor(&mut self, neighbor: NodeId) {
         self.neighbors.remove(&neighbor);
+        self.advertised.remove(&neighbor);
+        self.last_seen.remove(&neighbor);
+        self.link_latency.remove(&neighbor);
+        self.heard_heartbeats.remove(&neighbor);
+        self.announcements.remove(&neighbor);
+    }
+
+    /// Record a latency measured on the link to `neighbor`, such as a
+    /// heartbeat round trip
+    pub fn record_latency(&mut self, neighbor: NodeId, latency: Duration) {
+        if self.neighbors.contains(&neighbor) {
+            self.link_latency.insert(neighbor, latency);
+        }
+    }
+
+    /// Note a heartbeat from `neighbor` stamped `sent_ms`, heard at
+    /// `now_ms`, so the next heartbeat this node sends echoes it
+    pub fn hear_heartbeat(&mut self, neighbor: NodeId, sent_ms: u64, now_ms: u64) {
+        if self.neighbors.contains(&neighbor) {
+            self.heard_heartbeats.insert(neighbor, (sent_ms, now_ms));
+        }
+    }
+
+    /// Echoes for this node's next heartbeat: each neighbor heard since
+    /// the last, the `sent_ms` it sent and how long it was held by `now_ms`
+    pub fn take_heartbeat_echoes(&mut self, now_ms: u64) -> Vec<(NodeId, u64, u64)> {
+        self.heard_heartbeats
+            .drain()
+            .map(|(neighbor, (sent_ms, heard_ms))| (neighbor, sent_ms, now_ms.saturating_sub(heard_ms)))
+            .collect()
+    }
+
+    /// Heuristic desirability η of each neighbor, weighed by
+    /// `config.aco_exponents`: the best measured link latency over the
+    /// neighbor's (floored at 1ms), so the fastest link scores 1.0
+    ///
+    /// Neighbors without a measurement score the mean of those with one,
+    /// neither favored nor shunned. Empty until some link is measured.
+    pub fn latency_heuristic(&self) -> Vec<(NodeId, f64)> {
+        let latencies: HashMap<NodeId, f64> = self.neighbors
+            .iter()
+            .filter_map(|neighbor| Some((*neighbor, self.link_latency.get(neighbor)?.as_secs_f64().max(0.001))))
+            .collect();
+        let Some(best) = latencies.values().copied().min_by(f64::total_cmp) else {
+            return Vec::new();
+        };
+        let mean = latencies.values().map(|latency| best / latency).sum::<f64>() / latencies.len() as f64;
+
+        let mut heuristic: Vec<(NodeId, f64)> = self.neighbors
+            .iter()
+            .map(|neighbor| (*neighbor, latencies.get(neighbor).map_or(mean, |latency| best / latency)))
+            .collect();
+        heuristic.sort_by_key(|(neighbor, _)| *neighbor);
+        heuristic
+    }
+
+    /// Note that `node_id` announced consensus on `value` in the default round
+    pub fn record_announcement(&mut self, node_id: NodeId, value: ConsensusValue) {
+        if node_id != self.id {
+            let now = self.clock.now();
+            self.announcements.insert(node_id, (value, now));
+        }
+    }
+
+    /// Neighbors whose latest announcement, made within
+    /// `neighbor_timeout_secs`, is consensus on `value`
+    pub fn agreeing_neighbors(&self, value: &ConsensusValue) -> usize {
+        let now = self.clock.now();
+        self.neighbors
+            .iter()
+            .filter_map(|neighbor| self.announcements.get(neighbor))
+            .filter(|(announced, at)| {
+                announced == value && now.saturating_sub(*at) <= self.config.neighbor_timeout_secs
+            })
+            .count()
+    }
+
+    /// Value this node has settled on: the committed value once there is
+    /// one, otherwise the default round's current value
+    pub fn settled_value(&self) -> Option<&ConsensusValue> {
+        self.committed_value.as_ref().or(self.current_value.as_ref())
+    }
+
+    /// Record that a node was heard from directly, adding it as a neighbor
+    pub fn mark_seen(&mut self, neighbor: NodeId) {
+        if neighbor != self.id {
+            self.advertised.remove(&neighbor);
+            if !self.neighbors.contains(&neighbor) {
+                self.make_room_for_neighbor();
+                self.neighbors.insert(neighbor);
+                self.events.push(NodeEvent::NeighborAdded { node_id: neighbor });
+            }
+            let now = self.clock.now();
+            self.last_seen.insert(neighbor, now);
+        }
+    }
+
+    /// Record that `node` is alive, for the cluster-size estimate
+    ///
+    /// Only pass nodes that authenticated themselves, by a verified
+    /// pheromone or a signed message stamped within the window:
+    /// unauthenticated or replayed traffic is trivially spoofed, and every
+    /// node counted raises `effective_quorum`. Nodes
+    /// that fell out of the window are forgotten whenever a new one is
+    /// first observed, and at most `MAX_OBSERVED_NODES` are kept.
+    pub fn observe_node(&mut self, node: NodeId) {
+        if node == self.id {
+            return;
+        }
+
+        let now = self.clock.now();
+        if self.observed_nodes.insert(node, now).is_none() {
+            let window = self.config.cluster_window_secs;
+            self.observed_nodes.retain(|_, seen| now.saturating_sub(*seen) <= window);
+
+            while self.observed_nodes.len() > MAX_OBSERVED_NODES {
+                let stalest = self.observed_nodes
+                    .iter()
+                    .min_by_key(|(node, seen)| (**seen, **node))
+                    .map(|(node, _)| *node);
+                if let Some(stalest) = stalest {
+                    self.observed_nodes.remove(&stalest);
+                }
+            }
+        }
+    }
+
+    /// Estimated number of nodes in the cluster: this node plus every
+    /// other node observed within the last `config.cluster_window_secs`
+    pub fn estimated_cluster_size(&self) -> usize {
+        let now = self.clock.now();
+        let window = self.config.cluster_window_secs;
+        let live = self.observed_nodes
+            .values()
+            .filter(|seen| now.saturating_sub(**seen) <= window)
+            .count();
+        live + 1
     }
 
-    /// Emit a pheromone with a consensus value
-    pub fn emit_pheromone(
+    /// Distinct sources a value needs: `config.min_quorum`, raised to
+    /// `config.quorum_fraction` of the estimated cluster size when set
+    pub fn effective_quorum(&self) -> usize {
+        let Some(fraction) = self.config.quorum_fraction else {
+            return self.config.min_quorum;
+        };
+
+        let share = (fraction.clamp(0.0, 1.0) * self.estimated_cluster_size() as f64).ceil() as usize;
+        share.max(self.config.min_quorum)
+    }
+
+    /// Remove neighbors not heard from within `timeout_secs`, returning them
+    pub fn prune_stale_neighbors(&mut self, timeout_secs: u64) -> Vec<NodeId> {
+        let now = self.clock.now();
+        let stale: Vec<NodeId> = self.neighbors
+            .iter()
+            .filter(|neighbor| {
+                self.last_seen
+                    .get(neighbor)
+                    .map(|seen| now.saturating_sub(*seen) > timeout_secs)
+                    .unwrap_or(true)
+            })
+            .copied()
+            .collect();
+
+        for neighbor in &stale {
+            self.remove_neighbor(*neighbor);
+        }
+
+        stale
+    }
+
+    /// Jaccard similarity between this node's neighbor set and another's:
+    /// the size of their intersection over the size of their union
+    ///
+    /// Returns 0.0 when both sets are empty.
+    pub fn neighbor_jaccard(&self, other_neighbors: &HashSet<NodeId>) -> f64 {
+        let union = self.neighbors.union(other_neighbors).count();
+        if union == 0 {
+            return 0.0;
+        }
+
+        let intersection = self.neighbors.intersection(other_neighbors).count();
+        intersection as f64 / union as f64
+    }
+
+    /// Emit a pheromone with a consensus value, signed with this node's key
+    pub fn emit_pheromone(&mut self, value: ConsensusValue) -> Result<Pheromone> {
+        self.emit_pheromone_with_priority(value, 0)
+    }
+
+    /// Emit a pheromone carrying a signed priority, so it can preempt
+    /// lower-priority values in `check_consensus`
+    pub fn emit_pheromone_with_priority(
         &mut self,
         value: ConsensusValue,
-        private_key: &[u8],
+        priority: u8,
     ) -> Result<Pheromone> {
-        let pheromone = Pheromone::new(value.clone(), self.id, private_key)?;
-        
-        self.pheromones
-            .entry(value)
-            .or_insert_with(Vec::new)
-            .push(pheromone.clone());
+        self.emit_pheromone_with_expiry(value, priority, None)
+    }
+
+    /// Emit a pheromone that stops counting toward consensus at `expires_at`
+    pub fn emit_pheromone_with_expiry(
+        &mut self,
+        value: ConsensusValue,
+        priority: u8,
+        expires_at: Option<Timestamp>,
+    ) -> Result<Pheromone> {
+        self.emit_round_pheromone(DEFAULT_ROUND, value, priority, expires_at)
+    }
+
+    /// Emit a pheromone voting in consensus round `round`
+    pub fn emit_round_pheromone(
+        &mut self,
+        round: RoundId,
+        value: ConsensusValue,
+        priority: u8,
+        expires_at: Option<Timestamp>,
+    ) -> Result<Pheromone> {
+        let private_key = self.key_pair.private_key_bytes();
+        let seq = self.next_seq();
+        let mut pheromone = Pheromone::in_round(value, self.id, round, priority, expires_at, &private_key)?;
+        // Stamped by the node's clock, which `with_seq` signs over
+        pheromone.timestamp = self.clock.now();
+        let pheromone = pheromone.with_seq(seq, &private_key)?;
 
+        self.deposit(pheromone.clone());
         self.stats.pheromones_emitted += 1;
         Ok(pheromone)
     }
 
+    /// Next sequence number for an emitted pheromone
+    ///
+    /// Never below `SEQ_PER_SECOND` times the current time, so a restarted
+    /// node without its persisted sequence still numbers above what peers
+    /// saw from it before, unless it emitted faster than that.
+    fn next_seq(&mut self) -> u64 {
+        let floor = self.clock.now().saturating_mul(SEQ_PER_SECOND);
+        self.emitted_seq = (self.emitted_seq + 1).max(floor);
+        self.emitted_seq
+    }
+
+    /// Sequence number of the last pheromone emitted by this node
+    pub fn emitted_seq(&self) -> u64 {
+        self.emitted_seq
+    }
+
+    /// Continue numbering emitted pheromones above `seq`
+    pub(crate) fn resume_seq(&mut self, seq: u64) {
+        self.emitted_seq = self.emitted_seq.max(seq);
+    }
+
+    /// Highest sequence number accepted from `source` that is still
+    /// remembered, if any
+    pub fn last_seq(&self, source: NodeId) -> Option<u64> {
+        self.replays.highest(source)
+    }
+
     /// Receive a pheromone from another node
+    ///
+    /// The trail for each value holds at most one pheromone per source and
+    /// round: a repeat from a source already on the trail reinforces that source's
+    /// entry (capped at the initial intensity) instead of appending a
+    /// duplicate, so one node re-broadcasting cannot dominate the trail.
     pub fn receive_pheromone(&mut self, pheromone: Pheromone) {
-        let value = pheromone.value.clone();
-        self.pheromones
-            .entry(value)
-            .or_insert_with(Vec::new)
-            .push(pheromone);
-
+        self.events.push(NodeEvent::PheromoneReceived {
+            value: pheromone.value.clone(),
+            source: pheromone.source,
+        });
+        self.record_vote(&pheromone);
+        self.deposit(pheromone);
         self.stats.pheromones_received += 1;
     }
 
-    /// Evaporate all pheromones (reduce intensity over time)
-    pub fn evaporate_pheromones(&mut self) {
-        let mut to_remove = Vec::new();
+    /// Remember the value a source signed for the pheromone's round and
+    /// sequence number, flagging the source as Byzantine if it signed a
+    /// different value for the same slot
+    ///
+    /// A source numbers each pheromone it emits afresh, so distinct
+    /// proposals never share a slot however quickly they follow each other.
+    /// Unsequenced pheromones claim no slot and are not checked.
+    fn record_vote(&mut self, pheromone: &Pheromone) {
+        if pheromone.seq == 0 || pheromone.source == self.id || self.byzantine.contains(&pheromone.source) {
+            return;
+        }
 
-        for (value, pheromones) in &mut self.pheromones {
-            pheromones.retain_mut(|p| {
-                p.evaporate(self.evaporation_rate);
-                !p.should_remove()
-            });
+        let slot = (pheromone.round, pheromone.seq);
+        let votes = self.votes.entry(pheromone.source).or_default();
+        let conflicting = match votes.get(&slot) {
+            Some((earlier, _)) if *earlier != pheromone.value => earlier.clone(),
+            Some(_) => return,
+            None => {
+                votes.insert(slot, (pheromone.value.clone(), pheromone.timestamp));
+                return;
+            }
+        };
+
+        self.byzantine.insert(pheromone.source);
+        self.votes.remove(&pheromone.source);
+        self.penalize(pheromone.source);
+        self.stats.byzantine_detected += 1;
+        self.events.push(NodeEvent::ByzantineDetected {
+            source: pheromone.source,
+            round: pheromone.round,
+            seq: pheromone.seq,
+            timestamp: pheromone.timestamp,
+            values: (conflicting, pheromone.value.clone()),
+        });
+    }
+
+    /// Sources caught signing conflicting values for the same round and
+    /// sequence number; their pheromones are ignored by `check_consensus`
+    pub fn flagged_byzantine(&self) -> &HashSet<NodeId> {
+        &self.byzantine
+    }
+
+    /// Withdraw `source`'s support for `value` in `round` as of `timestamp`
+    ///
+    /// The source's pheromones for the value signed at or before
+    /// `timestamp` are removed from the trail, and any that arrive later
+    /// (from ants still on the move, or replayed) are discarded; a pheromone
+    /// timestamped after the withdrawal counts again. If the round had
+    /// settled on `value` it is cleared so the next check re-examines the
+    /// trails, unless the value was already committed.
+    pub fn withdraw(&mut self, source: NodeId, round: RoundId, value: &ConsensusValue, timestamp: Timestamp) {
+        let withdrawn_at = self.withdrawals
+            .entry((source, round, value.clone()))
+            .or_insert(timestamp);
+        *withdrawn_at = (*withdrawn_at).max(timestamp);
+        let withdrawn_at = *withdrawn_at;
 
-            if pheromones.is_empty() {
-                to_remove.push(value.clone());
+        if let Some(trail) = self.pheromones.get_mut(value) {
+            trail.retain(|p| !(p.source == source && p.round == round && p.timestamp <= withdrawn_at));
+            if trail.is_empty() {
+                self.pheromones.remove(value);
             }
         }
 
-        for value in to_remove {
-            self.pheromones.remove(&value);
+        if self.round_value(round) == Some(value) && self.committed_value.as_ref() != Some(value) {
+            if round == DEFAULT_ROUND {
+                self.current_value = None;
+                self.leader_streak = None;
+            } else {
+                self.round_values.remove(&round);
+            }
         }
+
+        self.events.push(NodeEvent::Withdrawn { source, round, value: value.clone() });
     }
 
-    /// Check if consensus has been reached
-    pub fn check_consensus(&mut self) -> Option<ConsensusValue> {
-        // Find the value with the strongest pheromone trail
-        let mut best_value: Option<(ConsensusValue, f64)> = None;
+    /// Check whether a pheromone's source has withdrawn the support it carries
+    fn is_withdrawn(&self, pheromone: &Pheromone) -> bool {
+        self.withdrawals
+            .get(&(pheromone.source, pheromone.round, pheromone.value.clone()))
+            .is_some_and(|withdrawn_at| pheromone.timestamp <= *withdrawn_at)
+    }
 
-        for (value, pheromones) in &self.pheromones {
-            // Calculate total intensity for this value
-            let total_intensity: f64 = pheromones
-                .iter()
-                .map(|p| p.strength())
-                .sum();
+    /// Lay down a trail left by an ant passing through this node (see
+    /// `AntAgent::deposit_at`), merging with any existing entry from the
+    /// same source
+    pub fn deposit_path(&mut self, pheromone: Pheromone) {
+        self.deposit(pheromone);
+    }
+
+    /// Store a pheromone on its value's trail, merging with any existing
+    /// entry from the same source in the same round
+    ///
+    /// Pheromones whose support the source has withdrawn are dropped.
+    ///
+    /// A trail that grows past `max_pheromones_per_value` is shrunk within
+    /// the pheromone's round by `collapse_oldest`, so trail length (and the
+    /// cost of `check_consensus`) stays bounded.
+    fn deposit(&mut self, pheromone: Pheromone) {
+        if self.is_withdrawn(&pheromone) {
+            return;
+        }
 
-            // Average intensity
-            let avg_intensity = total_intensity / pheromones.len() as f64;
+        let cap = self.config.max_pheromones_per_value.max(1);
+        let intensity_cap = self.config.intensity_cap();
+        let round = pheromone.round;
+        let value = pheromone.value.clone();
+        let existing = self.pheromones
+            .get_mut(&value)
+            .and_then(|trail| trail.iter_mut().find(|p| p.source == pheromone.source && p.round == round));
 
-            if let Some((_, best_intensity)) = best_value {
-                if avg_intensity > best_intensity {
-                    best_value = Some((value.clone(), avg_intensity));
+        match existing {
+            Some(existing) => {
+                let intensity = existing.intensity + pheromone.intensity;
+                let merged = existing.merged;
+                if pheromone.timestamp >= existing.timestamp {
+                    *existing = pheromone;
+                    existing.merged = merged;
                 }
-            } else {
-                best_value = Some((value.clone(), avg_intensity));
+                existing.intensity = intensity;
+                existing.cap_intensity(intensity_cap);
             }
+            None => {
+                let mut pheromone = pheromone;
+                pheromone.cap_intensity(intensity_cap);
+                self.pheromones.insert(pheromone);
+            }
+        }
+
+        if let Some(trail) = self.pheromones.get_mut(&value).filter(|trail| trail.len() > cap) {
+            let count = trail.len() - cap;
+            Self::collapse_oldest(trail, round, count, intensity_cap);
         }
+    }
 
-        if let Some((value, intensity)) = best_value {
-            if intensity >= CONSENSUS_THRESHOLD {
-                self.current_value = Some(value.clone());
-                self.stats.consensus_reached += 1;
-                return Some(value);
+    /// Shed `count` entries of `round` from a trail, oldest first
+    ///
+    /// An older entry is folded into a newer one from the same source
+    /// (capping the aggregate at `intensity_cap` per entry folded); entries
+    /// from different sources are never blended, since quorum, weights,
+    /// withdrawals and reputation all go by source, so any excess left is
+    /// evicted outright.
+    fn collapse_oldest(trail: &mut Vec<Pheromone>, round: RoundId, count: usize, intensity_cap: f64) {
+        let (mut entries, rest): (Vec<Pheromone>, Vec<Pheromone>) =
+            std::mem::take(trail).into_iter().partition(|p| p.round == round);
+        *trail = rest;
+        entries.sort_by_key(|p| std::cmp::Reverse(p.timestamp));
+
+        // Newest first, so each source's older entries fold into its newest
+        let mut excess = count;
+        let mut kept: Vec<Pheromone> = Vec::with_capacity(entries.len());
+        for pheromone in entries {
+            match kept.iter_mut().find(|p| p.source == pheromone.source) {
+                Some(newer) if excess > 0 => {
+                    newer.absorb(pheromone);
+                    newer.cap_intensity(intensity_cap);
+                    excess -= 1;
+                }
+                _ => kept.push(pheromone),
             }
         }
 
-        None
+        kept.truncate(kept.len().saturating_sub(excess));
+        trail.extend(kept);
     }
 
-    /// Get the strongest pheromone for a given value
-    pub fn get_strongest_pheromone(&self, value: &ConsensusValue) -> Option<&Pheromone> {
-        self.pheromones
-            .get(value)?
-            .iter()
-            .max_by(|a, b| a.strength().partial_cmp(&b.strength()).unwrap())
+    /// Set the voting weight of a source; negative weights count as zero
+    pub fn set_weight(&mut self, node_id: NodeId, weight: f64) {
+        self.weights.insert(node_id, weight.max(0.0));
     }
 
-    /// Add an ant agent to this node
-    pub fn add_ant(&mut self, ant: AntAgent) {
-        self.ants.push(ant);
-        self.stats.ants_created += 1;
+    /// Voting weight of a source
+    pub fn weight(&self, node_id: NodeId) -> f64 {
+        self.weights.get(&node_id).copied().unwrap_or(DEFAULT_NODE_WEIGHT)
     }
 
-    /// Remove dead ants
-    pub fn cleanup_dead_ants(&mut self) {
-        self.ants.retain(|ant| ant.is_alive());
+    /// Reputation of a source, in (0.0, 1.0]
+    pub fn reputation_of(&self, node_id: NodeId) -> f64 {
+        self.reputation.get(&node_id).copied().unwrap_or(DEFAULT_REPUTATION)
     }
 
-    /// Update all ants (energy decay, movement)
-    pub fn update_ants(&mut self) {
-        for ant in &mut self.ants {
-            ant.update_energy();
+    /// Weight a source's pheromones carry toward consensus: its voting
+    /// weight scaled by its reputation
+    pub fn effective_weight(&self, node_id: NodeId) -> f64 {
+        self.weight(node_id) * self.reputation_of(node_id)
+    }
+
+    /// Cut a source's reputation after it provably misbehaved
+    ///
+    /// Only sources in the key registry are tracked, so made-up ids can't
+    /// grow the reputation map.
+    fn penalize(&mut self, node_id: NodeId) {
+        if self.key_registry.get(node_id).is_none() {
+            return;
         }
-        self.cleanup_dead_ants();
+
+        let reputation = self.reputation_of(node_id) * REPUTATION_PENALTY;
+        self.reputation.insert(node_id, reputation);
     }
 
-    /// Get neighbor list as vector
-    pub fn get_neighbors(&self) -> Vec<NodeId> {
-        self.neighbors.iter().copied().collect()
+    /// Let a source's reputation recover after a valid pheromone, forgetting
+    /// it once fully restored
+    fn reward(&mut self, node_id: NodeId) {
+        let Some(reputation) = self.reputation.get_mut(&node_id) else {
+            return;
+        };
+
+        *reputation += REPUTATION_RECOVERY;
+        if *reputation >= DEFAULT_REPUTATION {
+            self.reputation.remove(&node_id);
+        }
     }
 
-    /// Get statistics
-    pub fn get_stats(&self) -> &NodeStats {
-        &self.stats
+    /// Reinforce the trail for `value` by boosting each source's pheromone
+    /// by `amount`, capped at `config.intensity_cap()`
+    ///
+    /// Returns `false` if there is no trail for `value`.
+    pub fn reinforce(&mut self, value: &ConsensusValue, amount: f64) -> bool {
+        let cap = self.config.intensity_cap();
+        let Some(trail) = self.pheromones.get_mut(value) else {
+            return false;
+        };
+
+        for pheromone in trail.iter_mut() {
+            pheromone.reinforce(amount, cap);
+        }
+        true
     }
-}
 
-/// Type alias for shared node state
-pub type SharedNodeState = Arc<RwLock<NodeState>>;
+    /// Summed strength of the trail for `value` across distinct sources
+    pub fn aggregate_strength(&self, value: &ConsensusValue) -> f64 {
+        self.pheromones.aggregate(value)
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Verify a pheromone from the network and store it if its signature
+    /// checks out against the registered key of its source
+    ///
+    /// Returns `false` (and counts the rejection) when the source is unknown,
+    /// the signature is invalid, the value's kind does not match the
+    /// configured `value_kind`, or its payload fails the `value_validator`.
+    /// Pheromones from `trusted_sources` skip the signature check.
+    ///
+    /// Verified pheromones dated more than `max_pheromone_skew_secs` ahead
+    /// of this node's clock or older than `max_pheromone_age_secs` are
+    /// dropped, counted in `timestamp_rejected`.
+    ///
+    /// A sequenced pheromone whose number was already accepted from its
+    /// source (or has slid out of the source's `REPLAY_WINDOW`) is a replay
+    /// and is dropped, counted in `replays_rejected`. Numbers may arrive in
+    /// any order.
+    ///
+    /// Verified pheromones beyond the source's `rate_limit` are also dropped,
+    /// counted in `rate_limited` instead. The limit applies after
+    /// verification so forged pheromones can't spend an honest source's
+    /// budget.
+    ///
+    /// A failed signature check proves nothing about the claimed source, so
+    /// only a verified, sequenced pheromone over the rate limit cuts its
+    /// reputation; each accepted pheromone lets it recover a little.
+    pub fn accept_pheromone(&mut self, pheromone: Pheromone) -> bool {
+        if self.validate_value(&pheromone.value).is_err() {
+            self.stats.pheromones_rejected += 1;
+            return false;
+        }
 
-    #[test]
-    fn test_node_creation() {
-        let node = NodeState::new(1);
-        assert_eq!(node.id, 1);
-        assert!(node.current_value.is_none());
-        assert!(node.neighbors.is_empty());
+        let verified = self.config.trusted_sources.contains(&pheromone.source)
+            || self.verify_pheromone(&pheromone);
+        self.accept_checked(pheromone, verified)
     }
 
-    #[test]
-    fn test_add_neighbor() {
-        let mut node = NodeState::new(1);
-        node.add_neighbor(2);
-        assert!(node.neighbors.contains(&2));
+    /// Accept pheromones as `accept_pheromone` does, verifying their
+    /// signatures together with `verify_batch`
+    ///
+    /// Returns whether each pheromone was stored, in order.
+    pub fn accept_pheromones(&mut self, pheromones: Vec<Pheromone>) -> Vec<bool> {
+        // Trusted sources pass and unknown sources fail outright; the rest
+        // are checked in one batch, bar any signed under another scheme
+        let mut pending = Vec::new();
+        let mut verified: Vec<bool> = pheromones
+            .iter()
+            .enumerate()
+            .map(|(index, pheromone)| {
+                if self.config.trusted_sources.contains(&pheromone.source) {
+                    return true;
+                }
+                if let Some(public_key) = self.key_registry.get(pheromone.source) {
+                    if self.validate_value(&pheromone.value).is_err() {
+                        return false;
+                    }
+                    // Only Ed25519 signatures can share a batch
+                    if pheromone.scheme != SignatureScheme::Ed25519 {
+                        return pheromone.verify(public_key);
+                    }
+                    pending.push((index, pheromone.signing_bytes(), public_key));
+                }
+                false
+            })
+            .collect();
+
+        let items: Vec<_> = pending
+            .iter()
+            .map(|(index, message, public_key)| (message.as_slice(), &pheromones[*index].signature, *public_key))
+            .collect();
+        for ((index, _, _), valid) in pending.iter().zip(verify_batch(&items)) {
+            verified[*index] = valid;
+        }
+
+        pheromones
+            .into_iter()
+            .zip(verified)
+            .map(|(pheromone, verified)| {
+                if self.validate_value(&pheromone.value).is_err() {
+                    self.stats.pheromones_rejected += 1;
+                    return false;
+                }
+                self.accept_checked(pheromone, verified)
+            })
+            .collect()
     }
 
-    #[test]
-    fn test_consensus_check() {
-        let mut node = NodeState::new(1);
-        // Consensus check with no pheromones should return None
-        assert!(node.check_consensus().is_none());
+    /// Store a pheromone of an accepted kind whose signature check yielded
+    /// `verified` and whose intensity is well formed, applying reputation,
+    /// timestamp bounds and rate limits
+    fn accept_checked(&mut self, pheromone: Pheromone, verified: bool) -> bool {
+        if !verified {
+            self.stats.pheromones_rejected += 1;
+            return false;
+        }
+
+        // Intensity isn't signed, so a degenerate one may be any relay's
+        // doing and costs the source no reputation
+        if !pheromone.has_valid_intensity() {
+            self.stats.pheromones_rejected += 1;
+            return false;
+        }
+
+        if !self.within_acceptance_window(&pheromone) {
+            self.stats.timestamp_rejected += 1;
+            return false;
+        }
+
+        if pheromone.seq != 0 && self.replays.is_replay(pheromone.source, pheromone.seq) {
+            // A reused sequence number on a different value is equivocation
+            // rather than a replay
+            self.record_vote(&pheromone);
+            self.stats.replays_rejected += 1;
+            return false;
+        }
+
+        if let Some(limit) = self.config.rate_limit {
+            if !self.rate_limiter.allow(pheromone.source, self.clock.now(), limit) {
+                self.stats.rate_limited += 1;
+                // A fresh sequence number shows the source itself sent it
+                if pheromone.seq != 0 {
+                    self.penalize(pheromone.source);
+                }
+                return false;
+            }
+        }
+
+        if pheromone.seq != 0 {
+            self.replays.record(pheromone.source, pheromone.seq, pheromone.timestamp);
+        }
+        self.reward(pheromone.source);
+        self.observe_node(pheromone.source);
+        self.receive_pheromone(pheromone);
+        true
+    }
+
+    /// Check a received pheromone's timestamp against the configured skew
+    /// and age bounds
+    fn within_acceptance_window(&self, pheromone: &Pheromone) -> bool {
+        let now = self.clock.now();
+        let too_new = self.config.max_pheromone_skew_secs
+            .is_some_and(|skew| pheromone.timestamp > now.saturating_add(skew));
+        let too_old = self.config.max_pheromone_age_secs
+            .is_some_and(|age| pheromone.timestamp < now.saturating_sub(age));
+        !too_new && !too_old
+    }
+
+    /// Accept a pheromone as `accept_pheromone` does, remembering which
+    /// neighbor delivered it so ant routing can follow real trails
+    pub fn accept_pheromone_from(&mut self, mut pheromone: Pheromone, neighbor: NodeId) -> bool {
+        pheromone.received_from = Some(neighbor);
+        self.accept_pheromone(pheromone)
+    }
+
+    /// Summed strength of the pheromones for `value` that `neighbor`
+    /// forwarded to this node, whoever their source
+    pub fn intensity_via(&self, neighbor: NodeId, value: &ConsensusValue) -> f64 {
+        self.pheromones
+            .get(value)
+            .map(|trail| {
+                trail.iter()
+                    .filter(|p| p.received_from == Some(neighbor))
+                    .map(|p| p.strength())
+                    .sum()
+            })
+            .unwrap_or(0.0)
+    }
+
+    /// Trail intensity behind each neighbor, from the pheromones it delivered
+    ///
+    /// With a `value`, this is the summed strength of that value's
+    /// pheromones received via each neighbor; without one, it is each
+    /// neighbor's strongest trail over all values. Neighbors with no trail
+    /// are omitted.
+    pub fn neighbor_intensities(&self, value: Option<&ConsensusValue>) -> Vec<(NodeId, f64)> {
+        let mut per_value: HashMap<(NodeId, &ConsensusValue), f64> = HashMap::new();
+        for (trail_value, pheromones) in self.pheromones.trails() {
+            if value.is_some_and(|v| v != trail_value) {
+                continue;
+            }
+            for pheromone in pheromones {
+                if let Some(neighbor) = pheromone.received_from {
+                    *per_value.entry((neighbor, trail_value)).or_default() += pheromone.strength();
+                }
+            }
+        }
+
+        let mut intensities: HashMap<NodeId, f64> = HashMap::new();
+        for ((neighbor, _), strength) in per_value {
+            if self.neighbors.contains(&neighbor) {
+                let best = intensities.entry(neighbor).or_default();
+                *best = best.max(strength);
+            }
+        }
+
+        let mut intensities: Vec<(NodeId, f64)> = intensities.into_iter().collect();
+        intensities.sort_unstable_by_key(|(neighbor, _)| *neighbor);
+        intensities
+    }
+
+    /// Check that this node would accept `value` as a consensus outcome:
+    /// its kind is accepted by the topic and its payload, if carried,
+    /// passes the `value_validator`
+    pub fn validate_value(&self, value: &ConsensusValue) -> Result<()> {
+        if !self.accepts_kind(value) {
+            return Err(ConsensusError::InvalidMessage(format!(
+                "value kind {:?} not accepted by this topic",
+                value.kind
+            )));
+        }
+        self.validate_payload(value)
+    }
+
+    /// Run the `value_validator`, if any, on the payload `value` carries
+    pub fn validate_payload(&self, value: &ConsensusValue) -> Result<()> {
+        match (&self.value_validator, &value.payload) {
+            (Some(validator), Some(payload)) => validator.check(payload),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check whether a value's kind is allowed by this node's topic
+    pub fn accepts_kind(&self, value: &ConsensusValue) -> bool {
+        self.config.value_kind.is_none_or(|kind| kind == value.kind)
+    }
+
+    /// Evaporate all pheromones by the wall-clock time elapsed since the
+    /// previous evaporation, so decay is independent of the step interval
+    pub fn evaporate_pheromones(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_evaporation);
+        self.last_evaporation = now;
+        self.evaporate_pheromones_elapsed(elapsed);
+    }
+
+    /// Evaporate all pheromones as if `elapsed` wall-clock time had passed,
+    /// sparing those still within the configured grace period and dropping
+    /// any that have expired
+    pub fn evaporate_pheromones_elapsed(&mut self, elapsed: Duration) {
+        let (model, rate) = (self.config.evaporation_model, self.config.evaporation_rate);
+        let grace = self.config.evaporation_grace();
+        let now = self.clock.now();
+
+        self.pheromones.evaporate_all(&mut |p| {
+            p.evaporate_after_grace(model, rate, elapsed, grace, now);
+            !p.should_remove() && !p.is_expired(now)
+        });
+
+        // Votes too old to matter for equivocation are forgotten
+        let cutoff = now.saturating_sub(EQUIVOCATION_WINDOW_SECS);
+        for votes in self.votes.values_mut() {
+            votes.retain(|_, (_, timestamp)| *timestamp >= cutoff);
+        }
+        self.votes.retain(|_, votes| !votes.is_empty());
+
+        if let Some(limit) = self.config.rate_limit {
+            self.rate_limiter.prune(now, limit);
+        }
+
+        // Replays and withdrawn support past the age bound are refused by
+        // that bound
+        if let Some(age) = self.config.max_pheromone_age_secs {
+            let cutoff = now.saturating_sub(age);
+            self.replays.prune(cutoff);
+            self.withdrawals.retain(|_, withdrawn_at| *withdrawn_at >= cutoff);
+        }
+    }
+
+    /// Intensity watchdog: clamp pheromones above the initial intensity
+    /// (which evaporation should make impossible) and scale back any value
+    /// whose summed trail exceeds `max_aggregate_intensity`
+    ///
+    /// Each correction raises a `NodeEvent::IntensityAnomaly`, which is both
+    /// returned and queued on `events`.
+    pub fn check_intensity_anomalies(&mut self) -> Vec<NodeEvent> {
+        let max_aggregate = self.config.max_aggregate_intensity;
+        let mut anomalies = Vec::new();
+
+        for (value, pheromones) in self.pheromones.trails_mut() {
+            for pheromone in pheromones.iter_mut() {
+                let bound = pheromone.max_intensity();
+                if !pheromone.intensity.is_finite() || pheromone.intensity > bound {
+                    anomalies.push(NodeEvent::IntensityAnomaly {
+                        value: value.clone(),
+                        source: Some(pheromone.source),
+                        observed: pheromone.intensity,
+                        clamped_to: bound,
+                    });
+                    pheromone.intensity = bound;
+                }
+            }
+
+            let aggregate: f64 = pheromones.iter().map(|p| p.strength()).sum();
+            if aggregate > max_aggregate {
+                let scale = max_aggregate / aggregate;
+                for pheromone in pheromones.iter_mut() {
+                    pheromone.intensity *= scale;
+                }
+                anomalies.push(NodeEvent::IntensityAnomaly {
+                    value: value.clone(),
+                    source: None,
+                    observed: aggregate,
+                    clamped_to: max_aggregate,
+                });
+            }
+        }
+
+        self.events.extend(anomalies.iter().cloned());
+        anomalies
+    }
+
+    /// Take all queued events
+    pub fn drain_events(&mut self) -> Vec<NodeEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Check whether a stored pheromone's timestamp is close enough to this
+    /// node's clock to count toward consensus
+    fn within_timestamp_drift(&self, pheromone: &Pheromone, now: Timestamp) -> bool {
+        match self.config.max_timestamp_drift {
+            Some(max_drift) => pheromone.timestamp.abs_diff(now) <= max_drift,
+            None => true,
+        }
+    }
+
+    /// Aggregate each value's trail into a `TrailScore`, counting only
+    /// unexpired pheromones within the allowed clock drift
+    ///
+    /// Each pheromone's strength is scaled by its source's weight before
+    /// averaging, so with default weights this is the plain average. Only
+    /// pheromones voting in `round` are considered, and none from sources
+    /// flagged as Byzantine.
+    fn trail_scores(&self, round: RoundId) -> Vec<TrailScore> {
+        let now = self.clock.now();
+        let mut scores = Vec::new();
+
+        for (value, pheromones) in self.pheromones.trails() {
+            // Only unexpired pheromones within the allowed clock drift are counted
+            let counted: Vec<&Pheromone> = pheromones
+                .iter()
+                .filter(|p| p.round == round && !self.byzantine.contains(&p.source))
+                .filter(|p| !p.is_expired(now) && self.within_timestamp_drift(p, now))
+                .collect();
+
+            if counted.is_empty() {
+                continue;
+            }
+
+            // Calculate total weighted intensity for this value
+            let total_intensity: f64 = counted
+                .iter()
+                .map(|p| p.strength() * self.effective_weight(p.source))
+                .sum();
+
+            // Average intensity, counting aggregates as the entries they hold
+            let entries: usize = counted.iter().map(|p| p.count()).sum();
+            let priority = counted.iter().map(|p| p.priority).max().unwrap_or(0);
+            let sources = counted
+                .iter()
+                .map(|p| p.source)
+                .collect::<HashSet<_>>()
+                .len();
+
+            scores.push(TrailScore {
+                value: value.clone(),
+                priority,
+                average: total_intensity / entries as f64,
+                total: total_intensity,
+                sources,
+            });
+        }
+
+        scores
+    }
+
+    /// Summarize `round`'s trails for the decision strategy
+    fn trail_summary(&self, round: RoundId) -> TrailSummary {
+        let participants = self.pheromones
+            .all_pheromones()
+            .filter(|p| p.round == round && !self.byzantine.contains(&p.source))
+            .map(|p| p.source)
+            .collect::<HashSet<_>>()
+            .len();
+
+        TrailSummary {
+            scores: self.trail_scores(round),
+            participants,
+            threshold: self.config.consensus_threshold,
+            min_quorum: self.effective_quorum(),
+            incumbent: self.round_value(round).cloned(),
+            incumbent_bias: self.config.incumbent_bias,
+        }
+    }
+
+    /// Check if consensus has been reached in the default round
+    ///
+    /// The outcome is chosen by `decision_strategy`. Under the default
+    /// `AverageThreshold`, among values whose trail meets the threshold and
+    /// is backed by at least `effective_quorum` distinct sources, the
+    /// highest-priority one wins; intensity only breaks ties between equal
+    /// priorities. The value already held gets `incumbent_bias` added to its
+    /// intensity for that comparison, so it only loses to a challenger that
+    /// is stronger by more than the bias.
+    pub fn check_consensus(&mut self) -> Option<ConsensusValue> {
+        self.check_round(DEFAULT_ROUND)
+    }
+
+    /// Check every round with pheromones on record, returning the value
+    /// each round that has reached consensus settled on
+    pub fn check_consensus_rounds(&mut self) -> BTreeMap<RoundId, ConsensusValue> {
+        let rounds: BTreeSet<RoundId> = self.pheromones
+            .all_pheromones()
+            .map(|p| p.round)
+            .collect();
+
+        rounds
+            .into_iter()
+            .filter_map(|round| self.check_round(round).map(|value| (round, value)))
+            .collect()
+    }
+
+    /// Consensus value held for `round`, if it has reached one
+    pub fn round_value(&self, round: RoundId) -> Option<&ConsensusValue> {
+        if round == DEFAULT_ROUND {
+            self.current_value.as_ref()
+        } else {
+            self.round_values.get(&round)
+        }
+    }
+
+    /// Check a single round for consensus, as `check_consensus` describes
+    ///
+    /// Once the default round has a `committed_value`, it is returned
+    /// without re-examining the trails.
+    pub fn check_round(&mut self, round: RoundId) -> Option<ConsensusValue> {
+        if round == DEFAULT_ROUND {
+            if let Some(committed) = &self.committed_value {
+                return Some(committed.clone());
+            }
+        }
+
+        let best_value = self.decision_strategy.decide(&self.trail_summary(round));
+        if round == DEFAULT_ROUND {
+            self.advance_finality(best_value.as_ref());
+        }
+
+        let value = best_value?;
+        if round == DEFAULT_ROUND {
+            self.current_value = Some(value.clone());
+        } else if self.round_values.insert(round, value.clone()).as_ref() != Some(&value) {
+            self.log_transition(WalRecord::RoundValue { round, value: value.clone() });
+        }
+        self.stats.consensus_reached += 1;
+        Some(value)
+    }
+
+    /// Extend or restart the default round's leader streak, committing the
+    /// leader once it has held for `finality_steps` consecutive checks
+    fn advance_finality(&mut self, leader: Option<&ConsensusValue>) {
+        let Some(required) = self.config.finality_steps else {
+            return;
+        };
+
+        let streak = match (leader, self.leader_streak.take()) {
+            (Some(leader), Some((held, steps))) if held == *leader => (held, steps + 1),
+            (Some(leader), _) => (leader.clone(), 1),
+            (None, _) => return,
+        };
+
+        if streak.1 >= required {
+            self.current_value = Some(streak.0.clone());
+            self.committed_value = Some(streak.0.clone());
+            self.committed_log.push(streak.0.clone());
+            self.log_transition(WalRecord::Committed { value: streak.0.clone() });
+            self.events.push(NodeEvent::Committed { value: streak.0 });
+        } else {
+            self.leader_streak = Some(streak);
+        }
+    }
+
+    /// Merkle root over `committed_log`, each leaf being a value's `hash`
+    ///
+    /// Light clients holding the root can check a committed value against
+    /// a `committed_proof` without replaying any pheromones.
+    pub fn committed_root(&self) -> [u8; 32] {
+        merkle_root(&self.commit_leaves())
+    }
+
+    /// Inclusion proof for the `index`th committed value, verified with
+    /// `proof.verify(&value.hash, &root)`
+    pub fn committed_proof(&self, index: usize) -> Option<MerkleProof> {
+        MerkleProof::new(&self.commit_leaves(), index)
+    }
+
+    fn commit_leaves(&self) -> Vec<[u8; 32]> {
+        self.committed_log.iter().map(|value| value.hash).collect()
+    }
+
+    /// Append commits and round values to `wal` from now on
+    ///
+    /// Pair with `load_snapshot` on startup so the log's snapshot and the
+    /// transitions after it are restored first. Transitions only replay
+    /// onto a snapshot, so a log without one is checkpointed with this
+    /// node's state before anything is appended.
+    pub fn attach_wal(&mut self, wal: WriteAheadLog) {
+        match wal.replay() {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                if let Err(e) = wal.checkpoint(self.persist()) {
+                    warn!("Failed to checkpoint write-ahead log: {}", e);
+                }
+            }
+            Err(e) => warn!("Failed to read write-ahead log: {}", e),
+        }
+        self.wal = Some(wal);
+    }
+
+    /// Append `record` to the attached write-ahead log, if any
+    fn log_transition(&self, record: WalRecord) {
+        if let Some(wal) = &self.wal {
+            if let Err(e) = wal.append(&record) {
+                warn!("Failed to log state transition: {}", e);
+            }
+        }
+    }
+
+    /// Confidence that consensus is forming in the default round, in [0.0, 1.0]
+    ///
+    /// This is the strongest trail's average intensity relative to the
+    /// consensus threshold, so 1.0 means some value has reached it.
+    pub fn consensus_confidence(&self) -> f64 {
+        let best = self.trail_scores(DEFAULT_ROUND)
+            .into_iter()
+            .map(|score| score.average)
+            .fold(0.0, f64::max);
+
+        if self.config.consensus_threshold <= 0.0 {
+            return 1.0;
+        }
+
+        (best / self.config.consensus_threshold).clamp(0.0, 1.0)
+    }
+
+    /// How close `value` is to consensus in the default round, in
+    /// [0.0, 1.0], as judged by `decision_strategy`
+    ///
+    /// Under the default strategy this is the lesser of the trail's average
+    /// intensity against the threshold and its sources against
+    /// `effective_quorum`, reaching 1.0 exactly when the value qualifies.
+    pub fn consensus_progress(&self, value: &ConsensusValue) -> f64 {
+        self.decision_strategy.progress(&self.trail_summary(DEFAULT_ROUND), value)
+    }
+
+    /// Number of distinct sources with a pheromone on the trail for `value`
+    pub fn distinct_sources(&self, value: &ConsensusValue) -> usize {
+        self.supporting_sources(value).len()
+    }
+
+    /// Sources with a pheromone on the trail for `value`
+    pub fn supporting_sources(&self, value: &ConsensusValue) -> HashSet<NodeId> {
+        self.pheromones
+            .get(value)
+            .map(|trail| trail.iter().map(|p| p.source).collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the strongest pheromone for a given value
+    pub fn get_strongest_pheromone(&self, value: &ConsensusValue) -> Option<&Pheromone> {
+        self.pheromones
+            .get(value)?
+            .iter()
+            .max_by(|a, b| a.strength().total_cmp(&b.strength()))
+    }
+
+    /// Pheromones to diffuse this step: the strongest sendable pheromone
+    /// of each of the top `diffusion_rate` fraction of trails, strongest
+    /// trail first
+    ///
+    /// At least one trail diffuses whenever the rate is positive. Merged
+    /// aggregates no longer verify, so a trail holding only those is
+    /// passed over, as is one whose strongest pheromone was already
+    /// diffused (see `mark_diffused`).
+    pub fn diffusion_candidates(&self) -> Vec<Pheromone> {
+        let rate = self.config.diffusion_rate;
+        if rate.is_nan() || rate <= 0.0 {
+            return Vec::new();
+        }
+
+        let mut trails: Vec<(f64, &Pheromone)> = self.pheromones
+            .trails()
+            .filter_map(|(value, trail)| {
+                let strongest = trail
+                    .iter()
+                    .filter(|p| p.merged == 0)
+                    .max_by(|a, b| a.strength().total_cmp(&b.strength()))?;
+                Some((self.pheromones.aggregate(value), strongest))
+            })
+            .collect();
+        trails.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let count = ((trails.len() as f64) * rate.min(1.0)).ceil() as usize;
+        trails
+            .into_iter()
+            .take(count)
+            .filter(|(_, p)| !self.diffused.contains(&Self::diffusion_key(p)))
+            .map(|(_, p)| p.clone())
+            .collect()
+    }
+
+    /// Record that `pheromones` were diffused, so `diffusion_candidates`
+    /// skips them until their trail changes, and forget any no longer stored
+    pub fn mark_diffused<'a>(&mut self, pheromones: impl IntoIterator<Item = &'a Pheromone>) {
+        self.diffused.extend(pheromones.into_iter().map(Self::diffusion_key));
+        let stored: HashSet<_> = self.pheromones.all_pheromones().map(Self::diffusion_key).collect();
+        self.diffused.retain(|key| stored.contains(key));
+    }
+
+    fn diffusion_key(pheromone: &Pheromone) -> (NodeId, RoundId, ConsensusValue, Timestamp, u64) {
+        (pheromone.source, pheromone.round, pheromone.value.clone(), pheromone.timestamp, pheromone.seq)
+    }
+
+    /// Add an ant agent to this node
+    pub fn add_ant(&mut self, ant: AntAgent) {
+        self.ants.push(ant);
+        self.stats.ants_created += 1;
+    }
+
+    /// Take over an ant relayed from `from_node` so it keeps carrying
+    /// `pheromones` (as many as it has room for), with one hop less of
+    /// `ttl`; false if the ttl is spent or an ant with this id is already here
+    pub fn adopt_ant(&mut self, ant_id: AntId, from_node: NodeId, pheromones: Vec<Pheromone>, ttl: u8) -> bool {
+        if ttl == 0 || self.ants.iter().any(|ant| ant.id == ant_id) {
+            return false;
+        }
+
+        let mut ant = AntAgent::with_config(ant_id, self.id, &self.config);
+        ant.visited_nodes.insert(from_node);
+        for pheromone in pheromones {
+            ant.pick_up(pheromone);
+        }
+        ant.ttl = ttl - 1;
+        self.add_ant(ant);
+        true
+    }
+
+    /// Summaries of the ants at this node, in the order they were added
+    pub fn ants_snapshot(&self) -> Vec<AntSummary> {
+        self.ants.iter().map(AntAgent::summary).collect()
+    }
+
+    /// Remove the ant with ID `id`, returning whether it was here
+    pub fn kill_ant(&mut self, id: AntId) -> bool {
+        let before = self.ants.len();
+        self.ants.retain(|ant| ant.id != id);
+        self.ants.len() < before
+    }
+
+    /// Remove dead ants
+    pub fn cleanup_dead_ants(&mut self) {
+        self.ants.retain(|ant| ant.is_alive());
+    }
+
+    /// Update all ants (energy decay, movement)
+    pub fn update_ants(&mut self) {
+        for ant in &mut self.ants {
+            ant.update_energy();
+        }
+        self.cleanup_dead_ants();
+    }
+
+    /// Get neighbor list as vector, sorted so iteration order is stable
+    pub fn get_neighbors(&self) -> Vec<NodeId> {
+        let mut neighbors: Vec<NodeId> = self.neighbors.iter().copied().collect();
+        neighbors.sort_unstable();
+        neighbors
+    }
+
+    /// Get statistics
+    pub fn get_stats(&self) -> &NodeStats {
+        &self.stats
+    }
+}
+
+/// Type alias for shared node state
+pub type SharedNodeState = Arc<RwLock<NodeState>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::timing::{current_timestamp, ManualClock};
+    use crate::core::pheromone::{CONSENSUS_THRESHOLD, INITIAL_PHEROMONE_INTENSITY};
+    use crate::core::types::ValueKind;
+    use crate::core::rate_limit::RateLimit;
+
+    #[test]
+    fn test_node_creation() {
+        let node = NodeState::new(1);
+        assert_eq!(node.id, 1);
+        assert!(node.current_value.is_none());
+        assert!(node.neighbors.is_empty());
+    }
+
+    #[test]
+    fn test_add_neighbor() {
+        let mut node = NodeState::new(1);
+        node.add_neighbor(2);
+        assert!(node.neighbors.contains(&2));
+    }
+
+    #[test]
+    fn test_neighbor_cap_evicts_stalest() {
+        let mut node = NodeState::new(1);
+        node.config.max_neighbors = 3;
+        let now = current_timestamp();
+        node.clock = Arc::new(ManualClock::new(now));
+
+        for neighbor in 2..=4 {
+            node.add_neighbor(neighbor);
+        }
+        node.last_seen.insert(2, now - 30);
+        node.last_seen.insert(3, now - 60);
+
+        // Node 3 is the stalest, so it makes way for node 5
+        node.add_neighbor(5);
+        assert_eq!(node.neighbors.len(), 3);
+        assert!(!node.neighbors.contains(&3));
+        assert!(!node.last_seen.contains_key(&3));
+        assert!(node.neighbors.contains(&2) && node.neighbors.contains(&4));
+
+        // Re-adding a present neighbor evicts nothing
+        node.add_neighbor(5);
+        node.mark_seen(4);
+        assert_eq!(node.neighbors.len(), 3);
+
+        // Node 2 is now the stalest
+        node.mark_seen(6);
+        assert_eq!(node.neighbors, HashSet::from([4, 5, 6]));
+    }
+
+    #[test]
+    fn test_advertised_neighbors_never_evict() {
+        let mut node = NodeState::new(1);
+        node.config.max_neighbors = 3;
+        node.clock = Arc::new(ManualClock::new(current_timestamp()));
+
+        node.mark_seen(2);
+        assert!(node.add_advertised_neighbor(3));
+        node.mark_seen(4);
+
+        // A full set ignores hearsay, however many ids it lists
+        assert!(!(100..200).any(|fake| node.add_advertised_neighbor(fake)));
+        assert_eq!(node.neighbors, HashSet::from([2, 3, 4]));
+
+        // A node heard from directly displaces the advertised one first,
+        // though node 2 is no fresher
+        node.mark_seen(5);
+        assert_eq!(node.neighbors, HashSet::from([2, 4, 5]));
+    }
+
+    #[test]
+    fn test_latency_heuristic_relative_to_best_link() {
+        let mut node = NodeState::new(1);
+        for neighbor in 2..=4 {
+            node.add_neighbor(neighbor);
+        }
+        assert!(node.latency_heuristic().is_empty());
+
+        node.record_latency(2, Duration::from_millis(10));
+        node.record_latency(3, Duration::from_millis(40));
+        // Not a neighbor, so not measured
+        node.record_latency(9, Duration::from_millis(1));
+
+        // The best link scores 1.0 and the unmeasured one the mean
+        assert_eq!(node.latency_heuristic(), vec![(2, 1.0), (3, 0.25), (4, 0.625)]);
+    }
+
+    #[test]
+    fn test_prune_stale_neighbors() {
+        let mut node = NodeState::new(1);
+        node.add_neighbor(2);
+        node.add_neighbor(3);
+        node.last_seen.insert(2, current_timestamp() - 120);
+        node.mark_seen(3);
+
+        let pruned = node.prune_stale_neighbors(60);
+
+        assert_eq!(pruned, vec![2]);
+        assert!(!node.neighbors.contains(&2));
+        assert!(!node.last_seen.contains_key(&2));
+        assert!(node.neighbors.contains(&3));
+    }
+
+    #[test]
+    fn test_neighbor_jaccard() {
+        let mut node = NodeState::new(1);
+        for neighbor in [2, 3, 4, 5] {
+            node.add_neighbor(neighbor);
+        }
+
+        // {2,3,4,5} vs {4,5,6}: intersection 2, union 5
+        let other: HashSet<NodeId> = [4, 5, 6].into_iter().collect();
+        assert!((node.neighbor_jaccard(&other) - 0.4).abs() < 1e-12);
+        assert_eq!(node.neighbor_jaccard(&node.neighbors.clone()), 1.0);
+        assert_eq!(NodeState::new(9).neighbor_jaccard(&HashSet::new()), 0.0);
+    }
+
+    #[test]
+    fn test_routing_biased_toward_strong_neighbor() {
+        use crate::core::ant_agent::AntAgent;
+        use crate::utils::random::SeededRngSource;
+
+        let mut node = NodeState::new(1);
+        node.config.trusted_sources.extend([5, 6]);
+        for neighbor in [2, 3, 4] {
+            node.add_neighbor(neighbor);
+        }
+
+        let value = ConsensusValue::from_string("routed");
+        assert!(node.accept_pheromone_from(Pheromone::new(value.clone(), 5, &[]).unwrap(), 2));
+        let mut faint = Pheromone::new(value.clone(), 6, &[]).unwrap();
+        faint.intensity = 0.05;
+        assert!(node.accept_pheromone_from(faint, 3));
+
+        let intensities = node.neighbor_intensities(Some(&value));
+        assert_eq!(intensities.len(), 2);
+        assert_eq!(intensities[0], (2, INITIAL_PHEROMONE_INTENSITY));
+
+        let ant = AntAgent::new(1, 1);
+        let rng = SeededRngSource::new(99);
+        let neighbors = node.get_neighbors();
+        let mut picks = HashMap::new();
+        for _ in 0..1000 {
+            let next = ant.select_next_node(&neighbors, &intensities, &rng).unwrap();
+            *picks.entry(next).or_insert(0) += 1;
+        }
+
+        // Weights 1.0 : 0.05 : 0.1 (unexplored default) => ~87% to node 2
+        assert!(picks[&2] > 750, "picks: {:?}", picks);
+        assert!(picks[&2] > picks[&3] + picks[&4]);
+    }
+
+    #[test]
+    fn test_ant_deposits_along_path() {
+        use crate::core::ant_agent::{AntAgent, PATH_DEPOSIT_INTENSITY};
+
+        let value = ConsensusValue::from_string("trail");
+        let mut origin = NodeState::new(1);
+        let carried = origin.emit_pheromone(value.clone()).unwrap();
+        let mut ant = AntAgent::with_pheromone(7, 1, carried);
+
+        let mut path: Vec<NodeState> = (2..=4).map(NodeState::new).collect();
+        for node in path.iter_mut() {
+            node.register_public_key(1, origin.public_key());
+        }
+
+        for node in path.iter_mut() {
+            ant.move_to(node.id);
+            ant.update_energy();
+            for deposit in ant.deposit_at(node.id) {
+                node.deposit_path(deposit);
+            }
+        }
+
+        for node in &path {
+            let strength = node.aggregate_strength(&value);
+            assert!(strength > 0.0 && strength <= PATH_DEPOSIT_INTENSITY);
+            assert!(node.verify_pheromone(&node.pheromones[&value][0]));
+        }
+        assert_eq!(ant.carried_pheromones.len(), 1);
+    }
+
+    #[test]
+    fn test_non_finite_intensity_not_stored() {
+        let mut node = NodeState::new(1);
+        let mut peer = NodeState::new(2);
+        node.register_public_key(2, peer.public_key());
+        let value = ConsensusValue::from_string("nan");
+
+        for intensity in [f64::NAN, f64::INFINITY, -1.0] {
+            let mut pheromone = peer.emit_pheromone(value.clone()).unwrap();
+            pheromone.intensity = intensity;
+            assert!(!node.accept_pheromone(pheromone));
+        }
+        assert!(!node.pheromones.contains_key(&value));
+        assert_eq!(node.stats.pheromones_rejected, 3);
+        assert!(node.get_strongest_pheromone(&value).is_none());
+    }
+
+    #[test]
+    fn test_kill_ant_by_id() {
+        use crate::core::ant_agent::AntAgent;
+
+        let value = ConsensusValue::from_string("carried");
+        let mut node = NodeState::new(1);
+        let pheromone = node.emit_pheromone(value.clone()).unwrap();
+        node.add_ant(AntAgent::with_pheromone(1, 1, pheromone));
+        node.add_ant(AntAgent::new(2, 1));
+        node.add_ant(AntAgent::new(3, 1));
+
+        let snapshot = node.ants_snapshot();
+        assert_eq!(snapshot.iter().map(|ant| ant.id).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(snapshot[0].carrying, vec![value]);
+        assert!(snapshot[1].carrying.is_empty());
+        assert!(snapshot.iter().all(|ant| ant.current_node == 1 && ant.energy > 0.0));
+
+        assert!(node.kill_ant(2));
+        assert!(!node.kill_ant(2));
+        assert_eq!(node.ants_snapshot().iter().map(|ant| ant.id).collect::<Vec<_>>(), [1, 3]);
+    }
+
+    #[test]
+    fn test_stats_json_round_trip() {
+        let mut node = NodeState::new(1);
+        node.emit_pheromone(ConsensusValue::from_string("counted")).unwrap();
+        node.stats.messages_dropped = 3;
+        node.stats.messages_rejected = 2;
+
+        let json = node.get_stats().to_json().unwrap();
+        assert_eq!(NodeStats::from_json(&json).unwrap(), node.stats);
+
+        let mut written = Vec::new();
+        node.get_stats().write_json(&mut written).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), json);
+
+        // Older snapshots without newer counters still parse
+        let partial = NodeStats::from_json(r#"{"pheromones_emitted": 1}"#).unwrap();
+        assert_eq!(partial.pheromones_emitted, 1);
+        assert_eq!(partial.messages_dropped, 0);
+    }
+
+    #[test]
+    fn test_trail_length_is_capped() {
+        let mut node = NodeState::new(1);
+        node.config.max_pheromones_per_value = 8;
+        let value = ConsensusValue::from_string("popular");
+
+        for source in 2..103 {
+            let mut pheromone = Pheromone::new(value.clone(), source, &[]).unwrap();
+            pheromone.intensity = 0.5;
+            pheromone.timestamp -= (103 - source) as u64;
+            node.receive_pheromone(pheromone);
+
+            // Each overflow sheds exactly one source, whatever the count
+            assert_eq!(node.pheromones[&value].len(), (source as usize - 1).min(8));
+        }
+
+        // Distinct sources are never blended: the newest eight are kept as-is
+        let trail = &node.pheromones[&value];
+        assert_eq!(trail.len(), 8);
+        assert!(trail.iter().all(|p| p.merged == 0 && p.source >= 95));
+        assert_eq!(node.distinct_sources(&value), 8);
+        assert!((node.aggregate_strength(&value) - 4.0).abs() < 1e-9);
+        assert!(node.check_intensity_anomalies().is_empty());
+        assert!((node.consensus_confidence() - 0.5 / CONSENSUS_THRESHOLD).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_collapse_folds_only_shared_sources() {
+        let value = ConsensusValue::from_string("folded");
+        let entry = |source, age| {
+            let mut pheromone = Pheromone::new(value.clone(), source, &[]).unwrap();
+            pheromone.intensity = 0.5;
+            pheromone.timestamp -= age;
+            pheromone
+        };
+        let mut trail = vec![entry(2, 30), entry(3, 20), entry(2, 10), entry(4, 5)];
+
+        NodeState::collapse_oldest(&mut trail, DEFAULT_ROUND, 2, 1.0);
+
+        // Source 2's older entry folds into its newer one; source 3 is evicted
+        trail.sort_by_key(|p| p.source);
+        assert_eq!(trail.iter().map(|p| (p.source, p.merged)).collect::<Vec<_>>(), vec![(2, 1), (4, 0)]);
+        assert!((trail[0].intensity - 1.0).abs() < 1e-9);
+
+        // The aggregate survives a restart as the entries it stands for
+        let mut node = NodeState::new(1);
+        for pheromone in trail {
+            node.pheromones.insert(pheromone);
+        }
+        let mut restarted = NodeState::new(1);
+        restarted.restore(node.persist()).unwrap();
+        assert_eq!(restarted.pheromones[&value].iter().map(|p| p.count()).sum::<usize>(), 3);
+    }
+
+    #[test]
+    fn test_flooding_source_is_rate_limited() {
+        let mut node = NodeState::new(1);
+        node.clock = Arc::new(ManualClock::new(current_timestamp()));
+        node.config.trusted_sources.insert(2);
+        node.config.rate_limit = Some(RateLimit { per_second: 1.0, burst: 20 });
+
+        let accepted = (0..1000)
+            .map(|i| Pheromone::new(ConsensusValue::from_string(&format!("flood-{}", i)), 2, &[]).unwrap())
+            .filter(|pheromone| node.accept_pheromone(pheromone.clone()))
+            .count();
+
+        assert_eq!(accepted, 20);
+        assert_eq!(node.pheromones.len(), 20);
+        assert_eq!(node.stats.pheromones_received, 20);
+        assert_eq!(node.stats.rate_limited, 980);
+
+        // Other sources keep their own budget
+        node.config.trusted_sources.insert(3);
+        assert!(node.accept_pheromone(Pheromone::new(ConsensusValue::from_string("quiet"), 3, &[]).unwrap()));
+    }
+
+    #[test]
+    fn test_bounded_store_keeps_active_values() {
+        use crate::core::store::BoundedStore;
+
+        let mut node = NodeState::new(1);
+        node.pheromones = Box::new(BoundedStore::new(3));
+        let active = ConsensusValue::from_string("active");
+        for source in 2..5 {
+            node.receive_pheromone(Pheromone::new(active.clone(), source, &[]).unwrap());
+        }
+
+        // Faint values churn through the spare slots, evicting one another
+        for i in 0..10 {
+            let mut faint = Pheromone::new(ConsensusValue::from_string(&format!("faint-{}", i)), 10 + i, &[]).unwrap();
+            faint.intensity = 0.05 + 0.01 * i as f64;
+            node.receive_pheromone(faint);
+        }
+
+        assert_eq!(node.pheromones.len(), 3);
+        assert!(node.pheromones.contains_key(&active));
+        assert!(node.pheromones.contains_key(&ConsensusValue::from_string("faint-9")));
+        assert!(!node.pheromones.contains_key(&ConsensusValue::from_string("faint-0")));
+        assert_eq!(node.check_consensus(), Some(active));
+    }
+
+    #[test]
+    fn test_finality_requires_sustained_lead() {
+        let mut node = NodeState::new(1);
+        node.config.finality_steps = Some(3);
+        let early = ConsensusValue::from_string("early");
+        let surging = ConsensusValue::from_string("surging");
+
+        let mut pheromone = Pheromone::new(early.clone(), 2, &[]).unwrap();
+        pheromone.intensity = 0.9;
+        node.receive_pheromone(pheromone);
+
+        // Leads for two checks, one short of finality
+        assert_eq!(node.check_consensus(), Some(early.clone()));
+        assert_eq!(node.check_consensus(), Some(early.clone()));
+        assert!(node.committed_value.is_none());
+
+        let mut pheromone = Pheromone::new(surging.clone(), 3, &[]).unwrap();
+        pheromone.intensity = 1.0;
+        node.receive_pheromone(pheromone);
+
+        // The overtaking value has to build its own streak
+        assert_eq!(node.check_consensus(), Some(surging.clone()));
+        assert_eq!(node.check_consensus(), Some(surging.clone()));
+        assert!(node.committed_value.is_none());
+        assert_eq!(node.check_consensus(), Some(surging.clone()));
+        assert_eq!(node.committed_value, Some(surging.clone()));
+        assert!(node.drain_events().contains(&NodeEvent::Committed { value: surging.clone() }));
+
+        // Once committed, a stronger rival no longer changes the outcome
+        node.pheromones.get_mut(&early).unwrap()[0].intensity = 5.0;
+        assert_eq!(node.check_consensus(), Some(surging.clone()));
+        assert_eq!(node.current_value, Some(surging));
+    }
+
+    #[test]
+    fn test_committed_root_tracks_commits() {
+        let mut node = NodeState::new(1);
+        node.config.finality_steps = Some(1);
+        let empty_root = node.committed_root();
+        let mut roots = vec![empty_root];
+
+        for (source, name) in [(2, "first"), (3, "second")] {
+            let value = ConsensusValue::from_string(name);
+            node.committed_value = None;
+            node.pheromones = Box::new(InMemoryStore::new());
+            node.receive_pheromone(Pheromone::new(value.clone(), source, &[]).unwrap());
+            assert_eq!(node.check_consensus(), Some(value));
+
+            // Each commit moves the root
+            let root = node.committed_root();
+            assert!(!roots.contains(&root));
+            roots.push(root);
+        }
+
+        let root = node.committed_root();
+        for (index, value) in node.committed_log.iter().enumerate() {
+            let proof = node.committed_proof(index).unwrap();
+            assert!(proof.verify(&value.hash, &root));
+            assert!(!proof.verify(&value.hash, &empty_root));
+        }
+        assert!(node.committed_proof(2).is_none());
+    }
+
+    #[test]
+    fn test_consensus_check() {
+        let mut node = NodeState::new(1);
+        // Consensus check with no pheromones should return None
+        assert!(node.check_consensus().is_none());
+    }
+
+    #[test]
+    fn test_clock_skewed_pheromones_excluded_from_consensus() {
+        let mut node = NodeState::new(1);
+        let value = ConsensusValue::from_string("skewed");
+        let now = current_timestamp();
+
+        for (source, timestamp) in [(2, now - 3600), (3, now + 3600)] {
+            let mut pheromone = Pheromone::new(value.clone(), source, &[]).unwrap();
+            pheromone.timestamp = timestamp;
+            node.receive_pheromone(pheromone);
+        }
+
+        node.config.max_timestamp_drift = Some(60);
+        assert!(node.check_consensus().is_none());
+        // Skewed pheromones are still stored, only excluded from aggregation
+        assert_eq!(node.pheromones[&value].len(), 2);
+
+        node.config.max_timestamp_drift = None;
+        assert_eq!(node.check_consensus(), Some(value));
+    }
+
+    #[test]
+    fn test_out_of_window_pheromones_rejected_on_receipt() {
+        let now = current_timestamp();
+        let mut node = NodeState::new(1);
+        node.clock = Arc::new(ManualClock::new(now));
+        node.config.trusted_sources.insert(2);
+        node.config.max_pheromone_skew_secs = Some(60);
+        node.config.max_pheromone_age_secs = Some(600);
+
+        let dated = |name: &str, timestamp: Timestamp| {
+            let mut pheromone = Pheromone::new(ConsensusValue::from_string(name), 2, &[]).unwrap();
+            pheromone.timestamp = timestamp;
+            pheromone
+        };
+
+        assert!(!node.accept_pheromone(dated("future", now + 3600)));
+        assert!(!node.accept_pheromone(dated("ancient", now - 86_400)));
+        assert!(node.accept_pheromone(dated("fresh", now)));
+        // The bounds themselves are inclusive
+        assert!(node.accept_pheromone(dated("edge-future", now + 60)));
+        assert!(node.accept_pheromone(dated("edge-old", now - 600)));
+
+        assert_eq!(node.stats.timestamp_rejected, 2);
+        assert_eq!(node.stats.pheromones_received, 3);
+        assert!(!node.pheromones.contains_key(&ConsensusValue::from_string("future")));
+
+        // Without bounds anything verified is stored
+        node.config.max_pheromone_skew_secs = None;
+        node.config.max_pheromone_age_secs = None;
+        assert!(node.accept_pheromone(dated("future", now + 3600)));
+        assert!(node.accept_pheromone(dated("ancient", now - 86_400)));
+    }
+
+    #[test]
+    fn test_withdrawals_forgotten_past_age_bound() {
+        let now = current_timestamp();
+        let clock = Arc::new(ManualClock::new(now));
+        let mut node = NodeState::new(1);
+        node.clock = clock.clone();
+        node.config.max_pheromone_age_secs = Some(600);
+
+        let value = ConsensusValue::from_string("withdrawn");
+        node.withdraw(2, DEFAULT_ROUND, &value, now);
+        node.evaporate_pheromones_elapsed(Duration::ZERO);
+        assert_eq!(node.withdrawals.len(), 1);
+
+        clock.advance(601);
+        node.evaporate_pheromones_elapsed(Duration::ZERO);
+        assert!(node.withdrawals.is_empty());
+    }
+
+    #[test]
+    fn test_replayed_pheromone_rejected() {
+        let mut node = NodeState::new(1);
+        let mut peer = NodeState::new(2);
+        node.register_public_key(2, peer.public_key());
+        let value = ConsensusValue::from_string("replayed");
+
+        let first = peer.emit_pheromone(value.clone()).unwrap();
+        assert!(node.accept_pheromone(first.clone()));
+        assert_eq!(node.last_seq(2), Some(first.seq));
+
+        // The captured pheromone is refused however often it comes back
+        assert!(!node.accept_pheromone(first.clone()));
+        assert!(!node.accept_pheromone(first.clone()));
+        assert_eq!(node.stats.replays_rejected, 2);
+
+        // Bumping the sequence number breaks the signature
+        let mut bumped = first.clone();
+        bumped.seq += 1;
+        assert!(!node.accept_pheromone(bumped));
+        assert_eq!(node.stats.pheromones_rejected, 1);
+
+        let second = peer.emit_pheromone(value.clone()).unwrap();
+        assert!(second.seq > first.seq);
+        assert!(node.accept_pheromone(second.clone()));
+        assert_eq!(node.last_seq(2), Some(second.seq));
+        assert_eq!(node.stats.pheromones_received, 2);
+
+        // A restarted peer keeps numbering above what it emitted before
+        let mut restarted = NodeState::new(2);
+        restarted.set_key_pair(peer.key_pair.clone());
+        restarted.restore(peer.persist()).unwrap();
+        let third = restarted.emit_pheromone(value).unwrap();
+        assert!(node.accept_pheromone(third));
+    }
+
+    #[test]
+    fn test_out_of_order_seqs_accepted() {
+        let mut node = NodeState::new(1);
+        let mut peer = NodeState::new(2);
+        node.register_public_key(2, peer.public_key());
+
+        // An ant's pheromone overtaken by a later proposal still counts
+        let first = peer.emit_pheromone(ConsensusValue::from_string("carried")).unwrap();
+        let second = peer.emit_round_pheromone(7, ConsensusValue::from_string("other"), 0, None).unwrap();
+        assert!(node.accept_pheromone(second.clone()));
+        assert!(node.accept_pheromone(first.clone()));
+        assert_eq!(node.last_seq(2), Some(second.seq));
+
+        assert!(!node.accept_pheromone(first));
+        assert!(!node.accept_pheromone(second));
+        assert_eq!(node.stats.replays_rejected, 2);
+        assert_eq!(node.stats.pheromones_received, 2);
+    }
+
+    #[test]
+    fn test_expired_pheromone_ignored() {
+        let now = current_timestamp();
+        let clock = Arc::new(ManualClock::new(now));
+        let mut node = NodeState::new(1);
+        node.clock = clock.clone();
+
+        let value = ConsensusValue::from_string("deadline");
+        let pheromone = node.emit_pheromone_with_expiry(value.clone(), 0, Some(now + 60)).unwrap();
+        assert!(node.verify_pheromone(&pheromone));
+        assert_eq!(node.check_consensus(), Some(value.clone()));
+
+        // Past the deadline the pheromone no longer counts, despite full intensity
+        clock.advance(60);
+        assert_eq!(node.pheromones[&value][0].intensity, INITIAL_PHEROMONE_INTENSITY);
+        assert!(node.check_consensus().is_none());
+
+        node.evaporate_pheromones_elapsed(Duration::ZERO);
+        assert!(!node.pheromones.contains_key(&value));
+    }
+
+    #[test]
+    fn test_configured_evaporation_model_applies() {
+        use crate::core::pheromone::EvaporationModel;
+
+        let mut node = NodeState::new(1);
+        node.config.evaporation_model = EvaporationModel::Linear;
+        node.config.evaporation_rate = 0.1;
+        let value = ConsensusValue::from_string("linear");
+        node.receive_pheromone(Pheromone::new(value.clone(), 2, &[]).unwrap());
+
+        node.evaporate_pheromones_elapsed(Duration::from_secs(5));
+        assert!((node.pheromones[&value][0].intensity - 0.5).abs() < 1e-9);
+
+        // Linear decay hits zero within ten seconds and the trail is removed
+        node.evaporate_pheromones_elapsed(Duration::from_secs(5));
+        assert!(!node.pheromones.contains_key(&value));
+
+        node.config.evaporation_model = EvaporationModel::HalfLife { seconds: 2.0 };
+        node.receive_pheromone(Pheromone::new(value.clone(), 2, &[]).unwrap());
+        node.evaporate_pheromones_elapsed(Duration::from_secs(4));
+        assert!((node.pheromones[&value][0].intensity - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_lower_threshold_reaches_consensus_sooner() {
+        let value = ConsensusValue::from_string("tunable");
+        let mut pheromone = Pheromone::new(value.clone(), 2, &[]).unwrap();
+        pheromone.intensity = 0.6;
+
+        let mut strict = NodeState::new(1);
+        strict.receive_pheromone(pheromone.clone());
+        assert!(strict.check_consensus().is_none());
+
+        let config = ConsensusConfig {
+            consensus_threshold: 0.5,
+            ..ConsensusConfig::default()
+        };
+        let mut relaxed = NodeState::with_config(1, config);
+        relaxed.receive_pheromone(pheromone);
+        assert_eq!(relaxed.check_consensus(), Some(value));
+    }
+
+    #[test]
+    fn test_reinforcement_dedups_per_source() {
+        let mut node = NodeState::new(1);
+        let value = ConsensusValue::from_string("reinforced");
+
+        let mut first = Pheromone::new(value.clone(), 2, &[]).unwrap();
+        first.intensity = 0.4;
+        node.receive_pheromone(first);
+        let single = node.aggregate_strength(&value);
+
+        // Repeats from the same source are merged, not appended
+        for _ in 0..5 {
+            let mut repeat = Pheromone::new(value.clone(), 2, &[]).unwrap();
+            repeat.intensity = 0.4;
+            node.receive_pheromone(repeat);
+        }
+        assert_eq!(node.pheromones[&value].len(), 1);
+        assert!(node.aggregate_strength(&value) <= INITIAL_PHEROMONE_INTENSITY);
+
+        // Distinct sources add to the aggregate
+        for source in 3..=5 {
+            let mut other = Pheromone::new(value.clone(), source, &[]).unwrap();
+            other.intensity = 0.4;
+            node.receive_pheromone(other);
+        }
+        assert_eq!(node.pheromones[&value].len(), 4);
+        assert!(node.aggregate_strength(&value) > single + 1.0);
+
+        assert!(node.reinforce(&value, 1.0));
+        assert_eq!(node.aggregate_strength(&value), 4.0 * INITIAL_PHEROMONE_INTENSITY);
+        assert!(!node.reinforce(&ConsensusValue::from_string("absent"), 1.0));
+    }
+
+    #[test]
+    fn test_single_source_does_not_meet_quorum() {
+        let mut node = NodeState::new(1);
+        node.config.min_quorum = 3;
+        let value = ConsensusValue::from_string("lonely");
+
+        node.receive_pheromone(Pheromone::new(value.clone(), 2, &[]).unwrap());
+        node.reinforce(&value, 1.0);
+        assert_eq!(node.distinct_sources(&value), 1);
+        assert!(node.check_consensus().is_none());
+    }
+
+    #[test]
+    fn test_many_sources_meet_quorum() {
+        let mut node = NodeState::new(1);
+        node.config.min_quorum = 3;
+        let value = ConsensusValue::from_string("agreed");
+
+        for source in 2..=4 {
+            node.receive_pheromone(Pheromone::new(value.clone(), source, &[]).unwrap());
+        }
+        assert_eq!(node.distinct_sources(&value), 3);
+        assert_eq!(node.check_consensus(), Some(value));
+    }
+
+    #[test]
+    fn test_quorum_scales_with_cluster_estimate() {
+        let mut node = NodeState::new(1);
+        node.config.quorum_fraction = Some(0.5);
+        let value = ConsensusValue::from_string("majority");
+
+        for source in 2..=4 {
+            node.receive_pheromone(Pheromone::new(value.clone(), source, &[]).unwrap());
+        }
+        assert_eq!(node.effective_quorum(), 1);
+        assert_eq!(node.check_consensus(), Some(value.clone()));
+
+        // Three sources fall short of half of a ten-node cluster
+        for peer in 2..=10 {
+            node.observe_node(peer);
+        }
+        assert_eq!(node.estimated_cluster_size(), 10);
+        assert_eq!(node.effective_quorum(), 5);
+        assert!(node.check_consensus().is_none());
+
+        node.receive_pheromone(Pheromone::new(value.clone(), 5, &[]).unwrap());
+        node.receive_pheromone(Pheromone::new(value.clone(), 6, &[]).unwrap());
+        assert_eq!(node.check_consensus(), Some(value));
+    }
+
+    /// Node holding `incumbent` with trails for it and a challenger
+    fn contested_node(bias: f64, incumbent: f64, challenger: f64) -> (NodeState, ConsensusValue, ConsensusValue) {
+        let mut node = NodeState::new(1);
+        node.config.incumbent_bias = bias;
+        let held = ConsensusValue::from_string("incumbent");
+        let rival = ConsensusValue::from_string("challenger");
+
+        for (value, intensity, source) in [(&held, incumbent, 2), (&rival, challenger, 3)] {
+            let mut pheromone = Pheromone::new(value.clone(), source, &[]).unwrap();
+            pheromone.intensity = intensity;
+            node.receive_pheromone(pheromone);
+        }
+        node.current_value = Some(held.clone());
+        (node, held, rival)
+    }
+
+    #[test]
+    fn test_incumbent_bias_prevents_flapping() {
+        let (mut biased, held, _) = contested_node(0.05, 0.90, 0.91);
+        assert_eq!(biased.check_consensus(), Some(held));
+
+        let (mut unbiased, _, rival) = contested_node(0.0, 0.90, 0.91);
+        assert_eq!(unbiased.check_consensus(), Some(rival));
+    }
+
+    #[test]
+    fn test_decision_strategy_is_pluggable() {
+        use crate::core::decision::MajorityIntensity;
+
+        let mut node = NodeState::new(1);
+        let loud = ConsensusValue::from_string("loud");
+        let broad = ConsensusValue::from_string("broad");
+        node.receive_pheromone(Pheromone::new(loud.clone(), 2, &[]).unwrap());
+        for source in [3, 4, 5] {
+            let mut pheromone = Pheromone::new(broad.clone(), source, &[]).unwrap();
+            pheromone.intensity = 0.5;
+            node.receive_pheromone(pheromone);
+        }
+
+        assert_eq!(node.check_consensus(), Some(loud));
+
+        node.decision_strategy = Box::new(MajorityIntensity);
+        assert_eq!(node.check_consensus(), Some(broad));
+    }
+
+    #[test]
+    fn test_consensus_progress_rises_to_decision() {
+        let mut node = NodeState::new(1);
+        node.config.min_quorum = 3;
+        let value = ConsensusValue::from_string("progressing");
+        assert_eq!(node.consensus_progress(&value), 0.0);
+
+        let mut last = 0.0;
+        for (source, intensity) in [(2, 0.6), (3, 0.8), (4, 0.9), (5, 1.0)] {
+            let mut pheromone = Pheromone::new(value.clone(), source, &[]).unwrap();
+            pheromone.intensity = intensity;
+            node.receive_pheromone(pheromone);
+
+            let progress = node.consensus_progress(&value);
+            assert!(progress > last, "{} after {}", progress, last);
+            assert_eq!(progress == 1.0, node.check_consensus() == Some(value.clone()));
+            last = progress;
+        }
+        assert_eq!(last, 1.0);
+    }
+
+    #[test]
+    fn test_weighted_source_tips_consensus() {
+        let build = || {
+            let mut node = NodeState::new(1);
+            let value = ConsensusValue::from_string("weighted");
+            for (source, intensity) in [(2, 0.9), (3, 0.5)] {
+                let mut pheromone = Pheromone::new(value.clone(), source, &[]).unwrap();
+                pheromone.intensity = intensity;
+                node.receive_pheromone(pheromone);
+            }
+            (node, value)
+        };
+
+        // Equal weights: (0.9 + 0.5) / 2 = 0.7 is below the threshold
+        let (mut equal, _) = build();
+        assert_eq!(equal.weight(2), DEFAULT_NODE_WEIGHT);
+        assert_eq!(equal.check_consensus(), None);
+
+        // Doubling node 2's weight: (1.8 + 0.5) / 2 = 1.15
+        let (mut weighted, value) = build();
+        weighted.set_weight(2, 2.0);
+        assert_eq!(weighted.check_consensus(), Some(value));
+    }
+
+    #[test]
+    fn test_forged_pheromones_spare_claimed_source() {
+        let mut node = NodeState::new(1);
+        let mut suspect = NodeState::new(2);
+        let mut honest = NodeState::new(3);
+        node.register_public_key(2, suspect.public_key());
+        node.register_public_key(3, honest.public_key());
+        node.clock = Arc::new(ManualClock::new(current_timestamp()));
+        node.config.rate_limit = Some(RateLimit { per_second: 0.001, burst: 1 });
+
+        // Forgeries naming a node, registered or not, cost it nothing
+        let attacker = KeyPairWrapper::generate().unwrap();
+        for (i, source) in [2, 3, 99].into_iter().enumerate() {
+            let value = ConsensusValue::from_string(&format!("forged-{}", i));
+            let forged = Pheromone::new(value, source, &attacker.private_key_bytes()).unwrap();
+            assert!(!node.accept_pheromone(forged));
+        }
+        assert!(node.reputation.is_empty());
+
+        // Signed pheromones beyond the rate limit do
+        let suspect_value = ConsensusValue::from_string("suspect");
+        assert!(node.accept_pheromone(suspect.emit_pheromone(suspect_value.clone()).unwrap()));
+        for i in 0..3 {
+            let flood = suspect.emit_pheromone(ConsensusValue::from_string(&format!("flood-{}", i))).unwrap();
+            assert!(!node.accept_pheromone(flood));
+        }
+        assert_eq!(node.reputation_of(2), REPUTATION_PENALTY.powi(3));
+        assert_eq!(node.reputation_of(3), DEFAULT_REPUTATION);
+
+        // Valid votes from the penalized source now count for less
+        let honest_value = ConsensusValue::from_string("honest");
+        assert!(node.accept_pheromone(honest.emit_pheromone(honest_value.clone()).unwrap()));
+
+        let scores: HashMap<_, _> = node.trail_scores(DEFAULT_ROUND)
+            .into_iter()
+            .map(|score| (score.value, score.average))
+            .collect();
+        assert!(scores[&suspect_value] < scores[&honest_value] / 4.0);
+        assert_eq!(node.check_consensus(), Some(honest_value));
+    }
+
+    #[test]
+    fn test_equivocating_source_flagged() {
+        let mut node = NodeState::new(1);
+        let mut peer = NodeState::new(2);
+        node.register_public_key(2, peer.public_key());
+
+        let honest = ConsensusValue::from_string("honest");
+        let conflicting = ConsensusValue::from_string("conflicting");
+        let first = peer.emit_pheromone(honest.clone()).unwrap();
+        // The same sequence number signed over a different value
+        let private_key = peer.key_pair.private_key_bytes();
+        let second = Pheromone::new(conflicting, 2, &private_key).unwrap()
+            .with_seq(first.seq, &private_key)
+            .unwrap();
+
+        assert!(node.accept_pheromone(first));
+        assert_eq!(node.check_consensus(), Some(honest.clone()));
+        assert!(node.flagged_byzantine().is_empty());
+
+        assert!(!node.accept_pheromone(second));
+        assert!(node.flagged_byzantine().contains(&2));
+        assert_eq!(node.stats.byzantine_detected, 1);
+        assert!(node.events.iter().any(|event| matches!(event, NodeEvent::ByzantineDetected { source: 2, .. })));
+
+        // Node 2's votes no longer count, for either value
+        node.current_value = None;
+        assert_eq!(node.check_consensus(), None);
+        assert_eq!(node.consensus_confidence(), 0.0);
+    }
+
+    #[test]
+    fn test_sequential_proposals_not_flagged() {
+        let mut node = NodeState::new(1);
+        let mut peer = NodeState::new(2);
+        node.register_public_key(2, peer.public_key());
+
+        // Distinct values in the same round, well within one second
+        for name in ["first", "second", "third"] {
+            assert!(node.accept_pheromone(peer.emit_pheromone(ConsensusValue::from_string(name)).unwrap()));
+        }
+
+        assert!(node.flagged_byzantine().is_empty());
+        assert_eq!(node.stats.byzantine_detected, 0);
+    }
+
+    #[test]
+    fn test_large_swing_overrides_incumbent() {
+        let (mut node, _, rival) = contested_node(0.05, 0.85, 1.0);
+        assert_eq!(node.check_consensus(), Some(rival));
+    }
+
+    #[test]
+    fn test_stored_intensity_capped() {
+        let mut node = NodeState::new(1);
+        node.config.max_pheromone_intensity = 0.6;
+        let value = ConsensusValue::from_string("loud");
+
+        // Intensity isn't signed, so an inflated copy must not land as-is
+        let mut inflated = Pheromone::new(value.clone(), 2, &[]).unwrap();
+        inflated.intensity = 40.0;
+        node.receive_pheromone(inflated.clone());
+        for _ in 0..10 {
+            node.receive_pheromone(inflated.clone());
+            node.reinforce(&value, 1.0);
+        }
+
+        assert_eq!(node.aggregate_strength(&value), 0.6);
+        assert!(node.check_intensity_anomalies().is_empty());
+    }
+
+    #[test]
+    fn test_watchdog_clamps_over_unity_pheromone() {
+        let mut node = NodeState::new(1);
+        let value = ConsensusValue::from_string("exploded");
+        node.receive_pheromone(Pheromone::new(value.clone(), 2, &[]).unwrap());
+        assert!(matches!(node.drain_events()[..], [NodeEvent::PheromoneReceived { source: 2, .. }]));
+
+        // Deposits are capped, so corrupt the stored entry directly
+        node.pheromones.get_mut(&value).unwrap()[0].intensity = 5.0;
+
+        let anomalies = node.check_intensity_anomalies();
+
+        assert_eq!(anomalies, vec![NodeEvent::IntensityAnomaly {
+            value: value.clone(),
+            source: Some(2),
+            observed: 5.0,
+            clamped_to: INITIAL_PHEROMONE_INTENSITY,
+        }]);
+        assert_eq!(node.pheromones[&value][0].intensity, INITIAL_PHEROMONE_INTENSITY);
+        assert_eq!(node.drain_events(), anomalies);
+        assert!(node.check_intensity_anomalies().is_empty());
+    }
+
+    #[test]
+    fn test_watchdog_scales_exploded_aggregate() {
+        let mut node = NodeState::new(1);
+        node.config.max_aggregate_intensity = 2.0;
+        let value = ConsensusValue::from_string("crowded");
+        for source in 2..=5 {
+            node.receive_pheromone(Pheromone::new(value.clone(), source, &[]).unwrap());
+        }
+
+        let anomalies = node.check_intensity_anomalies();
+
+        assert_eq!(anomalies.len(), 1);
+        let aggregate: f64 = node.pheromones[&value].iter().map(|p| p.strength()).sum();
+        assert!((aggregate - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_high_priority_value_preempts() {
+        let mut node = NodeState::new(1);
+        let routine = ConsensusValue::from_string("routine");
+        let emergency = ConsensusValue::from_string("emergency");
+
+        for source in 2..=4 {
+            node.receive_pheromone(Pheromone::new(routine.clone(), source, &[]).unwrap());
+        }
+
+        let mut urgent = Pheromone::with_priority(emergency.clone(), 5, 9, &[]).unwrap();
+        urgent.intensity = CONSENSUS_THRESHOLD;
+        node.receive_pheromone(urgent);
+
+        assert_eq!(node.check_consensus(), Some(emergency));
+    }
+
+    #[test]
+    fn test_emitted_pheromones_verify() {
+        let mut node = NodeState::new(1);
+        let value = ConsensusValue::from_string("signed");
+
+        let pheromone = node.emit_pheromone(value.clone()).unwrap();
+        assert!(pheromone.verify(&node.public_key()));
+        assert!(node.verify_pheromone(&pheromone));
+
+        // A dummy-signed pheromone claiming the same source is rejected
+        let forged = Pheromone::new(value, 1, &[]).unwrap();
+        assert!(!forged.verify(&node.public_key()));
+        assert!(!node.verify_pheromone(&forged));
+    }
+
+    #[test]
+    fn test_registry_lookup_by_source() {
+        let mut emitter = NodeState::new(2);
+        let mut receiver = NodeState::new(1);
+        let pheromone = emitter.emit_pheromone(ConsensusValue::from_string("peer")).unwrap();
+
+        // Unknown sources cannot be verified
+        assert!(!receiver.verify_pheromone(&pheromone));
+
+        receiver.register_public_key(2, emitter.public_key());
+        assert!(receiver.verify_pheromone(&pheromone));
+    }
+
+    #[test]
+    fn test_trusted_source_skips_verification() {
+        let mut node = NodeState::new(1);
+        node.config.trusted_sources.insert(2);
+
+        // Unsigned pheromones would never verify, so acceptance proves the
+        // trusted source was not checked
+        let trusted = Pheromone::new(ConsensusValue::from_string("trusted"), 2, &[]).unwrap();
+        let untrusted = Pheromone::new(ConsensusValue::from_string("untrusted"), 3, &[]).unwrap();
+
+        assert!(node.accept_pheromone(trusted));
+        assert!(!node.accept_pheromone(untrusted));
+        assert_eq!(node.stats.pheromones_rejected, 1);
+    }
+
+    #[test]
+    fn test_mismatched_value_kind_rejected() {
+        let mut emitter = NodeState::new(2);
+        let mut receiver = NodeState::new(1);
+        receiver.register_public_key(2, emitter.public_key());
+        receiver.config.value_kind = Some(ValueKind::Number);
+
+        let text = emitter.emit_pheromone(ConsensusValue::from_string("42")).unwrap();
+        assert!(!receiver.accept_pheromone(text));
+        assert_eq!(receiver.stats.pheromones_rejected, 1);
+
+        let number = ConsensusValue::from_bytes(&42u64.to_be_bytes()).with_kind(ValueKind::Number);
+        let number = emitter.emit_pheromone(number).unwrap();
+        assert!(receiver.accept_pheromone(number.clone()));
+        assert!(receiver.pheromones.contains_key(&number.value));
+    }
+
+    #[test]
+    fn test_value_kind_excluded_from_equality() {
+        let bytes = ConsensusValue::from_bytes(b"same");
+        let text = ConsensusValue::from_string("same");
+        assert_ne!(bytes.kind, text.kind);
+        assert_eq!(bytes, text);
     }
 }
 
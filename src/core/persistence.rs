@@ -0,0 +1,207 @@
+use crate::core::ant_agent::AntAgent;
+use crate::core::node_state::{NodeState, NodeStats};
+use crate::core::pheromone::Pheromone;
+use crate::core::types::{ConsensusError, ConsensusValue, NodeId, Result, RoundId};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Whether live ants are included when persisting node state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AntPersistence {
+    /// Ants are not persisted; a restarted node starts exploring afresh
+    #[default]
+    Discard,
+
+    /// Ants still at this node are persisted and resume after a restart
+    Resume,
+}
+
+/// Serializable snapshot of the state a node needs to resume after a restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedState {
+    /// Node the state belongs to
+    pub node_id: NodeId,
+
+    /// Consensus value held at the time of the snapshot
+    pub current_value: Option<ConsensusValue>,
+
+    /// Committed consensus value, if finality was reached
+    #[serde(default)]
+    pub committed_value: Option<ConsensusValue>,
+
+    /// Every value committed so far, oldest first
+    #[serde(default)]
+    pub committed_log: Vec<ConsensusValue>,
+
+    /// Consensus values held in non-default rounds
+    #[serde(default)]
+    pub round_values: HashMap<RoundId, ConsensusValue>,
+
+    /// Stored pheromones
+    pub pheromones: Vec<Pheromone>,
+
+    /// Older pheromones folded into each aggregate among `pheromones`, by
+    /// its index there (`Pheromone::merged` never leaves the node otherwise)
+    #[serde(default)]
+    pub merged: BTreeMap<usize, u32>,
+
+    /// Live ants (empty unless persisted with `AntPersistence::Resume`)
+    pub ants: Vec<AntAgent>,
+
+    /// Counters at the time of the snapshot
+    #[serde(default)]
+    pub stats: NodeStats,
+
+    /// Sequence number of the last pheromone the node emitted, so a
+    /// restarted node keeps numbering above it
+    #[serde(default)]
+    pub seq: u64,
+}
+
+impl PersistedState {
+    /// Serialize to bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec(self)
+            .map_err(|e| ConsensusError::Internal(format!("Serialization error: {}", e)))
+    }
+
+    /// Deserialize from bytes
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        serde_json::from_slice(data)
+            .map_err(|e| ConsensusError::Internal(format!("Deserialization error: {}", e)))
+    }
+}
+
+impl NodeState {
+    /// Capture the state needed to resume after a restart, including ants
+    /// according to the configured `ant_persistence` policy
+    pub fn persist(&self) -> PersistedState {
+        self.persist_with(self.config.ant_persistence)
+    }
+
+    /// Capture the state needed to resume after a restart with an explicit
+    /// ant persistence policy
+    pub fn persist_with(&self, ants: AntPersistence) -> PersistedState {
+        let ants = match ants {
+            AntPersistence::Discard => Vec::new(),
+            AntPersistence::Resume => self.ants
+                .iter()
+                .filter(|ant| ant.is_alive() && ant.current_node == self.id)
+                .cloned()
+                .collect(),
+        };
+
+        let pheromones: Vec<Pheromone> = self.pheromones.all_pheromones().cloned().collect();
+        let merged = pheromones
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.merged > 0)
+            .map(|(index, p)| (index, p.merged))
+            .collect();
+
+        PersistedState {
+            node_id: self.id,
+            current_value: self.current_value.clone(),
+            committed_value: self.committed_value.clone(),
+            committed_log: self.committed_log.clone(),
+            round_values: self.round_values.clone(),
+            pheromones,
+            merged,
+            ants,
+            stats: self.stats.clone(),
+            seq: self.emitted_seq(),
+        }
+    }
+
+    /// Restore previously persisted state into this node
+    ///
+    /// Ants that had already moved to another node before the snapshot are
+    /// owned by that peer now and are dropped; ants still at this node
+    /// resume with their energy, hops and memory intact.
+    pub fn restore(&mut self, persisted: PersistedState) -> Result<()> {
+        if persisted.node_id != self.id {
+            return Err(ConsensusError::Internal(format!(
+                "Persisted state belongs to node {}, not {}",
+                persisted.node_id, self.id
+            )));
+        }
+
+        self.current_value = persisted.current_value;
+        self.committed_value = persisted.committed_value;
+        self.committed_log = persisted.committed_log;
+        // Snapshots predating the log still carry their commit
+        if self.committed_log.is_empty() {
+            self.committed_log.extend(self.committed_value.clone());
+        }
+        self.round_values = persisted.round_values;
+        self.stats = persisted.stats;
+        self.resume_seq(persisted.seq);
+
+        for (index, mut pheromone) in persisted.pheromones.into_iter().enumerate() {
+            pheromone.merged = persisted.merged.get(&index).copied().unwrap_or(0);
+            self.pheromones.insert(pheromone);
+        }
+
+        let id = self.id;
+        self.ants.extend(
+            persisted.ants
+                .into_iter()
+                .filter(|ant| ant.is_alive() && ant.current_node == id),
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_with_ants() -> NodeState {
+        let mut node = NodeState::new(1);
+        node.emit_pheromone(ConsensusValue::from_string("persisted")).unwrap();
+
+        let mut resting = AntAgent::new(10, 1);
+        resting.visited_nodes.insert(4);
+        resting.energy_level = 42.0;
+        resting.hops = 3;
+        node.add_ant(resting);
+
+        let mut departed = AntAgent::new(11, 1);
+        departed.move_to(2);
+        node.add_ant(departed);
+
+        node
+    }
+
+    #[test]
+    fn test_ants_resume_after_restore() {
+        let mut node = node_with_ants();
+        node.config.ant_persistence = AntPersistence::Resume;
+        let bytes = node.persist().to_bytes().unwrap();
+
+        let mut restarted = NodeState::new(1);
+        restarted.restore(PersistedState::from_bytes(&bytes).unwrap()).unwrap();
+
+        // Only the ant still at this node resumes
+        assert_eq!(restarted.ants.len(), 1);
+        let ant = &restarted.ants[0];
+        assert_eq!(ant.id, 10);
+        assert_eq!(ant.energy_level, 42.0);
+        assert_eq!(ant.hops, 3);
+        assert!(ant.visited_nodes.contains(&4));
+        assert_eq!(restarted.pheromones.len(), 1);
+    }
+
+    #[test]
+    fn test_discard_policy_drops_ants() {
+        let node = node_with_ants();
+        let persisted = node.persist();
+
+        let mut restarted = NodeState::new(1);
+        restarted.restore(persisted).unwrap();
+
+        assert!(restarted.ants.is_empty());
+        assert_eq!(restarted.pheromones.len(), 1);
+    }
+}
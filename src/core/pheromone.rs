@@ -1,7 +1,7 @@
-use crate::crypto::signing::{sign_message, verify_signature, PublicKey, Signature};
-use crate::core::types::{ConsensusValue, NodeId, Timestamp};
+use crate::crypto::signing::{PublicKey, Signature, SignatureScheme};
+use crate::core::types::{ConsensusValue, NodeId, RoundId, Timestamp, DEFAULT_ROUND};
 use serde::{Serialize, Deserialize};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Pheromone intensity threshold for consensus
 pub const CONSENSUS_THRESHOLD: f64 = 0.8;
@@ -12,6 +12,42 @@ pub const MIN_PHEROMONE_INTENSITY: f64 = 0.01;
 /// Initial pheromone intensity when emitted
 pub const INITIAL_PHEROMONE_INTENSITY: f64 = 1.0;
 
+/// Hard upper bound on a single pheromone's intensity; no amount of
+/// reinforcement or merging takes `strength()` past it
+pub const MAX_PHEROMONE_INTENSITY: f64 = 1.0;
+
+/// How a pheromone's intensity decays over time
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum EvaporationModel {
+    /// Lose the fraction `rate` of the remaining intensity each second, so
+    /// intensity approaches zero without reaching it (default)
+    #[default]
+    Multiplicative,
+
+    /// Lose a fixed `rate` of intensity each second, reaching zero after
+    /// `intensity / rate` seconds
+    Linear,
+
+    /// Halve every `seconds`, whatever the configured rate
+    HalfLife { seconds: f64 },
+}
+
+impl EvaporationModel {
+    /// Intensity left of `intensity` after `elapsed` under this model, for
+    /// an entry standing for `count` pheromones, never below zero
+    pub fn decay(self, intensity: f64, rate: f64, elapsed: Duration, count: usize) -> f64 {
+        let secs = elapsed.as_secs_f64();
+        let remaining = match self {
+            EvaporationModel::Multiplicative => intensity * (1.0 - rate).powf(secs),
+            // Each folded-in pheromone loses its own share
+            EvaporationModel::Linear => intensity - rate * secs * count as f64,
+            EvaporationModel::HalfLife { seconds } if seconds > 0.0 => intensity * 0.5f64.powf(secs / seconds),
+            EvaporationModel::HalfLife { .. } => 0.0,
+        };
+        remaining.max(0.0)
+    }
+}
+
 /// Pheromone structure - represents a digital trail left by nodes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pheromone {
@@ -27,8 +63,45 @@ pub struct Pheromone {
     /// Consensus value this pheromone represents
     pub value: ConsensusValue,
     
+    /// Priority of the proposal (higher preempts lower), covered by the signature
+    #[serde(default)]
+    pub priority: u8,
+
+    /// Time after which the pheromone is dead regardless of intensity,
+    /// covered by the signature
+    #[serde(default)]
+    pub expires_at: Option<Timestamp>,
+
+    /// Consensus round the pheromone votes in, covered by the signature
+    #[serde(default)]
+    pub round: RoundId,
+
+    /// Position in its source's emission sequence, covered by the
+    /// signature; receivers refuse any pheromone not above the last one
+    /// they accepted from the source, so captured pheromones can't be
+    /// replayed. 0 marks an unsequenced pheromone, which is not checked
+    #[serde(default)]
+    pub seq: u64,
+
+    /// Algorithm the signature was made with; pheromones from before
+    /// schemes were recorded are Ed25519
+    #[serde(default)]
+    pub scheme: SignatureScheme,
+
     /// Digital signature for verification
     pub signature: Signature,
+
+    /// Neighbor this node received the pheromone from (local bookkeeping,
+    /// not signed or sent over the wire)
+    #[serde(skip)]
+    pub received_from: Option<NodeId>,
+
+    /// Number of older pheromones from the same source folded into this
+    /// one when its trail exceeded `max_pheromones_per_value` (local
+    /// bookkeeping, kept across restarts by `PersistedState`; an aggregate
+    /// no longer verifies and is never sent)
+    #[serde(skip)]
+    pub merged: u32,
 }
 
 impl Pheromone {
@@ -37,49 +110,222 @@ impl Pheromone {
         value: ConsensusValue,
         source: NodeId,
         private_key: &[u8],
+    ) -> crate::core::types::Result<Self> {
+        Self::with_priority(value, source, 0, private_key)
+    }
+
+    /// Create a new pheromone with a consensus value and priority
+    pub fn with_priority(
+        value: ConsensusValue,
+        source: NodeId,
+        priority: u8,
+        private_key: &[u8],
+    ) -> crate::core::types::Result<Self> {
+        Self::with_expiry(value, source, priority, None, private_key)
+    }
+
+    /// Create a new pheromone that is only valid until `expires_at`
+    pub fn with_expiry(
+        value: ConsensusValue,
+        source: NodeId,
+        priority: u8,
+        expires_at: Option<Timestamp>,
+        private_key: &[u8],
+    ) -> crate::core::types::Result<Self> {
+        Self::in_round(value, source, DEFAULT_ROUND, priority, expires_at, private_key)
+    }
+
+    /// Create a new pheromone voting in consensus round `round`
+    pub fn in_round(
+        value: ConsensusValue,
+        source: NodeId,
+        round: RoundId,
+        priority: u8,
+        expires_at: Option<Timestamp>,
+        private_key: &[u8],
+    ) -> crate::core::types::Result<Self> {
+        Self::signed_with(SignatureScheme::Ed25519, value, source, round, priority, expires_at, private_key)
+    }
+
+    /// Create a new pheromone signed under `scheme`, with `private_key` in
+    /// that scheme's encoding
+    pub fn signed_with(
+        scheme: SignatureScheme,
+        value: ConsensusValue,
+        source: NodeId,
+        round: RoundId,
+        priority: u8,
+        expires_at: Option<Timestamp>,
+        private_key: &[u8],
     ) -> crate::core::types::Result<Self> {
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map_err(|e| crate::core::types::ConsensusError::Internal(e.to_string()))?
             .as_secs();
 
-        let message = Self::create_message(&value, timestamp, source);
-        // For now, create a dummy signature since we need proper key management
-        let signature = if private_key.is_empty() {
-            vec![0u8; 64] // Dummy signature
-        } else {
-            sign_message(&message, private_key)
-                .map_err(|e| crate::core::types::ConsensusError::Crypto(e.to_string()))?
-        };
-
-        Ok(Self {
+        let mut pheromone = Self {
             timestamp,
             intensity: INITIAL_PHEROMONE_INTENSITY,
             source,
             value,
-            signature,
-        })
+            priority,
+            expires_at,
+            round,
+            seq: 0,
+            scheme,
+            signature: Vec::new(),
+            received_from: None,
+            merged: 0,
+        };
+        pheromone.sign(private_key)?;
+        Ok(pheromone)
     }
 
-    /// Verify the pheromone's signature
+    /// Number the pheromone `seq` in its source's sequence, re-signing it
+    /// with `private_key`
+    pub fn with_seq(mut self, seq: u64, private_key: &[u8]) -> crate::core::types::Result<Self> {
+        self.seq = seq;
+        self.sign(private_key)?;
+        Ok(self)
+    }
+
+    /// Sign the pheromone's current contents under its scheme
+    fn sign(&mut self, private_key: &[u8]) -> crate::core::types::Result<()> {
+        // For now, create a dummy signature since we need proper key management
+        self.signature = if private_key.is_empty() {
+            vec![0u8; 64] // Dummy signature
+        } else {
+            self.scheme.sign(&self.signing_bytes(), private_key)
+                .map_err(crate::core::types::ConsensusError::Crypto)?
+        };
+        Ok(())
+    }
+
+    /// Verify the pheromone's signature under its recorded scheme
     pub fn verify(&self, public_key: &PublicKey) -> bool {
-        let message = Self::create_message(&self.value, self.timestamp, self.source);
-        verify_signature(&message, &self.signature, public_key).unwrap_or(false)
+        self.verify_key_bytes(public_key.as_ref())
+    }
+
+    /// Verify the pheromone's signature under its recorded scheme against
+    /// a raw public key of that scheme
+    pub fn verify_key_bytes(&self, public_key: &[u8]) -> bool {
+        self.scheme.verify(&self.signing_bytes(), &self.signature, public_key)
     }
 
-    /// Evaporate the pheromone (reduce intensity)
+    /// Canonical bytes covered by the signature
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        Self::create_message(
+            &self.value,
+            self.timestamp,
+            self.source,
+            self.round,
+            self.priority,
+            self.expires_at,
+            self.seq,
+        )
+    }
+
+    /// Evaporate the pheromone (reduce intensity) by one fixed step
     pub fn evaporate(&mut self, rate: f64) {
         self.intensity *= 1.0 - rate;
+        self.cap_intensity(MAX_PHEROMONE_INTENSITY);
+    }
+
+    /// Evaporate the pheromone in proportion to wall-clock time, where
+    /// `rate` is the fraction of intensity lost per second
+    pub fn evaporate_elapsed(&mut self, rate: f64, elapsed: Duration) {
+        self.evaporate_with(EvaporationModel::Multiplicative, rate, elapsed);
+    }
+
+    /// Evaporate the pheromone by `elapsed` wall-clock time under `model`
+    pub fn evaporate_with(&mut self, model: EvaporationModel, rate: f64, elapsed: Duration) {
+        self.intensity = model.decay(self.intensity, rate, elapsed, self.count());
+        self.cap_intensity(MAX_PHEROMONE_INTENSITY);
+    }
+
+    /// Boost the intensity by `amount`, staying within `cap`
+    pub fn reinforce(&mut self, amount: f64, cap: f64) {
+        self.intensity += amount;
+        self.cap_intensity(cap);
+    }
+
+    /// Clamp the intensity into `[0.0, cap]` per entry this pheromone
+    /// stands for, never beyond `max_intensity`
+    pub fn cap_intensity(&mut self, cap: f64) {
+        let bound = (cap * self.count() as f64).min(self.max_intensity());
+        self.intensity = if self.intensity.is_nan() {
+            0.0
+        } else {
+            self.intensity.clamp(0.0, bound.max(0.0))
+        };
     }
 
-    /// Get the current strength of the pheromone
+    /// Evaporate like `evaporate_with`, except that intensity holds while
+    /// the pheromone is younger than `grace` (measured from its timestamp
+    /// to `now`); only the part of `elapsed` past the grace period decays
+    pub fn evaporate_after_grace(
+        &mut self,
+        model: EvaporationModel,
+        rate: f64,
+        elapsed: Duration,
+        grace: Duration,
+        now: Timestamp,
+    ) {
+        if grace.is_zero() {
+            self.evaporate_with(model, rate, elapsed);
+            return;
+        }
+
+        let age = Duration::from_secs(now.saturating_sub(self.timestamp));
+        let decaying = age.saturating_sub(grace).min(elapsed);
+        if !decaying.is_zero() {
+            self.evaporate_with(model, rate, decaying);
+        }
+    }
+
+    /// Whether the stored intensity is a finite, non-negative number, as
+    /// every honestly emitted pheromone's is
+    pub fn has_valid_intensity(&self) -> bool {
+        self.intensity.is_finite() && self.intensity >= 0.0
+    }
+
+    /// Get the current strength of the pheromone, within
+    /// `[0.0, max_intensity()]` whatever its stored intensity
     pub fn strength(&self) -> f64 {
-        self.intensity
+        if self.intensity.is_nan() {
+            return 0.0;
+        }
+        self.intensity.clamp(0.0, self.max_intensity())
+    }
+
+    /// Number of pheromones this entry stands for (more than one once
+    /// older entries have been folded into it)
+    pub fn count(&self) -> usize {
+        1 + self.merged as usize
+    }
+
+    /// Highest intensity this entry can legitimately hold:
+    /// `MAX_PHEROMONE_INTENSITY` for each pheromone it stands for
+    pub fn max_intensity(&self) -> f64 {
+        MAX_PHEROMONE_INTENSITY * self.count() as f64
+    }
+
+    /// Fold `other`, an older pheromone from the same source, into this
+    /// one, summing intensity and keeping the earliest timestamp
+    pub fn absorb(&mut self, other: Pheromone) {
+        self.intensity += other.intensity;
+        self.timestamp = self.timestamp.min(other.timestamp);
+        self.merged += 1 + other.merged;
+    }
+
+    /// Check whether the pheromone has passed its expiry time at `now`
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
     }
 
     /// Check if pheromone is strong enough for consensus
     pub fn is_strong_enough(&self) -> bool {
-        self.intensity >= CONSENSUS_THRESHOLD
+        self.strength() >= CONSENSUS_THRESHOLD
     }
 
     /// Check if pheromone should be removed (too weak)
@@ -88,11 +334,36 @@ impl Pheromone {
     }
 
     /// Create message for signing
-    fn create_message(value: &ConsensusValue, timestamp: Timestamp, source: NodeId) -> Vec<u8> {
+    ///
+    /// The round is only appended outside the default round, and the
+    /// sequence number only when set, so older signatures are unchanged.
+    fn create_message(
+        value: &ConsensusValue,
+        timestamp: Timestamp,
+        source: NodeId,
+        round: RoundId,
+        priority: u8,
+        expires_at: Option<Timestamp>,
+        seq: u64,
+    ) -> Vec<u8> {
         let mut message = Vec::new();
         message.extend_from_slice(&value.hash);
+        message.push(value.algo.id());
+        message.push(value.kind.id());
         message.extend_from_slice(&timestamp.to_be_bytes());
         message.extend_from_slice(&source.to_be_bytes());
+        message.push(priority);
+        if let Some(expires_at) = expires_at {
+            message.extend_from_slice(&expires_at.to_be_bytes());
+        }
+        if round != DEFAULT_ROUND {
+            message.push(b'r');
+            message.extend_from_slice(&round.to_be_bytes());
+        }
+        if seq != 0 {
+            message.push(b's');
+            message.extend_from_slice(&seq.to_be_bytes());
+        }
         message
     }
 }
@@ -104,7 +375,7 @@ mod tests {
     #[test]
     fn test_pheromone_creation() {
         let value = ConsensusValue::from_string("test");
-        let private_key = vec![0u8; 32]; // Dummy key for testing
+        let private_key = crate::crypto::signing::KeyPairWrapper::generate().unwrap().private_key_bytes();
         
         let pheromone = Pheromone::new(value.clone(), 1, &private_key);
         assert!(pheromone.is_ok());
@@ -118,7 +389,7 @@ mod tests {
     #[test]
     fn test_pheromone_evaporation() {
         let value = ConsensusValue::from_string("test");
-        let private_key = vec![0u8; 32];
+        let private_key = crate::crypto::signing::KeyPairWrapper::generate().unwrap().private_key_bytes();
         
         let mut pheromone = Pheromone::new(value, 1, &private_key).unwrap();
         let initial_intensity = pheromone.intensity;
@@ -126,5 +397,201 @@ mod tests {
         pheromone.evaporate(0.1);
         assert!(pheromone.intensity < initial_intensity);
     }
-}
 
+    #[test]
+    fn test_reinforcement_never_exceeds_cap() {
+        let value = ConsensusValue::from_string("test");
+        let mut pheromone = Pheromone::new(value, 1, &[]).unwrap();
+
+        for _ in 0..1000 {
+            pheromone.reinforce(0.3, MAX_PHEROMONE_INTENSITY);
+            assert!(pheromone.strength() <= MAX_PHEROMONE_INTENSITY);
+        }
+        assert_eq!(pheromone.strength(), MAX_PHEROMONE_INTENSITY);
+
+        // A lower configured cap holds too, and strength never goes negative
+        pheromone.reinforce(0.0, 0.5);
+        assert_eq!(pheromone.strength(), 0.5);
+        pheromone.reinforce(-10.0, 0.5);
+        assert_eq!(pheromone.strength(), 0.0);
+
+        // Intensity set out of range (e.g. straight off the wire) still reads within bounds
+        pheromone.intensity = 50.0;
+        assert_eq!(pheromone.strength(), MAX_PHEROMONE_INTENSITY);
+        pheromone.intensity = f64::NAN;
+        assert_eq!(pheromone.strength(), 0.0);
+    }
+
+    #[test]
+    fn test_strong_enough_at_threshold() {
+        let value = ConsensusValue::from_string("test");
+        let mut pheromone = Pheromone::new(value, 1, &[]).unwrap();
+
+        pheromone.intensity = CONSENSUS_THRESHOLD;
+        assert!(pheromone.is_strong_enough());
+
+        pheromone.intensity = CONSENSUS_THRESHOLD - 1e-9;
+        assert!(!pheromone.is_strong_enough());
+
+        pheromone.intensity = 50.0;
+        assert!(pheromone.is_strong_enough());
+        pheromone.evaporate(0.0);
+        assert_eq!(pheromone.intensity, MAX_PHEROMONE_INTENSITY);
+    }
+
+    #[test]
+    fn test_priority_is_signed() {
+        let key_pair = crate::crypto::signing::KeyPairWrapper::generate().unwrap();
+        let value = ConsensusValue::from_string("test");
+
+        let mut pheromone =
+            Pheromone::with_priority(value, 1, 3, &key_pair.private_key_bytes()).unwrap();
+        assert!(pheromone.verify(&key_pair.public_key()));
+
+        pheromone.priority = 200;
+        assert!(!pheromone.verify(&key_pair.public_key()));
+    }
+
+    #[test]
+    fn test_elapsed_evaporation_matches_stepwise() {
+        let value = ConsensusValue::from_string("test");
+        let mut once = Pheromone::new(value, 1, &[]).unwrap();
+        let mut stepwise = once.clone();
+
+        once.evaporate_elapsed(0.05, Duration::from_secs(10));
+        for _ in 0..10 {
+            stepwise.evaporate_elapsed(0.05, Duration::from_secs(1));
+        }
+
+        assert!((once.intensity - stepwise.intensity).abs() < 1e-9);
+        assert!(once.intensity < INITIAL_PHEROMONE_INTENSITY);
+    }
+
+    #[test]
+    fn test_expiry_is_signed() {
+        let key_pair = crate::crypto::signing::KeyPairWrapper::generate().unwrap();
+        let value = ConsensusValue::from_string("test");
+        let mut pheromone = Pheromone::with_expiry(
+            value, 1, 0, Some(1_000), &key_pair.private_key_bytes(),
+        ).unwrap();
+
+        assert!(pheromone.verify(&key_pair.public_key()));
+        assert!(!pheromone.is_expired(999));
+        assert!(pheromone.is_expired(1_000));
+
+        pheromone.expires_at = Some(2_000);
+        assert!(!pheromone.verify(&key_pair.public_key()));
+    }
+
+    #[test]
+    fn test_round_is_signed() {
+        let key_pair = crate::crypto::signing::KeyPairWrapper::generate().unwrap();
+        let value = ConsensusValue::from_string("test");
+        let mut pheromone = Pheromone::in_round(
+            value, 1, 7, 0, None, &key_pair.private_key_bytes(),
+        ).unwrap();
+
+        assert_eq!(pheromone.round, 7);
+        assert!(pheromone.verify(&key_pair.public_key()));
+
+        pheromone.round = DEFAULT_ROUND;
+        assert!(!pheromone.verify(&key_pair.public_key()));
+    }
+
+    #[test]
+    fn test_scheme_defaults_to_ed25519() {
+        let key_pair = crate::crypto::signing::KeyPairWrapper::generate().unwrap();
+        let pheromone = Pheromone::new(
+            ConsensusValue::from_string("test"), 1, &key_pair.private_key_bytes(),
+        ).unwrap();
+        assert_eq!(pheromone.scheme, SignatureScheme::Ed25519);
+
+        // Serialized before the scheme was recorded
+        let mut json = serde_json::to_value(&pheromone).unwrap();
+        json.as_object_mut().unwrap().remove("scheme");
+        let mut legacy: Pheromone = serde_json::from_value(json).unwrap();
+        assert_eq!(legacy.scheme, SignatureScheme::Ed25519);
+        assert!(legacy.verify(&key_pair.public_key()));
+
+        // The same bytes don't verify under another algorithm
+        legacy.scheme = SignatureScheme::Bls;
+        assert!(!legacy.verify(&key_pair.public_key()));
+    }
+
+    #[cfg(feature = "bls")]
+    #[test]
+    fn test_pheromones_verify_under_their_own_scheme() {
+        use crate::crypto::bls::BlsKeyPair;
+        use crate::crypto::signing::KeyPairWrapper;
+
+        let value = ConsensusValue::from_string("agile");
+        let ed25519 = KeyPairWrapper::generate().unwrap();
+        let bls = BlsKeyPair::generate().unwrap();
+        let ed25519_key = ed25519.public_key().as_ref().to_vec();
+        let bls_key = bls.public_key().to_bytes().to_vec();
+
+        let signed = |scheme, private_key: &[u8]| {
+            Pheromone::signed_with(scheme, value.clone(), 1, DEFAULT_ROUND, 0, None, private_key).unwrap()
+        };
+        let by_ed25519 = signed(SignatureScheme::Ed25519, &ed25519.private_key_bytes());
+        let by_bls = signed(SignatureScheme::Bls, &bls.secret_bytes());
+
+        assert_eq!(by_bls.scheme, SignatureScheme::Bls);
+        assert!(by_ed25519.verify_key_bytes(&ed25519_key));
+        assert!(by_bls.verify_key_bytes(&bls_key));
+        assert!(!by_ed25519.verify_key_bytes(&bls_key));
+        assert!(!by_bls.verify_key_bytes(&ed25519_key));
+    }
+
+    #[test]
+    fn test_grace_period_holds_intensity() {
+        let value = ConsensusValue::from_string("test");
+        let mut pheromone = Pheromone::new(value, 1, &[]).unwrap();
+        let grace = Duration::from_secs(30);
+        let emitted = pheromone.timestamp;
+
+        // Within the grace period intensity holds across evaporations
+        for age in [0, 10, 20, 30] {
+            pheromone.evaporate_after_grace(EvaporationModel::Multiplicative, 0.05, Duration::from_secs(10), grace, emitted + age);
+        }
+        assert_eq!(pheromone.intensity, INITIAL_PHEROMONE_INTENSITY);
+
+        // Only the 5 seconds past the grace period decay
+        pheromone.evaporate_after_grace(EvaporationModel::Multiplicative, 0.05, Duration::from_secs(10), grace, emitted + 35);
+        let expected = INITIAL_PHEROMONE_INTENSITY * 0.95f64.powi(5);
+        assert!((pheromone.intensity - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_evaporation_model_curves() {
+        let value = ConsensusValue::from_string("test");
+        let fresh = Pheromone::new(value, 1, &[]).unwrap();
+        let second = Duration::from_secs(1);
+        let curve = |model: EvaporationModel| {
+            let mut pheromone = fresh.clone();
+            (0..30)
+                .map(|_| {
+                    pheromone.evaporate_with(model, 0.1, second);
+                    pheromone.intensity
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Linear loses 0.1 a second and bottoms out at zero after ten
+        let linear = curve(EvaporationModel::Linear);
+        assert!((linear[4] - 0.5).abs() < 1e-9);
+        assert!(linear[8] > 0.0 && linear[8] < 0.2);
+        assert!(linear[9..].iter().all(|intensity| *intensity < 1e-9 && *intensity >= 0.0));
+
+        // Multiplicative keeps shrinking without ever reaching zero
+        let multiplicative = curve(EvaporationModel::Multiplicative);
+        assert!(multiplicative.windows(2).all(|pair| pair[1] < pair[0] && pair[1] > 0.0));
+        assert!((multiplicative[29] - 0.9f64.powi(30)).abs() < 1e-9);
+
+        // Half-life ignores the rate and halves every configured period
+        let half_life = curve(EvaporationModel::HalfLife { seconds: 5.0 });
+        assert!((half_life[4] - 0.5).abs() < 1e-9);
+        assert!((half_life[9] - 0.25).abs() < 1e-9);
+        assert!((half_life[29] - 0.5f64.powi(6)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::core::types::{NodeId, Timestamp};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+/// Sustained rate and burst allowed per pheromone source
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Pheromones per second a source may deliver once its burst is spent
+    pub per_second: f64,
+
+    /// Pheromones a source may deliver back to back
+    pub burst: u32,
+}
+
+/// Token bucket of a single source
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Timestamp,
+}
+
+/// Per-source token buckets
+///
+/// Each source starts with a full bucket of `burst` tokens, spends one per
+/// pheromone and regains `per_second` tokens for every second elapsed.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    buckets: HashMap<NodeId, TokenBucket>,
+}
+
+impl RateLimiter {
+    /// Create a limiter with every source at full burst
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spend a token for `source` at `now`, returning false when its bucket
+    /// is empty
+    pub fn allow(&mut self, source: NodeId, now: Timestamp, limit: RateLimit) -> bool {
+        let burst = f64::from(limit.burst);
+        let bucket = self.buckets.entry(source).or_insert(TokenBucket {
+            tokens: burst,
+            last_refill: now,
+        });
+
+        let elapsed = now.saturating_sub(bucket.last_refill) as f64;
+        bucket.tokens = (bucket.tokens + elapsed * limit.per_second).min(burst);
+        bucket.last_refill = bucket.last_refill.max(now);
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Forget sources whose buckets have refilled completely by `now`, since
+    /// a fresh bucket behaves the same
+    pub fn prune(&mut self, now: Timestamp, limit: RateLimit) {
+        let burst = f64::from(limit.burst);
+        self.buckets.retain(|_, bucket| {
+            let elapsed = now.saturating_sub(bucket.last_refill) as f64;
+            bucket.tokens + elapsed * limit.per_second < burst
+        });
+    }
+
+    /// Number of sources currently tracked
+    pub fn len(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Whether no source is being tracked
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: RateLimit = RateLimit { per_second: 2.0, burst: 3 };
+
+    #[test]
+    fn test_burst_then_refill() {
+        let mut limiter = RateLimiter::new();
+
+        assert_eq!((0..10).filter(|_| limiter.allow(1, 100, LIMIT)).count(), 3);
+        // Other sources have their own bucket
+        assert!(limiter.allow(2, 100, LIMIT));
+
+        assert_eq!((0..10).filter(|_| limiter.allow(1, 101, LIMIT)).count(), 2);
+        assert_eq!((0..10).filter(|_| limiter.allow(1, 111, LIMIT)).count(), 3);
+    }
+
+    #[test]
+    fn test_prune_forgets_refilled_sources() {
+        let mut limiter = RateLimiter::new();
+        limiter.allow(1, 100, LIMIT);
+        limiter.allow(2, 105, LIMIT);
+
+        limiter.prune(105, LIMIT);
+        assert_eq!(limiter.len(), 1);
+        limiter.prune(110, LIMIT);
+        assert!(limiter.is_empty());
+    }
+}
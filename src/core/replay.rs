@@ -0,0 +1,114 @@
+use crate::core::types::{NodeId, Timestamp};
+use std::collections::{BTreeMap, HashMap};
+
+/// Most sequence numbers remembered per source; once a source's window is
+/// full the lowest is forgotten and anything at or below it is refused
+pub const REPLAY_WINDOW: usize = 1024;
+
+/// Sequence numbers recently accepted from a single source
+#[derive(Debug, Clone, Default)]
+struct SeqWindow {
+    /// Accepted sequence numbers and the timestamps of their pheromones
+    seen: BTreeMap<u64, Timestamp>,
+
+    /// Highest sequence number forgotten to keep the window bounded
+    floor: u64,
+}
+
+/// Per-source windows of accepted sequence numbers, for refusing replays
+///
+/// Pheromones from one source may arrive out of order (ants take different
+/// paths, rounds and values interleave), so a sequence number is a replay
+/// only if it was already accepted, not merely if a higher one was. Each
+/// window holds at most `REPLAY_WINDOW` numbers, and `prune` forgets those
+/// older than the pheromone age bound, whose replays that bound refuses.
+#[derive(Debug, Default)]
+pub struct ReplayGuard {
+    windows: HashMap<NodeId, SeqWindow>,
+}
+
+impl ReplayGuard {
+    /// Create a guard that has seen nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `seq` from `source` was already accepted or has slid out of
+    /// its window
+    pub fn is_replay(&self, source: NodeId, seq: u64) -> bool {
+        self.windows
+            .get(&source)
+            .is_some_and(|window| seq <= window.floor || window.seen.contains_key(&seq))
+    }
+
+    /// Remember that `seq` from `source`, timestamped `timestamp`, was accepted
+    pub fn record(&mut self, source: NodeId, seq: u64, timestamp: Timestamp) {
+        let window = self.windows.entry(source).or_default();
+        window.seen.insert(seq, timestamp);
+        while window.seen.len() > REPLAY_WINDOW {
+            if let Some((lowest, _)) = window.seen.pop_first() {
+                window.floor = window.floor.max(lowest);
+            }
+        }
+    }
+
+    /// Forget sequence numbers of pheromones timestamped before `cutoff`,
+    /// and sources with none left
+    pub fn prune(&mut self, cutoff: Timestamp) {
+        for window in self.windows.values_mut() {
+            window.seen.retain(|_, timestamp| *timestamp >= cutoff);
+        }
+        self.windows.retain(|_, window| !window.seen.is_empty());
+    }
+
+    /// Highest sequence number accepted from `source` that is still
+    /// remembered
+    pub fn highest(&self, source: NodeId) -> Option<u64> {
+        self.windows.get(&source)?.seen.last_key_value().map(|(seq, _)| *seq)
+    }
+
+    /// Number of sources currently tracked
+    pub fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    /// Whether no source is being tracked
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_out_of_order_seqs_accepted_once() {
+        let mut guard = ReplayGuard::new();
+        guard.record(1, 20, 100);
+
+        assert!(!guard.is_replay(1, 10));
+        guard.record(1, 10, 100);
+        assert!(guard.is_replay(1, 10) && guard.is_replay(1, 20));
+        assert!(!guard.is_replay(1, 15) && !guard.is_replay(2, 20));
+        assert_eq!(guard.highest(1), Some(20));
+    }
+
+    #[test]
+    fn test_window_is_bounded() {
+        let mut guard = ReplayGuard::new();
+        for seq in 1..=REPLAY_WINDOW as u64 + 5 {
+            guard.record(1, seq, 100);
+        }
+
+        // The five lowest slid out and stay refused
+        assert!(guard.is_replay(1, 3));
+        assert_eq!(guard.windows[&1].seen.len(), REPLAY_WINDOW);
+
+        guard.record(2, 1, 50);
+        guard.prune(60);
+        assert_eq!(guard.len(), 1);
+        guard.prune(200);
+        assert!(guard.is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::core::node_state::NodeState;
+use crate::core::types::{ConsensusValue, NodeId};
+use std::collections::{BTreeSet, HashMap};
+
+/// Point-in-time summary of a node's trails and neighbors, cheap enough to
+/// take every step and compare with `diff`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateSnapshot {
+    /// Summed pheromone strength of each value with a trail
+    pub intensities: HashMap<ConsensusValue, f64>,
+
+    /// Neighbor set
+    pub neighbors: BTreeSet<NodeId>,
+}
+
+/// What changed between two `StateSnapshot`s
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateDiff {
+    /// Values that gained a trail
+    pub added_values: Vec<ConsensusValue>,
+
+    /// Values whose trail evaporated away or was dropped
+    pub removed_values: Vec<ConsensusValue>,
+
+    /// Change in summed strength of every value whose strength changed,
+    /// including added (positive) and removed (negative) ones
+    pub intensity_deltas: HashMap<ConsensusValue, f64>,
+
+    /// Nodes that became neighbors
+    pub neighbors_added: Vec<NodeId>,
+
+    /// Nodes that stopped being neighbors
+    pub neighbors_removed: Vec<NodeId>,
+}
+
+impl StateDiff {
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.added_values.is_empty()
+            && self.removed_values.is_empty()
+            && self.intensity_deltas.is_empty()
+            && self.neighbors_added.is_empty()
+            && self.neighbors_removed.is_empty()
+    }
+}
+
+impl StateSnapshot {
+    /// Changes from this snapshot to the later snapshot `other`
+    ///
+    /// Values are listed in hash order so diffs of equal states compare
+    /// equal.
+    pub fn diff(&self, other: &StateSnapshot) -> StateDiff {
+        let mut added_values: Vec<ConsensusValue> = other.intensities
+            .keys()
+            .filter(|value| !self.intensities.contains_key(*value))
+            .cloned()
+            .collect();
+        let mut removed_values: Vec<ConsensusValue> = self.intensities
+            .keys()
+            .filter(|value| !other.intensities.contains_key(*value))
+            .cloned()
+            .collect();
+        added_values.sort_by_key(|value| value.hash);
+        removed_values.sort_by_key(|value| value.hash);
+
+        let intensity_deltas = self.intensities
+            .keys()
+            .chain(other.intensities.keys())
+            .filter_map(|value| {
+                let before = self.intensities.get(value).copied().unwrap_or(0.0);
+                let after = other.intensities.get(value).copied().unwrap_or(0.0);
+                (after != before).then(|| (value.clone(), after - before))
+            })
+            .collect();
+
+        StateDiff {
+            added_values,
+            removed_values,
+            intensity_deltas,
+            neighbors_added: other.neighbors.difference(&self.neighbors).copied().collect(),
+            neighbors_removed: self.neighbors.difference(&other.neighbors).copied().collect(),
+        }
+    }
+}
+
+impl NodeState {
+    /// Summarize the node's trails and neighbors, e.g. before and after a
+    /// step to see what it changed
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            intensities: self.pheromones
+                .values()
+                .map(|value| (value.clone(), self.pheromones.aggregate(value)))
+                .collect(),
+            neighbors: self.neighbors.iter().copied().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::pheromone::Pheromone;
+
+    #[test]
+    fn test_diff_reports_new_value_and_intensity_increase() {
+        let mut node = NodeState::new(1);
+        let held = ConsensusValue::from_string("held");
+        let fresh = ConsensusValue::from_string("fresh");
+        node.receive_pheromone(Pheromone::new(held.clone(), 2, &[]).unwrap());
+        node.add_neighbor(2);
+
+        let before = node.snapshot();
+        assert!(before.diff(&node.snapshot()).is_empty());
+
+        node.receive_pheromone(Pheromone::new(held.clone(), 3, &[]).unwrap());
+        node.receive_pheromone(Pheromone::new(fresh.clone(), 3, &[]).unwrap());
+        node.add_neighbor(3);
+        node.remove_neighbor(2);
+        let after = node.snapshot();
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.added_values, vec![fresh.clone()]);
+        assert!(diff.removed_values.is_empty());
+        assert!(diff.intensity_deltas[&held] > 0.0);
+        assert_eq!(diff.intensity_deltas[&fresh], after.intensities[&fresh]);
+        assert_eq!(diff.neighbors_added, vec![3]);
+        assert_eq!(diff.neighbors_removed, vec![2]);
+
+        // The reverse diff undoes it
+        let reverse = after.diff(&before);
+        assert_eq!(reverse.removed_values, vec![fresh.clone()]);
+        assert_eq!(reverse.intensity_deltas[&fresh], -after.intensities[&fresh]);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::core::pheromone::Pheromone;
+use crate::core::types::ConsensusValue;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::ops::Index;
+
+/// Storage for a node's pheromone trails, one trail per consensus value
+///
+/// `NodeState` decides what goes on a trail (merging repeats, folding long
+/// trails, evaporation); a store decides how trails are kept, so backends
+/// such as `BoundedStore` can be swapped in through `NodeState::pheromones`.
+pub trait PheromoneStore: Debug + Send + Sync {
+    /// Append `pheromone` to its value's trail, starting the trail if needed
+    fn insert(&mut self, pheromone: Pheromone);
+
+    /// Trail for `value`, if it has one
+    fn get(&self, value: &ConsensusValue) -> Option<&Vec<Pheromone>>;
+
+    /// Mutable trail for `value`, if it has one
+    fn get_mut(&mut self, value: &ConsensusValue) -> Option<&mut Vec<Pheromone>>;
+
+    /// Drop the trail for `value`, returning it
+    fn remove(&mut self, value: &ConsensusValue) -> Option<Vec<Pheromone>>;
+
+    /// Values that have a trail, in no particular order
+    fn values(&self) -> Box<dyn Iterator<Item = &ConsensusValue> + '_>;
+
+    /// Every trail with its value, in no particular order
+    fn trails(&self) -> Box<dyn Iterator<Item = (&ConsensusValue, &Vec<Pheromone>)> + '_>;
+
+    /// Every trail with its value, mutably
+    fn trails_mut(&mut self) -> Box<dyn Iterator<Item = (&ConsensusValue, &mut Vec<Pheromone>)> + '_>;
+
+    /// Number of values with a trail
+    fn len(&self) -> usize;
+
+    /// Whether no value has a trail
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether `value` has a trail
+    fn contains_key(&self, value: &ConsensusValue) -> bool {
+        self.get(value).is_some()
+    }
+
+    /// Every stored pheromone, trail by trail
+    fn all_pheromones(&self) -> Box<dyn Iterator<Item = &Pheromone> + '_> {
+        Box::new(self.trails().flat_map(|(_, trail)| trail.iter()))
+    }
+
+    /// Apply `evaporate` to every pheromone, dropping those it returns
+    /// `false` for and any trail left empty
+    fn evaporate_all(&mut self, evaporate: &mut dyn FnMut(&mut Pheromone) -> bool) {
+        let mut emptied = Vec::new();
+        for (value, trail) in self.trails_mut() {
+            trail.retain_mut(|pheromone| evaporate(pheromone));
+            if trail.is_empty() {
+                emptied.push(value.clone());
+            }
+        }
+
+        for value in emptied {
+            self.remove(&value);
+        }
+    }
+
+    /// Summed strength of the trail for `value` (0.0 without a trail)
+    fn aggregate(&self, value: &ConsensusValue) -> f64 {
+        self.get(value)
+            .map(|trail| trail.iter().map(|p| p.strength()).sum())
+            .unwrap_or(0.0)
+    }
+}
+
+impl Index<&ConsensusValue> for dyn PheromoneStore {
+    type Output = Vec<Pheromone>;
+
+    fn index(&self, value: &ConsensusValue) -> &Vec<Pheromone> {
+        self.get(value).expect("no trail for value")
+    }
+}
+
+/// Unbounded store keeping every trail in a `HashMap` (default)
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryStore {
+    trails: HashMap<ConsensusValue, Vec<Pheromone>>,
+}
+
+impl InMemoryStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PheromoneStore for InMemoryStore {
+    fn insert(&mut self, pheromone: Pheromone) {
+        self.trails.entry(pheromone.value.clone()).or_default().push(pheromone);
+    }
+
+    fn get(&self, value: &ConsensusValue) -> Option<&Vec<Pheromone>> {
+        self.trails.get(value)
+    }
+
+    fn get_mut(&mut self, value: &ConsensusValue) -> Option<&mut Vec<Pheromone>> {
+        self.trails.get_mut(value)
+    }
+
+    fn remove(&mut self, value: &ConsensusValue) -> Option<Vec<Pheromone>> {
+        self.trails.remove(value)
+    }
+
+    fn values(&self) -> Box<dyn Iterator<Item = &ConsensusValue> + '_> {
+        Box::new(self.trails.keys())
+    }
+
+    fn trails(&self) -> Box<dyn Iterator<Item = (&ConsensusValue, &Vec<Pheromone>)> + '_> {
+        Box::new(self.trails.iter())
+    }
+
+    fn trails_mut(&mut self) -> Box<dyn Iterator<Item = (&ConsensusValue, &mut Vec<Pheromone>)> + '_> {
+        Box::new(self.trails.iter_mut())
+    }
+
+    fn len(&self) -> usize {
+        self.trails.len()
+    }
+}
+
+/// Store holding trails for at most `max_values` values
+///
+/// A pheromone for a new value arriving when the store is full evicts the
+/// weakest trail, unless the newcomer is weaker still, in which case it is
+/// dropped. Strong, active trails are never displaced by faint ones.
+#[derive(Debug, Clone)]
+pub struct BoundedStore {
+    inner: InMemoryStore,
+    max_values: usize,
+    evicted: u64,
+}
+
+impl BoundedStore {
+    /// Create an empty store holding at most `max_values` trails
+    pub fn new(max_values: usize) -> Self {
+        Self {
+            inner: InMemoryStore::new(),
+            max_values: max_values.max(1),
+            evicted: 0,
+        }
+    }
+
+    /// Trails evicted or turned away so far
+    pub fn evicted(&self) -> u64 {
+        self.evicted
+    }
+
+    /// Value with the weakest trail, and that trail's strength
+    fn weakest(&self) -> Option<(ConsensusValue, f64)> {
+        self.inner
+            .values()
+            .map(|value| (value.clone(), self.inner.aggregate(value)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+}
+
+impl PheromoneStore for BoundedStore {
+    fn insert(&mut self, pheromone: Pheromone) {
+        if !self.inner.contains_key(&pheromone.value) && self.inner.len() >= self.max_values {
+            match self.weakest() {
+                Some((weakest, strength)) if strength <= pheromone.strength() => {
+                    self.inner.remove(&weakest);
+                }
+                _ => {
+                    self.evicted += 1;
+                    return;
+                }
+            }
+            self.evicted += 1;
+        }
+        self.inner.insert(pheromone);
+    }
+
+    fn get(&self, value: &ConsensusValue) -> Option<&Vec<Pheromone>> {
+        self.inner.get(value)
+    }
+
+    fn get_mut(&mut self, value: &ConsensusValue) -> Option<&mut Vec<Pheromone>> {
+        self.inner.get_mut(value)
+    }
+
+    fn remove(&mut self, value: &ConsensusValue) -> Option<Vec<Pheromone>> {
+        self.inner.remove(value)
+    }
+
+    fn values(&self) -> Box<dyn Iterator<Item = &ConsensusValue> + '_> {
+        self.inner.values()
+    }
+
+    fn trails(&self) -> Box<dyn Iterator<Item = (&ConsensusValue, &Vec<Pheromone>)> + '_> {
+        self.inner.trails()
+    }
+
+    fn trails_mut(&mut self) -> Box<dyn Iterator<Item = (&ConsensusValue, &mut Vec<Pheromone>)> + '_> {
+        self.inner.trails_mut()
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pheromone(name: &str, source: u32, intensity: f64) -> Pheromone {
+        let mut pheromone = Pheromone::new(ConsensusValue::from_string(name), source, &[]).unwrap();
+        pheromone.intensity = intensity;
+        pheromone
+    }
+
+    #[test]
+    fn test_bounded_store_evicts_weakest_trail() {
+        let mut store = BoundedStore::new(2);
+        store.insert(pheromone("strong", 1, 0.9));
+        store.insert(pheromone("faint", 2, 0.1));
+
+        // A newcomer displaces the faintest trail
+        store.insert(pheromone("fresh", 3, 0.5));
+        assert_eq!(store.len(), 2);
+        assert!(!store.contains_key(&ConsensusValue::from_string("faint")));
+
+        // One weaker than every trail is turned away
+        store.insert(pheromone("weaker", 4, 0.2));
+        assert!(!store.contains_key(&ConsensusValue::from_string("weaker")));
+        assert!(store.contains_key(&ConsensusValue::from_string("strong")));
+        assert_eq!(store.evicted(), 2);
+
+        // Existing trails keep growing regardless of the bound
+        store.insert(pheromone("strong", 5, 0.8));
+        assert_eq!(store.get(&ConsensusValue::from_string("strong")).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_evaporate_all_drops_emptied_trails() {
+        let mut store = InMemoryStore::new();
+        store.insert(pheromone("kept", 1, 0.9));
+        store.insert(pheromone("gone", 2, 0.1));
+
+        store.evaporate_all(&mut |p| p.intensity > 0.5);
+
+        let values: Vec<_> = store.values().cloned().collect();
+        assert_eq!(values, vec![ConsensusValue::from_string("kept")]);
+        assert!((store.aggregate(&values[0]) - 0.9).abs() < 1e-9);
+    }
+}
@@ -1,5 +1,8 @@
+use crate::crypto::hashing::HashAlgo;
+use serde::de::DeserializeOwned;
 use serde::{Serialize, Deserialize};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 /// Node identifier
 pub type NodeId = u32;
@@ -10,24 +13,140 @@ pub type AntId = u64;
 /// Timestamp in seconds since epoch
 pub type Timestamp = u64;
 
+/// Identifier of an independent consensus round (or topic)
+pub type RoundId = u64;
+
+/// Round used by APIs that don't name one
+pub const DEFAULT_ROUND: RoundId = 0;
+
+/// Kind of payload a consensus value was derived from, so a topic can
+/// insist on one kind and decoders know how to interpret the payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum ValueKind {
+    /// Opaque bytes (default)
+    #[default]
+    Bytes,
+
+    /// UTF-8 string
+    String,
+
+    /// Numeric value
+    Number,
+
+    /// Serialized structured data
+    Struct,
+}
+
+impl ValueKind {
+    /// Stable one-byte identifier, used when the kind is signed
+    pub fn id(&self) -> u8 {
+        match self {
+            ValueKind::Bytes => 0,
+            ValueKind::String => 1,
+            ValueKind::Number => 2,
+            ValueKind::Struct => 3,
+        }
+    }
+}
+
 /// Consensus value - represents the value nodes are trying to agree on
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+///
+/// Two values are equal only when both the digest and the algorithm that
+/// produced it match; the `kind` tag and stored `payload` do not take part
+/// in equality.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConsensusValue {
-    /// SHA-256 hash of the value
+    /// Hash of the value
     pub hash: [u8; 32],
+
+    /// Algorithm that produced `hash`
+    #[serde(default)]
+    pub algo: HashAlgo,
+
+    /// Kind of payload that was hashed
+    #[serde(default)]
+    pub kind: ValueKind,
+
+    /// Bytes that were hashed, when kept so peers can decode the value;
+    /// carried with every pheromone for the value, so keep it small
+    #[serde(default)]
+    pub payload: Option<Vec<u8>>,
+}
+
+impl PartialEq for ConsensusValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.algo == other.algo
+    }
+}
+
+impl Eq for ConsensusValue {}
+
+impl Hash for ConsensusValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+        self.algo.hash(state);
+    }
 }
 
 impl ConsensusValue {
-    /// Create a new consensus value from bytes
+    /// Create a new consensus value from bytes (SHA-256)
     pub fn from_bytes(data: &[u8]) -> Self {
-        use crate::crypto::hash_sha256;
-        let hash = hash_sha256(data);
-        Self { hash }
+        Self::from_bytes_with(HashAlgo::Sha256, data)
+    }
+
+    /// Create a new consensus value from bytes with the chosen hash algorithm
+    pub fn from_bytes_with(algo: HashAlgo, data: &[u8]) -> Self {
+        use crate::crypto::hash_with;
+        let hash = hash_with(algo, data);
+        Self { hash, algo, kind: ValueKind::Bytes, payload: None }
+    }
+
+    /// Create a consensus value from bytes (SHA-256), keeping them as the
+    /// payload so peers and a `ValueValidator` can inspect them
+    pub fn with_payload(data: &[u8]) -> Self {
+        let mut value = Self::from_bytes(data);
+        value.payload = Some(data.to_vec());
+        value
+    }
+
+    /// Create a consensus value from structured data, serialized as JSON
+    ///
+    /// The serialized bytes are kept as the payload so any node holding the
+    /// value can recover the data with `try_decode`.
+    pub fn from_serializable<T: Serialize>(value: &T) -> Result<Self> {
+        let payload = serde_json::to_vec(value)
+            .map_err(|e| ConsensusError::Internal(format!("Serialization error: {}", e)))?;
+        let mut value = Self::from_bytes(&payload).with_kind(ValueKind::Struct);
+        value.payload = Some(payload);
+        Ok(value)
+    }
+
+    /// Decode the stored payload back into structured data
+    ///
+    /// Fails when no payload is stored, when it does not hash to this
+    /// value (so a tampered payload is never decoded), or when it does not
+    /// deserialize as `T`.
+    pub fn try_decode<T: DeserializeOwned>(&self) -> Result<T> {
+        let payload = self.payload.as_ref()
+            .ok_or_else(|| ConsensusError::InvalidMessage("Value has no stored payload".to_string()))?;
+
+        if crate::crypto::hash_with(self.algo, payload) != self.hash {
+            return Err(ConsensusError::InvalidMessage("Payload does not match value hash".to_string()));
+        }
+
+        serde_json::from_slice(payload)
+            .map_err(|e| ConsensusError::InvalidMessage(format!("Payload decode error: {}", e)))
     }
 
     /// Create a consensus value from a string
     pub fn from_string(s: &str) -> Self {
-        Self::from_bytes(s.as_bytes())
+        Self::from_bytes(s.as_bytes()).with_kind(ValueKind::String)
+    }
+
+    /// Tag this value with the kind of payload it was derived from
+    pub fn with_kind(mut self, kind: ValueKind) -> Self {
+        self.kind = kind;
+        self
     }
 
     /// Get the hash as a hex string
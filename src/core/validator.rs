@@ -0,0 +1,82 @@
+use crate::core::types::{ConsensusError, Result};
+use std::fmt;
+use std::sync::Arc;
+
+/// Payload check wrapped by `ValueValidator`
+type PayloadCheck = dyn Fn(&[u8]) -> Result<()> + Send + Sync;
+
+/// Check run on the payload of proposed and received values, so
+/// deployments can restrict values to a schema and reject malformed ones
+/// early
+///
+/// Installed with `ConsensusEngine::set_value_validator`. Only values that
+/// carry a payload (see `ConsensusValue::with_payload`) can be checked;
+/// hash-only values pass untouched.
+#[derive(Clone)]
+pub struct ValueValidator(Arc<PayloadCheck>);
+
+impl ValueValidator {
+    /// Wrap a check returning `ConsensusError::InvalidMessage` for payloads
+    /// it rejects
+    pub fn new(check: impl Fn(&[u8]) -> Result<()> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(check))
+    }
+
+    /// Accept payloads of exactly `len` bytes, e.g. 32-byte block hashes
+    pub fn fixed_length(len: usize) -> Self {
+        Self::new(move |payload| {
+            if payload.len() != len {
+                return Err(ConsensusError::InvalidMessage(format!(
+                    "value is {} bytes, expected {}",
+                    payload.len(),
+                    len
+                )));
+            }
+            Ok(())
+        })
+    }
+
+    /// Accept UTF-8 payloads of at most `max_len` bytes
+    pub fn utf8(max_len: usize) -> Self {
+        Self::new(move |payload| {
+            if payload.len() > max_len {
+                return Err(ConsensusError::InvalidMessage(format!(
+                    "value is {} bytes, over the {} byte limit",
+                    payload.len(),
+                    max_len
+                )));
+            }
+            std::str::from_utf8(payload)
+                .map(|_| ())
+                .map_err(|e| ConsensusError::InvalidMessage(format!("value is not UTF-8: {}", e)))
+        })
+    }
+
+    /// Run the check on `payload`
+    pub fn check(&self, payload: &[u8]) -> Result<()> {
+        (self.0)(payload)
+    }
+}
+
+impl fmt::Debug for ValueValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ValueValidator")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_validators() {
+        let hashes = ValueValidator::fixed_length(32);
+        assert!(hashes.check(&[0u8; 32]).is_ok());
+        assert!(hashes.check(&[0u8; 31]).is_err());
+
+        let text = ValueValidator::utf8(8);
+        assert!(text.check("short".as_bytes()).is_ok());
+        assert!(text.check("far too long".as_bytes()).is_err());
+        assert!(matches!(text.check(&[0xff, 0xfe]), Err(ConsensusError::InvalidMessage(_))));
+    }
+}
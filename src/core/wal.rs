@@ -0,0 +1,225 @@
+use crate::core::node_state::NodeState;
+use crate::core::persistence::PersistedState;
+use crate::core::types::{ConsensusError, ConsensusValue, Result, RoundId, DEFAULT_ROUND};
+use serde::{Serialize, Deserialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// One entry of a write-ahead log, stored as a line of JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WalRecord {
+    /// Full node state; replay starts from the latest snapshot
+    Snapshot(Box<PersistedState>),
+
+    /// The default round committed `value`
+    Committed { value: ConsensusValue },
+
+    /// A non-default round settled on `value`
+    RoundValue { round: RoundId, value: ConsensusValue },
+}
+
+/// Append-only log of a node's snapshots and the transitions since, so a
+/// restarted node recovers its committed value without re-learning it
+#[derive(Debug, Clone)]
+pub struct WriteAheadLog {
+    path: PathBuf,
+}
+
+impl WriteAheadLog {
+    /// Open the log at `path`, creating an empty one if it doesn't exist
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| wal_error(&path, e))?;
+        Ok(Self { path })
+    }
+
+    /// Path of the log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Durably append `record` to the log
+    pub fn append(&self, record: &WalRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)
+            .map_err(|e| ConsensusError::Internal(format!("Serialization error: {}", e)))?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| wal_error(&self.path, e))?;
+        file.write_all(&line).map_err(|e| wal_error(&self.path, e))?;
+        file.sync_data().map_err(|e| wal_error(&self.path, e))
+    }
+
+    /// Replace the log with a single snapshot record, discarding the
+    /// transitions it already covers
+    ///
+    /// The snapshot is written beside the log and renamed over it, so a
+    /// crash mid-write leaves the previous log intact.
+    pub fn checkpoint(&self, snapshot: PersistedState) -> Result<()> {
+        let staging = self.path.with_extension("wal.tmp");
+        let mut line = serde_json::to_vec(&WalRecord::Snapshot(Box::new(snapshot)))
+            .map_err(|e| ConsensusError::Internal(format!("Serialization error: {}", e)))?;
+        line.push(b'\n');
+
+        let mut file = File::create(&staging).map_err(|e| wal_error(&staging, e))?;
+        file.write_all(&line).map_err(|e| wal_error(&staging, e))?;
+        file.sync_all().map_err(|e| wal_error(&staging, e))?;
+        fs::rename(&staging, &self.path).map_err(|e| wal_error(&self.path, e))
+    }
+
+    /// Rebuild the logged state: the latest snapshot with every later
+    /// transition applied, or `None` if the log holds no snapshot
+    ///
+    /// A torn final line, left by a crash mid-append, is ignored.
+    pub fn replay(&self) -> Result<Option<PersistedState>> {
+        let file = File::open(&self.path).map_err(|e| wal_error(&self.path, e))?;
+        let mut state: Option<PersistedState> = None;
+
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| wal_error(&self.path, e))?;
+            let Ok(record) = serde_json::from_str::<WalRecord>(&line) else {
+                break;
+            };
+
+            match (record, state.as_mut()) {
+                (WalRecord::Snapshot(snapshot), _) => state = Some(*snapshot),
+                (WalRecord::Committed { value }, Some(state)) => {
+                    state.current_value = Some(value.clone());
+                    state.committed_log.push(value.clone());
+                    state.committed_value = Some(value);
+                }
+                (WalRecord::RoundValue { round, value }, Some(state)) if round != DEFAULT_ROUND => {
+                    state.round_values.insert(round, value);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(state)
+    }
+}
+
+fn wal_error(path: &Path, e: std::io::Error) -> ConsensusError {
+    ConsensusError::Internal(format!("Write-ahead log {}: {}", path.display(), e))
+}
+
+impl NodeState {
+    /// Checkpoint this node's state into the write-ahead log at `path`
+    pub fn save_snapshot(&self, path: impl AsRef<Path>) -> Result<()> {
+        WriteAheadLog::open(path)?.checkpoint(self.persist())
+    }
+
+    /// Restore the state logged at `path`, returning false if the log
+    /// holds no snapshot yet
+    ///
+    /// The committed value, round values and stats come back as logged;
+    /// call `attach_wal` afterwards to keep logging new transitions.
+    pub fn load_snapshot(&mut self, path: impl AsRef<Path>) -> Result<bool> {
+        match WriteAheadLog::open(path)?.replay()? {
+            Some(persisted) => self.restore(persisted).map(|()| true),
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_wal() -> PathBuf {
+        std::env::temp_dir().join(format!("antcolony-{}.wal", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let path = temp_wal();
+        let mut node = NodeState::new(1);
+        let value = ConsensusValue::from_string("durable");
+        node.emit_pheromone(value.clone()).unwrap();
+        node.committed_value = Some(value.clone());
+        node.stats.consensus_reached = 7;
+        node.save_snapshot(&path).unwrap();
+
+        let mut restarted = NodeState::new(1);
+        assert!(restarted.load_snapshot(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(restarted.committed_value, Some(value.clone()));
+        assert_eq!(restarted.stats, node.stats);
+        assert!(restarted.pheromones.contains_key(&value));
+    }
+
+    #[test]
+    fn test_replay_applies_transitions_after_snapshot() {
+        let path = temp_wal();
+        NodeState::new(1).save_snapshot(&path).unwrap();
+
+        let wal = WriteAheadLog::open(&path).unwrap();
+        let value = ConsensusValue::from_string("later");
+        wal.append(&WalRecord::Committed { value: value.clone() }).unwrap();
+        wal.append(&WalRecord::RoundValue { round: 3, value: value.clone() }).unwrap();
+
+        // A crash mid-append leaves a torn line behind
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"Commi").unwrap();
+
+        let replayed = wal.replay().unwrap().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(replayed.committed_value, Some(value.clone()));
+        assert_eq!(replayed.round_values.get(&3), Some(&value));
+    }
+
+    #[test]
+    fn test_attached_log_records_commit() {
+        let path = temp_wal();
+        let mut node = NodeState::new(1);
+        node.config.finality_steps = Some(1);
+        node.save_snapshot(&path).unwrap();
+        node.attach_wal(WriteAheadLog::open(&path).unwrap());
+
+        // Committed after the snapshot was taken
+        let value = ConsensusValue::from_string("committed");
+        node.emit_pheromone(value.clone()).unwrap();
+        assert_eq!(node.check_consensus(), Some(value.clone()));
+
+        let mut restarted = NodeState::new(1);
+        assert!(restarted.load_snapshot(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(restarted.committed_value, Some(value));
+        assert_eq!(restarted.committed_root(), node.committed_root());
+    }
+
+    #[test]
+    fn test_commit_on_fresh_log_survives_crash() {
+        let path = temp_wal();
+        let mut node = NodeState::new(1);
+        node.config.finality_steps = Some(1);
+        node.attach_wal(WriteAheadLog::open(&path).unwrap());
+
+        let value = ConsensusValue::from_string("crashed");
+        node.emit_pheromone(value.clone()).unwrap();
+        assert_eq!(node.check_consensus(), Some(value.clone()));
+        // Dropped without a clean shutdown's snapshot
+        drop(node);
+
+        let mut restarted = NodeState::new(1);
+        assert!(restarted.load_snapshot(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(restarted.committed_value, Some(value));
+    }
+
+    #[test]
+    fn test_empty_log_restores_nothing() {
+        let path = temp_wal();
+        let mut node = NodeState::new(1);
+        assert!(!node.load_snapshot(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,233 @@
+use crate::core::types::{ConsensusValue, NodeId, Timestamp};
+use crate::crypto::signing::Signature;
+use blst::min_pk::{AggregateSignature, SecretKey, Signature as BlsSignature};
+use blst::BLST_ERROR;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/// BLS public key (48-byte compressed G1 point)
+pub type BlsPublicKey = blst::min_pk::PublicKey;
+
+/// A signer's share: who signed, the timestamp covered, and the signature
+pub type Share = (NodeId, Timestamp, Signature);
+
+/// Domain separation tag for every BLS signature made by this crate
+const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// BLS12-381 key pair
+#[derive(Clone)]
+pub struct BlsKeyPair {
+    secret: SecretKey,
+}
+
+impl BlsKeyPair {
+    /// Generate a new key pair from system randomness
+    pub fn generate() -> Result<Self, String> {
+        let mut ikm = [0u8; 32];
+        SystemRandom::new()
+            .fill(&mut ikm)
+            .map_err(|_| "Failed to generate BLS key material".to_string())?;
+
+        let secret = SecretKey::key_gen(&ikm, &[])
+            .map_err(|e| format!("Failed to generate BLS key pair: {:?}", e))?;
+        Ok(Self { secret })
+    }
+
+    /// Create from a 32-byte secret key
+    pub fn from_secret_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let secret = SecretKey::from_bytes(bytes)
+            .map_err(|e| format!("Failed to parse BLS secret key: {:?}", e))?;
+        Ok(Self { secret })
+    }
+
+    /// Get the secret key bytes
+    pub fn secret_bytes(&self) -> Vec<u8> {
+        self.secret.to_bytes().to_vec()
+    }
+
+    /// Get the public key
+    pub fn public_key(&self) -> BlsPublicKey {
+        self.secret.sk_to_pk()
+    }
+
+    /// Sign a message
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        self.secret.sign(message, DST, &[]).to_bytes().to_vec()
+    }
+}
+
+impl fmt::Debug for BlsKeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the private key material
+        f.debug_struct("BlsKeyPair")
+            .field("public_key", &self.public_key().to_bytes())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Bytes a signer signs to back `value` at `timestamp`
+///
+/// The signer is part of the message, so every share in an aggregate signs
+/// a distinct message and rogue-key attacks don't apply.
+pub fn share_bytes(value: &ConsensusValue, signer: NodeId, timestamp: Timestamp) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(46);
+    bytes.extend_from_slice(&value.hash);
+    bytes.push(value.algo.id());
+    bytes.push(value.kind.id());
+    bytes.extend_from_slice(&signer.to_be_bytes());
+    bytes.extend_from_slice(&timestamp.to_be_bytes());
+    bytes
+}
+
+/// Verify a single signature made with `BlsKeyPair::sign` against a
+/// compressed public key
+pub fn verify(message: &[u8], signature: &Signature, public_key: &[u8]) -> bool {
+    let (Ok(signature), Ok(public_key)) = (BlsSignature::from_bytes(signature), BlsPublicKey::from_bytes(public_key)) else {
+        return false;
+    };
+    signature.verify(true, message, DST, &[], &public_key, true) == BLST_ERROR::BLST_SUCCESS
+}
+
+/// Sign a share backing `value`, for inclusion in an `AggregateProof`
+pub fn sign_share(key_pair: &BlsKeyPair, value: &ConsensusValue, signer: NodeId, timestamp: Timestamp) -> Signature {
+    key_pair.sign(&share_bytes(value, signer, timestamp))
+}
+
+/// Compact proof that a set of nodes backed a value: one BLS signature
+/// aggregating every signer's share
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregateProof {
+    /// Value the signers backed
+    pub value: ConsensusValue,
+
+    /// Each signer and the timestamp its share covers
+    pub signers: Vec<(NodeId, Timestamp)>,
+
+    /// Aggregate of every share's signature
+    pub signature: Signature,
+}
+
+impl AggregateProof {
+    /// Aggregate shares signed with `sign_share` into one proof
+    ///
+    /// Fails if there are no shares, a signer appears twice, or a share is
+    /// not a valid signature encoding.
+    pub fn aggregate(value: ConsensusValue, shares: &[Share]) -> Result<Self, String> {
+        let mut seen = BTreeSet::new();
+        if let Some((signer, _, _)) = shares.iter().find(|(signer, _, _)| !seen.insert(*signer)) {
+            return Err(format!("Signer {} appears more than once", signer));
+        }
+
+        let signatures = shares
+            .iter()
+            .map(|(signer, _, signature)| {
+                BlsSignature::from_bytes(signature)
+                    .map_err(|e| format!("Invalid BLS signature from {}: {:?}", signer, e))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let refs: Vec<&BlsSignature> = signatures.iter().collect();
+
+        let aggregate = AggregateSignature::aggregate(&refs, true)
+            .map_err(|e| format!("Failed to aggregate BLS signatures: {:?}", e))?;
+
+        Ok(Self {
+            value,
+            signers: shares.iter().map(|(signer, timestamp, _)| (*signer, *timestamp)).collect(),
+            signature: aggregate.to_signature().to_bytes().to_vec(),
+        })
+    }
+
+    /// Nodes whose shares the proof aggregates
+    pub fn signer_set(&self) -> BTreeSet<NodeId> {
+        self.signers.iter().map(|(signer, _)| *signer).collect()
+    }
+
+    /// Verify the aggregate in one operation against each signer's key
+    ///
+    /// Fails when any signer has no key in `keys`.
+    pub fn verify(&self, keys: &HashMap<NodeId, BlsPublicKey>) -> bool {
+        if self.signers.is_empty() || self.signer_set().len() != self.signers.len() {
+            return false;
+        }
+
+        let Ok(signature) = BlsSignature::from_bytes(&self.signature) else {
+            return false;
+        };
+
+        let mut public_keys = Vec::with_capacity(self.signers.len());
+        for (signer, _) in &self.signers {
+            match keys.get(signer) {
+                Some(key) => public_keys.push(key),
+                None => return false,
+            }
+        }
+
+        let messages: Vec<Vec<u8>> = self.signers
+            .iter()
+            .map(|(signer, timestamp)| share_bytes(&self.value, *signer, *timestamp))
+            .collect();
+        let messages: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+
+        signature.aggregate_verify(true, &messages, DST, &public_keys, true) == BLST_ERROR::BLST_SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed_shares(value: &ConsensusValue, signers: &[NodeId]) -> (Vec<Share>, HashMap<NodeId, BlsPublicKey>) {
+        let mut shares = Vec::new();
+        let mut keys = HashMap::new();
+        for &signer in signers {
+            let key_pair = BlsKeyPair::generate().unwrap();
+            let timestamp = 1_000 + u64::from(signer);
+            shares.push((signer, timestamp, sign_share(&key_pair, value, signer, timestamp)));
+            keys.insert(signer, key_pair.public_key());
+        }
+        (shares, keys)
+    }
+
+    #[test]
+    fn test_aggregate_of_three_verifies() {
+        let value = ConsensusValue::from_string("backed");
+        let (shares, keys) = signed_shares(&value, &[1, 2, 3]);
+
+        let proof = AggregateProof::aggregate(value, &shares).unwrap();
+        assert_eq!(proof.signer_set(), [1, 2, 3].into_iter().collect());
+        assert_eq!(proof.signature.len(), 96);
+        assert!(proof.verify(&keys));
+    }
+
+    #[test]
+    fn test_aggregate_rejects_altered_signer_set() {
+        let value = ConsensusValue::from_string("backed");
+        let (shares, mut keys) = signed_shares(&value, &[1, 2, 3]);
+        let proof = AggregateProof::aggregate(value.clone(), &shares).unwrap();
+
+        let mut dropped = proof.clone();
+        dropped.signers.pop();
+        assert!(!dropped.verify(&keys));
+
+        let mut swapped = proof.clone();
+        swapped.signers[2].0 = 4;
+        keys.insert(4, BlsKeyPair::generate().unwrap().public_key());
+        assert!(!swapped.verify(&keys));
+
+        let mut other_value = proof;
+        other_value.value = ConsensusValue::from_string("other");
+        assert!(!other_value.verify(&keys));
+
+        assert!(AggregateProof::aggregate(value, &[shares[0].clone(), shares[0].clone()]).is_err());
+    }
+
+    #[test]
+    fn test_secret_key_round_trip() {
+        let key_pair = BlsKeyPair::generate().unwrap();
+        let reloaded = BlsKeyPair::from_secret_bytes(&key_pair.secret_bytes()).unwrap();
+        assert_eq!(reloaded.public_key(), key_pair.public_key());
+        assert_eq!(reloaded.sign(b"hello"), key_pair.sign(b"hello"));
+    }
+}
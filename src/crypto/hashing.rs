@@ -1,4 +1,31 @@
 use ring::digest;
+use serde::{Serialize, Deserialize};
+use sha3::{Digest, Sha3_256};
+
+/// Hash algorithm used to derive a 32-byte digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum HashAlgo {
+    /// SHA-256 (default)
+    #[default]
+    Sha256,
+
+    /// SHA3-256
+    Sha3_256,
+
+    /// BLAKE3 with a 32-byte output
+    Blake3,
+}
+
+impl HashAlgo {
+    /// Stable one-byte identifier, used when the algorithm is signed
+    pub fn id(&self) -> u8 {
+        match self {
+            HashAlgo::Sha256 => 0,
+            HashAlgo::Sha3_256 => 1,
+            HashAlgo::Blake3 => 2,
+        }
+    }
+}
 
 /// Hash a byte slice using SHA-256
 pub fn hash_sha256(data: &[u8]) -> [u8; 32] {
@@ -13,6 +40,15 @@ pub fn hash_string(s: &str) -> [u8; 32] {
     hash_sha256(s.as_bytes())
 }
 
+/// Hash a byte slice with the chosen algorithm
+pub fn hash_with(algo: HashAlgo, data: &[u8]) -> [u8; 32] {
+    match algo {
+        HashAlgo::Sha256 => hash_sha256(data),
+        HashAlgo::Sha3_256 => Sha3_256::digest(data).into(),
+        HashAlgo::Blake3 => *blake3::hash(data).as_bytes(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -30,5 +66,21 @@ mod tests {
         let hash3 = hash_sha256(b"different data");
         assert_ne!(hash1, hash3);
     }
-}
 
+    #[test]
+    fn test_hash_algorithms_differ() {
+        let data = b"test data";
+        let sha256 = hash_with(HashAlgo::Sha256, data);
+        let sha3 = hash_with(HashAlgo::Sha3_256, data);
+        let blake3 = hash_with(HashAlgo::Blake3, data);
+
+        assert_eq!(sha256, hash_sha256(data));
+        assert_ne!(sha256, sha3);
+        assert_ne!(sha256, blake3);
+        assert_ne!(sha3, blake3);
+
+        // Each algorithm is deterministic
+        assert_eq!(sha3, hash_with(HashAlgo::Sha3_256, data));
+        assert_eq!(blake3, hash_with(HashAlgo::Blake3, data));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::crypto::hashing::hash_sha256;
+use serde::{Serialize, Deserialize};
+
+/// Root of a tree with no leaves
+pub const EMPTY_MERKLE_ROOT: [u8; 32] = [0u8; 32];
+
+/// Domain prefixes keeping leaf and interior hashes apart, so an interior
+/// node can't be passed off as a leaf
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Hash of a single leaf
+pub fn leaf_hash(data: &[u8]) -> [u8; 32] {
+    let mut bytes = Vec::with_capacity(data.len() + 1);
+    bytes.push(LEAF_PREFIX);
+    bytes.extend_from_slice(data);
+    hash_sha256(&bytes)
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut bytes = [0u8; 65];
+    bytes[0] = NODE_PREFIX;
+    bytes[1..33].copy_from_slice(left);
+    bytes[33..].copy_from_slice(right);
+    hash_sha256(&bytes)
+}
+
+/// Hashes of the level above `level`; an unpaired last node is carried up
+/// unchanged rather than paired with itself
+fn parent_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Merkle root over `leaves`, in order (`EMPTY_MERKLE_ROOT` when empty)
+pub fn merkle_root<T: AsRef<[u8]>>(leaves: &[T]) -> [u8; 32] {
+    let mut level: Vec<[u8; 32]> = leaves.iter().map(|leaf| leaf_hash(leaf.as_ref())).collect();
+    if level.is_empty() {
+        return EMPTY_MERKLE_ROOT;
+    }
+
+    while level.len() > 1 {
+        level = parent_level(&level);
+    }
+    level[0]
+}
+
+/// Sibling hash met on the way from a leaf to the root
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofStep {
+    /// The sibling sits to the left of the running hash
+    Left([u8; 32]),
+
+    /// The sibling sits to the right of the running hash
+    Right([u8; 32]),
+}
+
+/// Proof that a leaf sits at `index` in a tree with a given root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// Position of the leaf
+    pub index: usize,
+
+    /// Siblings from the leaf's level up to just below the root
+    pub path: Vec<ProofStep>,
+}
+
+impl MerkleProof {
+    /// Build the proof for the leaf at `index`, or `None` if out of range
+    pub fn new<T: AsRef<[u8]>>(leaves: &[T], index: usize) -> Option<Self> {
+        if index >= leaves.len() {
+            return None;
+        }
+
+        let mut level: Vec<[u8; 32]> = leaves.iter().map(|leaf| leaf_hash(leaf.as_ref())).collect();
+        let mut position = index;
+        let mut path = Vec::new();
+
+        while level.len() > 1 {
+            let sibling = position ^ 1;
+            if sibling < level.len() {
+                path.push(if sibling < position {
+                    ProofStep::Left(level[sibling])
+                } else {
+                    ProofStep::Right(level[sibling])
+                });
+            }
+            level = parent_level(&level);
+            position /= 2;
+        }
+
+        Some(Self { index, path })
+    }
+
+    /// Whether `leaf` hashes up through this proof to `root`
+    pub fn verify(&self, leaf: &[u8], root: &[u8; 32]) -> bool {
+        let hash = self.path.iter().fold(leaf_hash(leaf), |hash, step| match step {
+            ProofStep::Left(sibling) => node_hash(sibling, &hash),
+            ProofStep::Right(sibling) => node_hash(&hash, sibling),
+        });
+        &hash == root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_proof_verifies() {
+        for count in 1..=9usize {
+            let leaves: Vec<Vec<u8>> = (0..count).map(|i| vec![i as u8]).collect();
+            let root = merkle_root(&leaves);
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = MerkleProof::new(&leaves, index).unwrap();
+                assert!(proof.verify(leaf, &root), "leaf {} of {}", index, count);
+                assert!(!proof.verify(b"forged", &root));
+            }
+            assert!(MerkleProof::new(&leaves, count).is_none());
+        }
+    }
+
+    #[test]
+    fn test_root_depends_on_order_and_content() {
+        let root = merkle_root(&[b"a", b"b", b"c"]);
+        assert_ne!(root, merkle_root(&[b"b", b"a", b"c"]));
+        assert_ne!(root, merkle_root(&[b"a", b"b"]));
+        assert_eq!(merkle_root::<&[u8]>(&[]), EMPTY_MERKLE_ROOT);
+    }
+}
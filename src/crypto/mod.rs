@@ -1,6 +1,11 @@
 pub mod signing;
 pub mod hashing;
+pub mod registry;
+pub mod merkle;
+#[cfg(feature = "bls")]
+pub mod bls;
 
-pub use signing::{PublicKey, Signature, KeyPairWrapper, sign_message, verify_signature, generate_key_pair};
-pub use hashing::{hash_sha256, hash_string};
-
+pub use signing::{PublicKey, Signature, SignatureScheme, KeyPairWrapper, sign_message, verify_signature, verify_batch, generate_key_pair};
+pub use hashing::{hash_sha256, hash_string, hash_with, HashAlgo};
+pub use registry::KeyRegistry;
+pub use merkle::{merkle_root, MerkleProof, ProofStep, EMPTY_MERKLE_ROOT};
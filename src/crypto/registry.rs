@@ -0,0 +1,132 @@
+use crate::core::types::NodeId;
+use crate::crypto::signing::{decode_hex, PublicKey};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Length of a raw Ed25519 public key
+const ED25519_PUBLIC_KEY_LEN: usize = 32;
+
+/// Registry of known public keys, keyed by the node that owns them
+///
+/// Nodes only accept pheromones and signed messages from sources whose key
+/// is registered here, and keys are not exchanged over the network, so the
+/// registry must be provisioned: by the embedder with `register`, or from a
+/// peer-key file with `load_peer_file` (the `--peer-keys` option).
+#[derive(Debug, Clone, Default)]
+pub struct KeyRegistry {
+    keys: HashMap<NodeId, PublicKey>,
+}
+
+impl KeyRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register (or replace) the public key for a node
+    pub fn register(&mut self, node_id: NodeId, public_key: PublicKey) {
+        self.keys.insert(node_id, public_key);
+    }
+
+    /// Remove the public key for a node
+    pub fn remove(&mut self, node_id: NodeId) -> Option<PublicKey> {
+        self.keys.remove(&node_id)
+    }
+
+    /// Look up the public key for a node
+    pub fn get(&self, node_id: NodeId) -> Option<&PublicKey> {
+        self.keys.get(&node_id)
+    }
+
+    /// Check whether a key is registered for a node
+    pub fn contains(&self, node_id: NodeId) -> bool {
+        self.keys.contains_key(&node_id)
+    }
+
+    /// Number of registered keys
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Check whether the registry is empty
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Register every key listed in a peer-key file, returning how many
+    ///
+    /// Each line holds a node id and its raw 32-byte Ed25519 public key as
+    /// hex or base64, separated by whitespace (the hex is what
+    /// `KeyPairWrapper::public_key_hex` prints). Blank lines and lines
+    /// starting with `#` are skipped; any other malformed line is an error
+    /// and registers nothing.
+    pub fn load_peer_keys(&mut self, text: &str) -> Result<usize, String> {
+        let mut parsed = Vec::new();
+        for (number, line) in text.lines().enumerate().map(|(i, line)| (i + 1, line.trim())) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let (Some(node_id), Some(key), None) = (fields.next(), fields.next(), fields.next()) else {
+                return Err(format!("Line {}: expected a node id and a public key", number));
+            };
+            let node_id: NodeId = node_id
+                .parse()
+                .map_err(|e| format!("Line {}: invalid node id: {}", number, e))?;
+            let key = decode_hex(key)
+                .or_else(|| BASE64.decode(key).ok())
+                .filter(|bytes| bytes.len() == ED25519_PUBLIC_KEY_LEN)
+                .ok_or_else(|| format!("Line {}: public key is not 32 bytes of hex or base64", number))?;
+            parsed.push((node_id, UnparsedPublicKey::new(&ED25519, key)));
+        }
+
+        let count = parsed.len();
+        for (node_id, public_key) in parsed {
+            self.register(node_id, public_key);
+        }
+        Ok(count)
+    }
+
+    /// Register the keys listed in the peer-key file at `path`, as
+    /// `load_peer_keys` reads them
+    pub fn load_peer_file(&mut self, path: impl AsRef<Path>) -> Result<usize, String> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read peer keys from {}: {}", path.display(), e))?;
+        self.load_peer_keys(&text)
+            .map_err(|e| format!("Invalid peer-key file {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::signing::KeyPairWrapper;
+
+    #[test]
+    fn test_peer_keys_parsed_from_text() {
+        let alice = KeyPairWrapper::generate().unwrap();
+        let bob = KeyPairWrapper::generate().unwrap();
+        let text = format!(
+            "# cluster keys\n2 {}\n\n3\t{}\n",
+            alice.public_key_hex(),
+            BASE64.encode(bob.public_key().as_ref()),
+        );
+
+        let mut registry = KeyRegistry::new();
+        assert_eq!(registry.load_peer_keys(&text).unwrap(), 2);
+        assert_eq!(registry.get(2).unwrap().as_ref(), alice.public_key().as_ref());
+        assert_eq!(registry.get(3).unwrap().as_ref(), bob.public_key().as_ref());
+
+        // A bad line fails the whole file
+        let mut registry = KeyRegistry::new();
+        for bad in [format!("4 {}", &alice.public_key_hex()[2..]), "four 00".to_string(), "5".to_string()] {
+            assert!(registry.load_peer_keys(&format!("{}\n{}", text, bad)).is_err());
+        }
+        assert!(registry.is_empty());
+    }
+}
@@ -3,6 +3,8 @@ pub mod network;
 pub mod consensus;
 pub mod crypto;
 pub mod utils;
+#[cfg(feature = "http")]
+pub mod api;
 
 pub use core::*;
 pub use network::*;
@@ -1,4 +1,10 @@
 use antcolony_consensus::*;
+use antcolony_consensus::core::ant_agent::{ENERGY_DECAY_RATE, INITIAL_ANT_ENERGY};
+use antcolony_consensus::core::config::{
+    DEFAULT_MAX_PHEROMONE_AGE_SECS, DEFAULT_MAX_PHEROMONE_SKEW_SECS, DEFAULT_MIN_QUORUM, DEFAULT_STEP_INTERVAL_MS,
+};
+use antcolony_consensus::core::node_state::{DEFAULT_EVAPORATION_RATE, MAX_NEIGHBORS};
+use antcolony_consensus::core::pheromone::CONSENSUS_THRESHOLD;
 use clap::Parser;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -12,7 +18,7 @@ struct Args {
     #[arg(short, long, default_value = "1")]
     node_id: u32,
 
-    /// Multicast address
+    /// Multicast address (IPv4, or IPv6 such as [ff02::1]:5000)
     #[arg(short, long, default_value = "239.255.0.1:5000")]
     multicast_addr: String,
 
@@ -20,64 +26,233 @@ struct Args {
     #[arg(short, long, default_value = "5000")]
     port: u16,
 
+    /// Local IPv4 address of the interface to join the multicast group on
+    /// and send from (default: chosen by the OS)
+    #[arg(long)]
+    interface: Option<std::net::Ipv4Addr>,
+
     /// Enable verbose logging
     #[arg(short, long)]
     verbose: bool,
+
+    /// Pheromone intensity required for consensus
+    #[arg(long, default_value_t = CONSENSUS_THRESHOLD)]
+    consensus_threshold: f64,
+
+    /// Minimum number of distinct sources backing a consensus value
+    #[arg(long, default_value_t = DEFAULT_MIN_QUORUM)]
+    min_quorum: usize,
+
+    /// Fraction of pheromone intensity lost per second
+    #[arg(long, default_value_t = DEFAULT_EVAPORATION_RATE)]
+    evaporation_rate: f64,
+
+    /// Starting energy for ant agents
+    #[arg(long, default_value_t = INITIAL_ANT_ENERGY)]
+    ant_energy: f64,
+
+    /// Energy ants lose per step
+    #[arg(long, default_value_t = ENERGY_DECAY_RATE)]
+    energy_decay_rate: f64,
+
+    /// Milliseconds between consensus steps
+    #[arg(long, default_value_t = DEFAULT_STEP_INTERVAL_MS)]
+    step_interval_ms: u64,
+
+    /// Let the step interval back off to this many milliseconds while the
+    /// node is idle, stepping every --step-interval-ms when busy
+    #[arg(long)]
+    max_step_interval_ms: Option<u64>,
+
+    /// Seconds a received pheromone may be dated ahead of this node's
+    /// clock (0 accepts any future date)
+    #[arg(long, default_value_t = DEFAULT_MAX_PHEROMONE_SKEW_SECS)]
+    max_pheromone_skew_secs: u64,
+
+    /// Age in seconds beyond which a received pheromone is refused
+    /// (0 accepts any age)
+    #[arg(long, default_value_t = DEFAULT_MAX_PHEROMONE_AGE_SECS)]
+    max_pheromone_age_secs: u64,
+
+    /// Neighbors kept at once; a new one evicts the neighbor heard from
+    /// least recently
+    #[arg(long, default_value_t = MAX_NEIGHBORS)]
+    max_neighbors: usize,
+
+    /// File of values to propose, one per line ("-" reads stdin)
+    #[arg(long)]
+    propose_from: Option<String>,
+
+    /// Milliseconds between values proposed from --propose-from
+    #[arg(long, default_value_t = 1000)]
+    propose_interval_ms: u64,
+
+    /// Milliseconds between heartbeats (0 disables them)
+    #[arg(long, default_value_t = 5000)]
+    heartbeat_interval_ms: u64,
+
+    /// Random variation of each heartbeat gap, as a fraction of the interval
+    #[arg(long, default_value_t = DEFAULT_HEARTBEAT_JITTER)]
+    heartbeat_jitter: f64,
+
+    /// PKCS8 private key (raw, PEM, hex or base64) the node signs with;
+    /// a fresh key is generated and saved there as PEM if the file is absent
+    #[arg(long)]
+    key_file: Option<std::path::PathBuf>,
+
+    /// File of peers' public keys, one "<node id> <hex or base64 key>"
+    /// per line; pheromones and signed messages from nodes not listed
+    /// are refused
+    #[arg(long)]
+    peer_keys: Option<std::path::PathBuf>,
+
+    /// Write-ahead log to restore the node from on startup and record
+    /// commits to while running
+    #[arg(long)]
+    wal: Option<std::path::PathBuf>,
+
+    /// Address to serve the HTTP control API on, e.g. 127.0.0.1:8080
+    #[cfg(feature = "http")]
+    #[arg(long)]
+    http_addr: Option<std::net::SocketAddr>,
+}
+
+impl Args {
+    /// Build the consensus configuration from command-line flags
+    fn consensus_config(&self) -> ConsensusConfig {
+        ConsensusConfig {
+            consensus_threshold: self.consensus_threshold,
+            min_quorum: self.min_quorum,
+            evaporation_rate: self.evaporation_rate,
+            initial_ant_energy: self.ant_energy,
+            energy_decay_rate: self.energy_decay_rate,
+            step_interval_ms: self.step_interval_ms,
+            adaptive_step_interval: self.max_step_interval_ms.map(|max_ms| StepIntervalBounds {
+                min_ms: self.step_interval_ms,
+                max_ms,
+            }),
+            max_pheromone_skew_secs: (self.max_pheromone_skew_secs > 0).then_some(self.max_pheromone_skew_secs),
+            max_pheromone_age_secs: (self.max_pheromone_age_secs > 0).then_some(self.max_pheromone_age_secs),
+            max_neighbors: self.max_neighbors,
+            ..ConsensusConfig::default()
+        }
+    }
+
+    /// Heartbeat interval, or `None` when heartbeats are disabled
+    fn heartbeat_interval(&self) -> Option<std::time::Duration> {
+        (self.heartbeat_interval_ms > 0)
+            .then(|| std::time::Duration::from_millis(self.heartbeat_interval_ms))
+    }
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
 
-    // Initialize logging
+    // Initialize logging; RUST_LOG, when set, overrides --verbose
     let log_level = if args.verbose { "debug" } else { "info" };
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| format!("antcolony_consensus={}", log_level).into());
     tracing_subscriber::fmt()
-        .with_env_filter(format!("antcolony_consensus={}", log_level))
+        .with_env_filter(filter)
         .init();
 
     info!("🐜 AntColony Consensus Node {} starting...", args.node_id);
 
     // Create node state
-    let node_state = Arc::new(RwLock::new(
-        NodeState::new(args.node_id)
-    ));
+    let mut state = NodeState::with_config(args.node_id, args.consensus_config());
+    if let Some(path) = &args.key_file {
+        let (key_pair, generated) = KeyPairWrapper::load_or_generate(path)?;
+        if generated {
+            info!("Generated a new node key at {}", path.display());
+        } else {
+            info!("Loaded node key from {}", path.display());
+        }
+        state.set_key_pair(key_pair);
+    }
+    info!("Node public key: {}", state.key_pair.public_key_hex());
+    if let Some(path) = &args.peer_keys {
+        let count = state.key_registry.load_peer_file(path)?;
+        info!("Registered {} peer keys from {}", count, path.display());
+    }
+    if let Some(path) = &args.wal {
+        if state.load_snapshot(path)? {
+            info!("Restored state from {}", path.display());
+        }
+        state.attach_wal(WriteAheadLog::open(path)?);
+    }
+    let node_state = Arc::new(RwLock::new(state));
 
     // Initialize network layer
     let network = NetworkManager::new(
         args.multicast_addr.parse()?,
         args.port,
         node_state.clone(),
-    ).await?;
+    ).await?
+    .with_interface(args.interface)
+    .with_heartbeat_interval(args.heartbeat_interval())
+    .with_heartbeat_jitter(args.heartbeat_jitter);
 
     // Start consensus engine
-    let consensus_engine = ConsensusEngine::new(
+    let consensus_engine = Arc::new(ConsensusEngine::new(
         node_state.clone(),
-        network.clone(),
-    );
+        Arc::new(network.clone()),
+    ));
 
     info!("Node {} initialized successfully", args.node_id);
     info!("Listening on multicast: {}", args.multicast_addr);
     info!("Press Ctrl+C to stop");
 
     // Start all services
-    let network_handle = tokio::spawn(async move {
-        if let Err(e) = network.start().await {
-            error!("Network error: {}", e);
-        }
-    });
+    if let Err(e) = network.start().await {
+        error!("Network error: {}", e);
+    }
 
+    let engine_task = consensus_engine.clone();
     let consensus_handle = tokio::spawn(async move {
-        if let Err(e) = consensus_engine.run().await {
+        if let Err(e) = engine_task.run().await {
             error!("Consensus error: {}", e);
         }
     });
 
+    #[cfg(feature = "http")]
+    if let Some(addr) = args.http_addr {
+        antcolony_consensus::api::serve(consensus_engine.clone(), addr).await?;
+    }
+
+    // Propose values from a file or stdin, if requested
+    if let Some(source) = args.propose_from.clone() {
+        let interval = std::time::Duration::from_millis(args.propose_interval_ms);
+        let engine = consensus_engine.clone();
+        tokio::spawn(async move {
+            let result = if source == "-" {
+                AutoProposer::from_stdin(engine).with_interval(interval).run().await
+            } else {
+                match AutoProposer::from_file(engine, &source).await {
+                    Ok(proposer) => proposer.with_interval(interval).run().await,
+                    Err(e) => Err(e),
+                }
+            };
+            if let Err(e) = result {
+                error!("Auto-proposer error: {}", e);
+            }
+        });
+    }
+
     // Wait for shutdown signal
     tokio::signal::ctrl_c().await?;
     info!("Shutting down...");
 
-    network_handle.abort();
     consensus_handle.abort();
+    if let Err(e) = network.shutdown().await {
+        error!("Network shutdown error: {}", e);
+    }
+
+    if let Some(path) = &args.wal {
+        if let Err(e) = node_state.read().await.save_snapshot(path) {
+            error!("Failed to save snapshot: {}", e);
+        }
+    }
 
     Ok(())
 }
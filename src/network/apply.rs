@@ -0,0 +1,605 @@
+use crate::core::config::DissentPolicy;
+use crate::core::events::NodeEvent;
+use crate::core::node_state::{NodeState, SharedNodeState};
+use crate::core::pheromone::Pheromone;
+use crate::core::types::{NodeId, DEFAULT_ROUND};
+use crate::network::message::Message;
+use crate::utils::timing::current_timestamp_ms;
+use std::time::Duration;
+use tracing::{debug, info, info_span, warn, Instrument};
+
+/// What `apply_message` did with a message
+#[derive(Debug, Clone)]
+pub enum ApplyOutcome {
+    /// At least one pheromone the message carried was stored
+    Stored,
+
+    /// The message updated node state without storing a pheromone:
+    /// liveness, neighbors, announcements, dissent or a withdrawal
+    Applied,
+
+    /// The message, or every pheromone it carried, failed verification or
+    /// validation
+    Rejected,
+
+    /// The message was this node's own, addressed to another node, or
+    /// carried nothing new
+    Ignored,
+
+    /// The message was applied and calls for this reply, which the caller
+    /// should broadcast (currently only a `Dissent` under
+    /// `DissentPolicy::Broadcast`)
+    Reply(Box<Message>),
+}
+
+impl ApplyOutcome {
+    /// Reply to broadcast, if the outcome carries one
+    pub fn into_reply(self) -> Option<Message> {
+        match self {
+            ApplyOutcome::Reply(reply) => Some(*reply),
+            _ => None,
+        }
+    }
+}
+
+/// Verify `message` and apply it to `node_state`, reporting what happened
+///
+/// This is the whole receive path short of decoding: every transport
+/// (multicast, TCP, in-memory, simulated) hands decoded messages here, and
+/// tests can drive a node with it directly. Signatures are checked against
+/// the node's `key_registry`.
+///
+/// Runs in a `network_receive` span carrying the receiving `node_id`, the
+/// `sender`, the message `kind` and, when it concerns one round, its
+/// `round_id`.
+pub async fn apply_message(message: &Message, node_state: &SharedNodeState) -> Result<ApplyOutcome, String> {
+    let node_id = node_state.read().await.id;
+    let span = info_span!(
+        "network_receive",
+        node_id,
+        sender = message.sender(),
+        kind = message.kind(),
+        round_id = message.round(),
+    );
+    apply(message, node_state).instrument(span).await
+}
+
+async fn apply(message: &Message, node_state: &SharedNodeState) -> Result<ApplyOutcome, String> {
+    {
+        let mut state = node_state.write().await;
+
+        // Drop forged or unsigned control messages before they can
+        // touch liveness or neighbor state. Pheromone-carrying messages
+        // have no message signature, so they vouch for their sender only
+        // once one of its own pheromones verifies (see `vouch_for_sender`)
+        let signed = message.is_signed_by_sender(&state.key_registry);
+        if state.config.authenticate_messages && !signed && !message.is_authenticated(&state.key_registry) {
+            state.stats.messages_rejected += 1;
+            warn!("Rejected unauthenticated message");
+            return Ok(ApplyOutcome::Rejected);
+        }
+
+        // Any other message from another node proves it is still alive,
+        // bar its farewell
+        if let Some(sender) = message.sender() {
+            if sender == state.id {
+                return Ok(ApplyOutcome::Ignored);
+            }
+            state.stats.messages_received += 1;
+            state.stats.record_received(message.kind());
+            if !carries_pheromones(message) && !matches!(message, Message::Leave { .. }) {
+                state.mark_seen(sender);
+                // Only a signed, freshly stamped message proves the sender
+                // exists; a replayed one would keep a departed node counted
+                let now = state.clock.now();
+                let window = state.config.cluster_window_secs;
+                if signed && message.timestamp().is_some_and(|stamped| stamped.abs_diff(now) <= window) {
+                    state.observe_node(sender);
+                }
+            }
+        }
+    }
+
+    let mut state = node_state.write().await;
+    let outcome = match message {
+        Message::PheromoneBroadcast { pheromone, sender } => {
+            // Receive pheromone, dropping it if the signature doesn't verify
+            if state.accept_pheromone_from(pheromone.clone(), *sender) {
+                debug!(source = pheromone.source, value = %pheromone.value, "Received pheromone");
+                vouch_for_sender(&mut state, [pheromone.source], *sender);
+                ApplyOutcome::Stored
+            } else {
+                warn!(source = pheromone.source, value = %pheromone.value, "Rejected pheromone");
+                ApplyOutcome::Rejected
+            }
+        }
+
+        Message::PheromoneBatch { pheromones, sender } => {
+            // Each pheromone passes the same checks as one arriving in a
+            // `PheromoneBroadcast`, with signatures verified together
+            let pheromones = pheromones
+                .iter()
+                .cloned()
+                .map(|mut pheromone| {
+                    pheromone.received_from = Some(*sender);
+                    pheromone
+                })
+                .collect();
+            let stored = accept_batch(&mut state, pheromones);
+            if stored.is_empty() {
+                ApplyOutcome::Rejected
+            } else {
+                vouch_for_sender(&mut state, stored.into_iter().map(|(source, _)| source), *sender);
+                ApplyOutcome::Stored
+            }
+        }
+
+        Message::AntMovement { ant_id, from_node, to_node, carried_pheromone, ttl, extra_pheromones } => {
+            // Only the node the ant moved to takes it in
+            let carried: Vec<&Pheromone> = carried_pheromone.iter().chain(extra_pheromones).collect();
+            if to_node != &state.id || carried.is_empty() {
+                ApplyOutcome::Ignored
+            } else {
+                let mut accepted = Vec::new();
+                for pheromone in carried {
+                    if state.accept_pheromone_from(pheromone.clone(), *from_node) {
+                        debug!(ant_id, value = %pheromone.value, "Ant arrived with pheromone");
+                        accepted.push(pheromone.clone());
+                    } else {
+                        warn!(ant_id, source = pheromone.source, value = %pheromone.value, "Rejected pheromone carried by ant");
+                    }
+                }
+
+                if accepted.is_empty() {
+                    ApplyOutcome::Rejected
+                } else {
+                    vouch_for_sender(&mut state, accepted.iter().map(|p| p.source), *from_node);
+                    // Keep the ant moving while it has hops to spare
+                    if state.adopt_ant(*ant_id, *from_node, accepted, *ttl) {
+                        debug!(ant_id, ttl = ttl - 1, "Adopted ant");
+                    }
+                    ApplyOutcome::Stored
+                }
+            }
+        }
+
+        Message::NeighborDiscovery { node_id, neighbors, .. } => {
+            state.add_neighbor(*node_id);
+            // The advertised ids are hearsay and only fill free room
+            for neighbor in neighbors {
+                state.add_advertised_neighbor(*neighbor);
+            }
+            ApplyOutcome::Applied
+        }
+
+        Message::ConsensusAnnouncement { node_id, round, value, .. } => {
+            if *round == DEFAULT_ROUND {
+                state.record_announcement(*node_id, value.clone());
+            }
+
+            match state.validate_value(value) {
+                Ok(()) => {
+                    info!(value = %value, "Peer announced consensus");
+                    ApplyOutcome::Applied
+                }
+                Err(e) => {
+                    warn!(value = %value, error = %e, "Peer announced consensus on rejected value");
+
+                    if state.config.dissent_policy == DissentPolicy::Broadcast {
+                        let dissent = Message::Dissent {
+                            node_id: state.id,
+                            value: value.clone(),
+                            reason: e.to_string(),
+                            signature: None,
+                        }.sign(&state.key_pair);
+                        ApplyOutcome::Reply(Box::new(dissent))
+                    } else {
+                        ApplyOutcome::Rejected
+                    }
+                }
+            }
+        }
+
+        Message::Dissent { node_id, value, reason, .. } => {
+            warn!(value = %value, reason = %reason, "Peer disputes consensus");
+            state.events.push(NodeEvent::DissentReceived {
+                node_id: *node_id,
+                value: value.clone(),
+                reason: reason.clone(),
+            });
+            ApplyOutcome::Applied
+        }
+
+        Message::ConsensusResponse { node_id, pheromones } => {
+            let pheromones = pheromones
+                .iter()
+                .cloned()
+                .map(|mut pheromone| {
+                    pheromone.received_from = Some(*node_id);
+                    pheromone
+                })
+                .collect();
+            let report = state.ingest_response(pheromones);
+            debug!(
+                accepted = report.accepted,
+                rejected = report.rejected,
+                duplicate = report.duplicate,
+                rate_limited = report.rate_limited,
+                "Ingested consensus response"
+            );
+
+            if report.accepted > 0 {
+                ApplyOutcome::Stored
+            } else if report.rejected > 0 {
+                ApplyOutcome::Rejected
+            } else {
+                ApplyOutcome::Ignored
+            }
+        }
+
+        Message::Heartbeat { node_id, sent_ms, echoes, .. } => {
+            debug!("Heartbeat");
+
+            // Only signed echoes time a link; forged ones could steer ants
+            if message.is_signed_by_sender(&state.key_registry) {
+                let now_ms = current_timestamp_ms();
+                state.hear_heartbeat(*node_id, *sent_ms, now_ms);
+                let own = state.id;
+                if let Some(echo) = echoes.iter().find(|echo| echo.node_id == own) {
+                    let round_trip = now_ms.saturating_sub(echo.sent_ms).saturating_sub(echo.held_ms);
+                    state.record_latency(*node_id, Duration::from_millis(round_trip));
+                }
+            }
+            ApplyOutcome::Applied
+        }
+
+        Message::Withdraw { node_id, round, value, timestamp, .. } => {
+            // A forged withdrawal would erase honest support, so it must
+            // verify even when other control messages needn't
+            if !message.is_authenticated(&state.key_registry) {
+                state.stats.messages_rejected += 1;
+                warn!("Rejected unsigned withdrawal");
+                ApplyOutcome::Rejected
+            } else {
+                info!(value = %value, "Peer withdrew its support");
+                state.withdraw(*node_id, *round, value, *timestamp);
+                ApplyOutcome::Applied
+            }
+        }
+
+        Message::Leave { node_id, timestamp, .. } => {
+            // Like a withdrawal, a forged farewell would cut off an honest
+            // neighbor, so it must verify whatever the authentication
+            // setting. A farewell dated outside the neighbor timeout, or
+            // older than the last word from the node, is a replay
+            let now = state.clock.now();
+            let stale = timestamp.abs_diff(now) > state.config.neighbor_timeout_secs
+                || state.last_seen.get(node_id).is_some_and(|seen| *seen > *timestamp);
+            if !message.is_authenticated(&state.key_registry) {
+                state.stats.messages_rejected += 1;
+                warn!("Rejected unsigned farewell");
+                ApplyOutcome::Rejected
+            } else if stale {
+                state.stats.messages_rejected += 1;
+                warn!(timestamp, "Rejected stale farewell");
+                ApplyOutcome::Rejected
+            } else {
+                // Drop the neighbor now rather than wait for it to time out
+                info!("Peer left the network");
+                state.remove_neighbor(*node_id);
+                ApplyOutcome::Applied
+            }
+        }
+    };
+
+    Ok(outcome)
+}
+
+/// Whether `message` carries pheromones in place of a message signature
+fn carries_pheromones(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::PheromoneBroadcast { .. }
+            | Message::PheromoneBatch { .. }
+            | Message::AntMovement { .. }
+            | Message::ConsensusResponse { .. }
+    )
+}
+
+/// Mark `sender` seen, and so a neighbor, if one of the verified pheromone
+/// `sources` is its own; anyone can claim to relay someone else's
+pub(crate) fn vouch_for_sender(state: &mut NodeState, sources: impl IntoIterator<Item = NodeId>, sender: NodeId) {
+    if sources.into_iter().any(|source| source == sender) {
+        state.mark_seen(sender);
+    }
+}
+
+/// Accept pheromones with batch verification, logging each rejection and
+/// returning the `(source, received_from)` of each one stored
+pub(crate) fn accept_batch(state: &mut NodeState, pheromones: Vec<Pheromone>) -> Vec<(NodeId, Option<NodeId>)> {
+    let received: Vec<(NodeId, Option<NodeId>)> = pheromones
+        .iter()
+        .map(|p| (p.source, p.received_from))
+        .collect();
+
+    let mut stored = Vec::new();
+    for ((source, sender), accepted) in received.into_iter().zip(state.accept_pheromones(pheromones)) {
+        if accepted {
+            stored.push((source, sender));
+            debug!(source, sender, "Received pheromone");
+        } else {
+            warn!(source, sender, "Rejected pheromone");
+        }
+    }
+    stored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::node_state::NodeState;
+    use crate::core::types::{ConsensusValue, ValueKind};
+    use crate::network::message::HeartbeatEcho;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    /// Node 1 with node 2's key registered, and node 2 itself
+    fn pair() -> (SharedNodeState, NodeState) {
+        let peer = NodeState::new(2);
+        let mut state = NodeState::new(1);
+        state.register_public_key(2, peer.public_key());
+        (Arc::new(RwLock::new(state)), peer)
+    }
+
+    #[tokio::test]
+    async fn test_pheromone_outcomes() {
+        let (node_state, mut peer) = pair();
+        let value = ConsensusValue::from_string("alpha");
+
+        let broadcast = Message::PheromoneBroadcast { pheromone: peer.emit_pheromone(value.clone()).unwrap(), sender: 2 };
+        assert!(matches!(apply_message(&broadcast, &node_state).await.unwrap(), ApplyOutcome::Stored));
+
+        let mut forged = peer.emit_pheromone(value.clone()).unwrap();
+        forged.value = ConsensusValue::from_string("beta");
+        let tampered = Message::PheromoneBroadcast { pheromone: forged.clone(), sender: 2 };
+        assert!(matches!(apply_message(&tampered, &node_state).await.unwrap(), ApplyOutcome::Rejected));
+
+        let batch = Message::PheromoneBatch { pheromones: vec![forged, peer.emit_pheromone(value.clone()).unwrap()], sender: 2 };
+        assert!(matches!(apply_message(&batch, &node_state).await.unwrap(), ApplyOutcome::Stored));
+
+        let own = Message::PheromoneBroadcast { pheromone: peer.emit_pheromone(value).unwrap(), sender: 1 };
+        assert!(matches!(apply_message(&own, &node_state).await.unwrap(), ApplyOutcome::Ignored));
+    }
+
+    #[tokio::test]
+    async fn test_forged_pheromone_leaves_neighbors_alone() {
+        let (node_state, mut peer) = pair();
+        {
+            let mut state = node_state.write().await;
+            state.config.max_neighbors = 2;
+            state.config.authenticate_messages = true;
+            state.add_neighbor(2);
+            state.add_neighbor(3);
+        }
+
+        // Claims to come from node 99, which has no key to verify it
+        let mut outsider = NodeState::new(99);
+        let forged = Message::PheromoneBroadcast { pheromone: outsider.emit_pheromone(ConsensusValue::from_string("evict")).unwrap(), sender: 99 };
+        assert!(matches!(apply_message(&forged, &node_state).await.unwrap(), ApplyOutcome::Rejected));
+
+        // Relaying node 2's genuine pheromone doesn't vouch for node 98
+        let relayed = Message::PheromoneBatch { pheromones: vec![peer.emit_pheromone(ConsensusValue::from_string("relayed")).unwrap()], sender: 98 };
+        assert!(matches!(apply_message(&relayed, &node_state).await.unwrap(), ApplyOutcome::Stored));
+
+        assert_eq!(node_state.read().await.neighbors, HashSet::from([2, 3]));
+    }
+
+    #[tokio::test]
+    async fn test_ant_movement_outcomes() {
+        let (node_state, mut peer) = pair();
+        let pheromone = peer.emit_pheromone(ConsensusValue::from_string("carried")).unwrap();
+
+        let elsewhere = Message::AntMovement { ant_id: 1, from_node: 2, to_node: 3, carried_pheromone: Some(pheromone.clone()), ttl: 0, extra_pheromones: vec![] };
+        assert!(matches!(apply_message(&elsewhere, &node_state).await.unwrap(), ApplyOutcome::Ignored));
+
+        let empty = Message::AntMovement { ant_id: 2, from_node: 2, to_node: 1, carried_pheromone: None, ttl: 0, extra_pheromones: vec![] };
+        assert!(matches!(apply_message(&empty, &node_state).await.unwrap(), ApplyOutcome::Ignored));
+
+        let arrival = Message::AntMovement { ant_id: 3, from_node: 2, to_node: 1, carried_pheromone: Some(pheromone), ttl: 0, extra_pheromones: vec![] };
+        assert!(matches!(apply_message(&arrival, &node_state).await.unwrap(), ApplyOutcome::Stored));
+    }
+
+    #[tokio::test]
+    async fn test_response_outcomes() {
+        let (node_state, mut peer) = pair();
+        let pheromone = peer.emit_pheromone(ConsensusValue::from_string("answered")).unwrap();
+        let response = Message::ConsensusResponse { node_id: 2, pheromones: vec![pheromone] };
+
+        assert!(matches!(apply_message(&response, &node_state).await.unwrap(), ApplyOutcome::Stored));
+
+        // Everything in a repeated response is already known
+        assert!(matches!(apply_message(&response, &node_state).await.unwrap(), ApplyOutcome::Ignored));
+    }
+
+    #[tokio::test]
+    async fn test_control_message_outcomes() {
+        let (node_state, peer) = pair();
+        let value = ConsensusValue::from_string("settled");
+
+        let heartbeat = Message::Heartbeat { node_id: 2, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None };
+        assert!(matches!(apply_message(&heartbeat, &node_state).await.unwrap(), ApplyOutcome::Applied));
+
+        let discovery = Message::NeighborDiscovery { node_id: 2, neighbors: vec![3], tcp_port: None, signature: None };
+        assert!(matches!(apply_message(&discovery, &node_state).await.unwrap(), ApplyOutcome::Applied));
+        assert!(node_state.read().await.neighbors.contains(&3));
+
+        let announcement = Message::ConsensusAnnouncement { node_id: 2, round: DEFAULT_ROUND, value: value.clone(), signature: None };
+        assert!(matches!(apply_message(&announcement, &node_state).await.unwrap(), ApplyOutcome::Applied));
+
+        let dissent = Message::Dissent { node_id: 2, value: value.clone(), reason: "no".into(), signature: None };
+        assert!(matches!(apply_message(&dissent, &node_state).await.unwrap(), ApplyOutcome::Applied));
+
+        // Withdrawals must be signed whatever the authentication setting
+        let withdraw = Message::Withdraw { node_id: 2, round: DEFAULT_ROUND, value, timestamp: 0, signature: None };
+        assert!(matches!(apply_message(&withdraw, &node_state).await.unwrap(), ApplyOutcome::Rejected));
+        let signed = withdraw.sign(&peer.key_pair);
+        assert!(matches!(apply_message(&signed, &node_state).await.unwrap(), ApplyOutcome::Applied));
+    }
+
+    #[tokio::test]
+    async fn test_leave_removes_neighbor_immediately() {
+        use crate::utils::timing::{Clock, ManualClock};
+
+        let (node_state, peer) = pair();
+        let clock = Arc::new(ManualClock::new(1_000));
+        node_state.write().await.clock = clock.clone();
+        let heartbeat = Message::Heartbeat { node_id: 2, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None };
+        apply_message(&heartbeat, &node_state).await.unwrap();
+        assert!(node_state.read().await.neighbors.contains(&2));
+
+        // Anyone could claim node 2 left; only its signature counts
+        let unsigned = Message::Leave { node_id: 2, timestamp: clock.now(), signature: None };
+        assert!(matches!(apply_message(&unsigned, &node_state).await.unwrap(), ApplyOutcome::Rejected));
+        assert!(node_state.read().await.neighbors.contains(&2));
+
+        clock.advance(5);
+        let leave = Message::Leave { node_id: 2, timestamp: clock.now(), signature: None }.sign(&peer.key_pair);
+        assert!(matches!(apply_message(&leave, &node_state).await.unwrap(), ApplyOutcome::Applied));
+        {
+            let state = node_state.read().await;
+            assert!(!state.neighbors.contains(&2));
+            assert!(!state.last_seen.contains_key(&2));
+        }
+
+        // Once node 2 is back, replaying its farewell cuts it off no more,
+        // nor does it once the farewell has aged past the timeout
+        clock.advance(1);
+        apply_message(&heartbeat, &node_state).await.unwrap();
+        assert!(matches!(apply_message(&leave, &node_state).await.unwrap(), ApplyOutcome::Rejected));
+        clock.advance(node_state.read().await.config.neighbor_timeout_secs + 1);
+        node_state.write().await.last_seen.clear();
+        assert!(matches!(apply_message(&leave, &node_state).await.unwrap(), ApplyOutcome::Rejected));
+        assert!(node_state.read().await.neighbors.contains(&2));
+        assert_eq!(node_state.read().await.stats.messages_rejected, 3);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_announcement_outcomes() {
+        let (node_state, _) = pair();
+        node_state.write().await.config.value_kind = Some(ValueKind::Number);
+        let announcement = Message::ConsensusAnnouncement {
+            node_id: 2,
+            round: DEFAULT_ROUND,
+            value: ConsensusValue::from_string("not a number"),
+            signature: None,
+        };
+
+        assert!(matches!(apply_message(&announcement, &node_state).await.unwrap(), ApplyOutcome::Rejected));
+
+        node_state.write().await.config.dissent_policy = DissentPolicy::Broadcast;
+        let outcome = apply_message(&announcement, &node_state).await.unwrap();
+        assert!(matches!(outcome.into_reply(), Some(Message::Dissent { node_id: 1, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_unauthenticated_message_rejected() {
+        let (node_state, _) = pair();
+        node_state.write().await.config.authenticate_messages = true;
+
+        let heartbeat = Message::Heartbeat { node_id: 2, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None };
+        assert!(matches!(apply_message(&heartbeat, &node_state).await.unwrap(), ApplyOutcome::Rejected));
+        assert_eq!(node_state.read().await.stats.messages_rejected, 1);
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_echo_times_link() {
+        let (node_state, peer) = pair();
+        node_state.write().await.add_neighbor(2);
+        let now_ms = current_timestamp_ms();
+        let beat = |echoes| Message::Heartbeat { node_id: 2, timestamp: 0, sent_ms: now_ms - 5, echoes, signature: None };
+
+        // Node 2 echoes a heartbeat node 1 sent 50ms ago and it held for 20ms
+        let echo = HeartbeatEcho { node_id: 1, sent_ms: now_ms - 50, held_ms: 20 };
+        apply_message(&beat(vec![echo]), &node_state).await.unwrap();
+        assert!(node_state.read().await.link_latency.is_empty(), "unsigned echo timed the link");
+
+        apply_message(&beat(vec![echo]).sign(&peer.key_pair), &node_state).await.unwrap();
+        let mut state = node_state.write().await;
+        let latency = state.link_latency[&2];
+        assert!(latency >= Duration::from_millis(30) && latency < Duration::from_secs(1), "{:?}", latency);
+
+        // Node 2's own heartbeat is echoed back once
+        let echoes = state.take_heartbeat_echoes(now_ms + 10);
+        assert_eq!(echoes.len(), 1);
+        assert_eq!((echoes[0].0, echoes[0].1), (2, now_ms - 5));
+        assert!(state.take_heartbeat_echoes(now_ms + 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cluster_estimate_follows_heartbeats() {
+        use crate::utils::timing::{Clock, ManualClock};
+
+        let clock = Arc::new(ManualClock::new(1_000));
+        let mut state = NodeState::new(1);
+        state.clock = clock.clone();
+        state.config.max_neighbors = 4;
+        let peers: Vec<NodeState> = (2..=13).map(NodeState::new).collect();
+        for peer in &peers {
+            state.register_public_key(peer.id, peer.public_key());
+        }
+        let node_state = Arc::new(RwLock::new(state));
+        let beat = |peer: &NodeState| Message::Heartbeat { node_id: peer.id, timestamp: clock.now(), sent_ms: 0, echoes: vec![], signature: None }.sign(&peer.key_pair);
+
+        // Spoofed, unsigned heartbeats from made-up ids count for nothing
+        for node_id in 100..200 {
+            let spoofed = Message::Heartbeat { node_id, timestamp: clock.now(), sent_ms: 0, echoes: vec![], signature: None };
+            apply_message(&spoofed, &node_state).await.unwrap();
+        }
+        assert_eq!(node_state.read().await.estimated_cluster_size(), 1);
+
+        // Twelve peers beat twice; the estimate counts every one of them
+        // plus this node, though only four fit in the neighbor set
+        for _ in 0..2 {
+            for peer in &peers {
+                apply_message(&beat(peer), &node_state).await.unwrap();
+            }
+            clock.advance(10);
+        }
+        assert_eq!(node_state.read().await.estimated_cluster_size(), 13);
+
+        // Only two keep beating once the rest go silent
+        let window = node_state.read().await.config.cluster_window_secs;
+        for _ in 0..=window / 10 {
+            for peer in &peers[..2] {
+                apply_message(&beat(peer), &node_state).await.unwrap();
+            }
+            clock.advance(10);
+        }
+        assert_eq!(node_state.read().await.estimated_cluster_size(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_replayed_heartbeat_does_not_keep_node_counted() {
+        use crate::utils::timing::{Clock, ManualClock};
+
+        let clock = Arc::new(ManualClock::new(1_000));
+        let mut state = NodeState::new(1);
+        state.clock = clock.clone();
+        let peer = NodeState::new(2);
+        state.register_public_key(peer.id, peer.public_key());
+        let node_state = Arc::new(RwLock::new(state));
+        let window = node_state.read().await.config.cluster_window_secs;
+
+        // A captured heartbeat from before the node left
+        let captured = Message::Heartbeat { node_id: 2, timestamp: clock.now(), sent_ms: 0, echoes: vec![], signature: None }
+            .sign(&peer.key_pair);
+        apply_message(&captured, &node_state).await.unwrap();
+        assert_eq!(node_state.read().await.estimated_cluster_size(), 2);
+
+        // Replayed once the window has passed, it no longer counts
+        clock.advance(window + 1);
+        apply_message(&captured, &node_state).await.unwrap();
+        assert_eq!(node_state.read().await.estimated_cluster_size(), 1);
+    }
+}
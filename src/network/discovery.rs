@@ -1,13 +1,31 @@
 use crate::core::node_state::SharedNodeState;
+use crate::core::types::NodeId;
 use crate::network::message::Message;
 use crate::network::multicast::NetworkManager;
-use tokio::time::{interval, Duration};
+use crate::utils::random::RngSource;
+use tokio::time::{sleep, Duration};
 use tracing::info;
 
+/// Default interval between discovery broadcasts
+pub const DEFAULT_DISCOVERY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Default upper bound on the random delay added to each interval
+pub const DEFAULT_DISCOVERY_JITTER: Duration = Duration::from_secs(2);
+
+/// Default number of neighbors advertised per discovery broadcast
+pub const DEFAULT_MAX_ADVERTISED_NEIGHBORS: usize = 16;
+
 /// Neighbor discovery service
+///
+/// Each broadcast advertises a random sample of at most
+/// `max_advertised_neighbors` neighbors, so message size stays bounded
+/// while the whole neighbor set still spreads over successive rounds.
 pub struct NeighborDiscovery {
     node_state: SharedNodeState,
     network: NetworkManager,
+    interval: Duration,
+    jitter: Duration,
+    max_advertised_neighbors: usize,
 }
 
 impl NeighborDiscovery {
@@ -16,35 +34,122 @@ impl NeighborDiscovery {
         Self {
             node_state,
             network,
+            interval: DEFAULT_DISCOVERY_INTERVAL,
+            jitter: DEFAULT_DISCOVERY_JITTER,
+            max_advertised_neighbors: DEFAULT_MAX_ADVERTISED_NEIGHBORS,
         }
     }
 
+    /// Broadcast every `interval` plus a random delay of up to `jitter`,
+    /// so nodes started together don't broadcast in lockstep
+    pub fn with_interval(mut self, interval: Duration, jitter: Duration) -> Self {
+        self.interval = interval;
+        self.jitter = jitter;
+        self
+    }
+
+    /// Advertise at most `max` neighbors per broadcast
+    pub fn with_max_advertised_neighbors(mut self, max: usize) -> Self {
+        self.max_advertised_neighbors = max;
+        self
+    }
+
     /// Start neighbor discovery
     pub async fn start(&self) {
         let node_state = self.node_state.clone();
         let network = self.network.clone();
+        let interval = self.interval;
+        let jitter = self.jitter;
+        let max_advertised = self.max_advertised_neighbors;
 
         tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(10));
-
             loop {
-                interval.tick().await;
-
-                let (node_id, neighbors) = {
-                    let state = node_state.read().await;
-                    (state.id, state.get_neighbors())
-                };
-
-                let message = Message::NeighborDiscovery {
-                    node_id,
-                    neighbors,
-                };
-
+                let message = Self::discovery_message(&node_state, &network, max_advertised).await;
                 if let Err(e) = network.broadcast(message).await {
                     info!("Failed to broadcast neighbor discovery: {}", e);
                 }
+
+                let rng = node_state.read().await.rng.clone();
+                sleep(interval + jitter.mul_f64(rng.next_f64())).await;
             }
         });
     }
+
+    /// Signed discovery message advertising a sample of this node's neighbors
+    async fn discovery_message(
+        node_state: &SharedNodeState,
+        network: &NetworkManager,
+        max_advertised: usize,
+    ) -> Message {
+        let (node_id, neighbors, key_pair) = {
+            let state = node_state.read().await;
+            let neighbors = sample_neighbors(state.get_neighbors(), max_advertised, state.rng.as_ref());
+            (state.id, neighbors, state.key_pair.clone())
+        };
+
+        Message::NeighborDiscovery {
+            node_id,
+            neighbors,
+            tcp_port: network.tcp_port(),
+            signature: None,
+        }.sign(&key_pair)
+    }
 }
 
+/// Pick `max` of `neighbors` uniformly at random (all of them when there
+/// are no more than `max`)
+pub fn sample_neighbors(mut neighbors: Vec<NodeId>, max: usize, rng: &dyn RngSource) -> Vec<NodeId> {
+    if neighbors.len() <= max {
+        return neighbors;
+    }
+
+    // Partial Fisher-Yates: the first `max` slots end up a uniform sample
+    for i in 0..max {
+        let j = i + rng.gen_index(neighbors.len() - i);
+        neighbors.swap(i, j);
+    }
+    neighbors.truncate(max);
+    neighbors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::node_state::NodeState;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    #[tokio::test]
+    async fn test_discovery_advertises_bounded_varying_sample() {
+        let mut state = NodeState::new(1);
+        for neighbor in 2..=101 {
+            state.add_neighbor(neighbor);
+        }
+        let node_state = Arc::new(RwLock::new(state));
+        let network = NetworkManager::new("239.255.0.1:5000".parse().unwrap(), 0, node_state.clone())
+            .await
+            .unwrap();
+
+        let mut samples = Vec::new();
+        for _ in 0..5 {
+            let message = NeighborDiscovery::discovery_message(&node_state, &network, 10).await;
+            let Message::NeighborDiscovery { mut neighbors, .. } = message else {
+                panic!("Wrong message type");
+            };
+            assert_eq!(neighbors.len(), 10);
+            neighbors.sort_unstable();
+            neighbors.dedup();
+            assert_eq!(neighbors.len(), 10);
+            assert!(neighbors.iter().all(|n| (2..=101).contains(n)));
+            samples.push(neighbors);
+        }
+
+        assert!(samples.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn test_small_neighbor_sets_sent_whole() {
+        let rng = crate::utils::random::SeededRngSource::new(1);
+        assert_eq!(sample_neighbors(vec![3, 4, 5], 16, &rng), vec![3, 4, 5]);
+    }
+}
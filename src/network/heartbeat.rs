@@ -0,0 +1,107 @@
+use crate::core::node_state::SharedNodeState;
+use crate::network::message::{HeartbeatEcho, Message};
+use crate::network::transport::Transport;
+use crate::utils::random::RngSource;
+use crate::utils::timing::current_timestamp_ms;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
+use tracing::debug;
+
+/// Default interval between heartbeats
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default jitter, as a fraction of the heartbeat interval
+pub const DEFAULT_HEARTBEAT_JITTER: f64 = 0.1;
+
+/// Broadcast a signed heartbeat over `transport` straight away and then
+/// every `period` ± `jitter` × `period`, until the transport stops
+/// accepting messages
+///
+/// The jitter is drawn from the node's RNG for each gap, so nodes started
+/// together drift apart instead of heartbeating in lockstep. Each heartbeat
+/// echoes those heard from neighbors since the last, so they can time
+/// their links to this node.
+pub fn spawn_heartbeats(
+    transport: Arc<dyn Transport>,
+    node_state: SharedNodeState,
+    period: Duration,
+    jitter: f64,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let sent_ms = current_timestamp_ms();
+            let (node_id, key_pair, now, echoes, rng) = {
+                let mut state = node_state.write().await;
+                let echoes = state.take_heartbeat_echoes(sent_ms)
+                    .into_iter()
+                    .map(|(node_id, sent_ms, held_ms)| HeartbeatEcho { node_id, sent_ms, held_ms })
+                    .collect();
+                (state.id, state.key_pair.clone(), state.clock.now(), echoes, state.rng.clone())
+            };
+
+            let heartbeat = Message::Heartbeat {
+                node_id,
+                timestamp: now,
+                sent_ms,
+                echoes,
+                signature: None,
+            }.sign(&key_pair);
+
+            if let Err(e) = transport.broadcast(heartbeat).await {
+                debug!("Stopping heartbeats: {}", e);
+                break;
+            }
+
+            sleep(jittered(period, jitter, rng.as_ref())).await;
+        }
+    })
+}
+
+/// `period` shifted by a uniformly random offset of up to ± `jitter` ×
+/// `period` (`jitter` is clamped to [0.0, 1.0])
+pub fn jittered(period: Duration, jitter: f64, rng: &dyn RngSource) -> Duration {
+    let jitter = if jitter.is_nan() { 0.0 } else { jitter.clamp(0.0, 1.0) };
+    period.mul_f64(1.0 + jitter * (2.0 * rng.next_f64() - 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::node_state::NodeState;
+    use crate::network::memory::InMemoryNetwork;
+    use tokio::sync::RwLock;
+
+    #[tokio::test]
+    async fn test_short_interval_emits_several_heartbeats() {
+        let network = InMemoryNetwork::new();
+        let sender_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let listener_state = Arc::new(RwLock::new(NodeState::new(2)));
+
+        let sender = network.transport(1, sender_state.clone());
+        network.transport(2, listener_state.clone()).start().await.unwrap();
+
+        let heartbeats = spawn_heartbeats(Arc::new(sender), sender_state, Duration::from_millis(20), 0.0);
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        heartbeats.abort();
+
+        let state = listener_state.read().await;
+        assert!(state.stats.messages_received >= 3, "only {} heartbeats", state.stats.messages_received);
+        assert!(state.last_seen.contains_key(&1));
+    }
+
+    #[test]
+    fn test_gaps_vary_within_jitter_bounds() {
+        use crate::utils::random::SeededRngSource;
+
+        let rng = SeededRngSource::new(7);
+        let period = Duration::from_millis(1000);
+        let gaps: Vec<Duration> = (0..20).map(|_| jittered(period, 0.2, &rng)).collect();
+
+        assert!(gaps.iter().all(|gap| (800..=1200).contains(&gap.as_millis())), "{:?}", gaps);
+        assert!(gaps.iter().any(|gap| *gap < period) && gaps.iter().any(|gap| *gap > period));
+
+        // Without jitter every gap is the period itself
+        assert!((0..5).all(|_| jittered(period, 0.0, &rng) == period));
+    }
+}
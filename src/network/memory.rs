@@ -0,0 +1,260 @@
+use crate::core::node_state::SharedNodeState;
+use crate::core::pheromone::Pheromone;
+use crate::core::types::NodeId;
+use crate::network::message::Message;
+use crate::network::apply::apply_message;
+use crate::network::transport::Transport;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::error;
+
+type Inboxes = Arc<Mutex<HashMap<NodeId, mpsc::UnboundedSender<Message>>>>;
+
+/// In-process hub routing messages between `InMemoryTransport`s, for
+/// deterministic multi-node tests without real sockets
+#[derive(Clone, Default)]
+pub struct InMemoryNetwork {
+    inboxes: Inboxes,
+}
+
+impl InMemoryNetwork {
+    /// Create an empty network
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach a node to the network, returning its transport
+    pub fn transport(&self, node_id: NodeId, node_state: SharedNodeState) -> InMemoryTransport {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.inboxes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(node_id, tx);
+
+        InMemoryTransport {
+            node_id,
+            node_state,
+            inboxes: self.inboxes.clone(),
+            receiver: Arc::new(Mutex::new(Some(rx))),
+        }
+    }
+}
+
+/// Transport delivering messages to the other nodes of an `InMemoryNetwork`
+#[derive(Clone)]
+pub struct InMemoryTransport {
+    node_id: NodeId,
+    node_state: SharedNodeState,
+    inboxes: Inboxes,
+    receiver: Arc<Mutex<Option<mpsc::UnboundedReceiver<Message>>>>,
+}
+
+#[async_trait]
+impl Transport for InMemoryTransport {
+    async fn start(&self) -> Result<(), String> {
+        let mut receiver = self.receiver
+            .lock()
+            .map_err(|_| "Transport receiver lock poisoned".to_string())?
+            .take()
+            .ok_or_else(|| "Transport already started".to_string())?;
+        let node_state = self.node_state.clone();
+        let transport = self.clone();
+
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                match apply_message(&message, &node_state).await {
+                    Ok(outcome) => {
+                        if let Some(reply) = outcome.into_reply() {
+                            let _ = transport.broadcast(reply).await;
+                        }
+                    }
+                    Err(e) => error!("Error handling message: {}", e),
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    async fn broadcast(&self, message: Message) -> Result<(), String> {
+        self.node_state.write().await.stats.record_sent(message.kind());
+
+        let inboxes = self.inboxes.lock().unwrap_or_else(|e| e.into_inner());
+        for (node_id, inbox) in inboxes.iter() {
+            if *node_id != self.node_id {
+                // A node whose receiver has gone away has simply left
+                let _ = inbox.send(message.clone());
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_pheromone(&self, pheromone: Pheromone) -> Result<(), String> {
+        self.broadcast(Message::PheromoneBroadcast {
+            pheromone,
+            sender: self.node_id,
+        }).await
+    }
+}
+
+/// Transport that never touches the network: `start` does nothing and
+/// every outbound message is kept in an outbox instead of being sent
+///
+/// Backs `ConsensusEngine::dry_run`, so a node's decision logic can be
+/// driven step by step against injected pheromones.
+#[derive(Clone, Default)]
+pub struct NoopTransport {
+    node_id: NodeId,
+    outbox: Arc<Mutex<Vec<Message>>>,
+}
+
+impl NoopTransport {
+    /// Create a transport for `node_id` with an empty outbox
+    pub fn new(node_id: NodeId) -> Self {
+        Self {
+            node_id,
+            outbox: Arc::default(),
+        }
+    }
+
+    /// Take every message "sent" so far
+    pub fn drain(&self) -> Vec<Message> {
+        std::mem::take(&mut *self.outbox.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+#[async_trait]
+impl Transport for NoopTransport {
+    async fn start(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn broadcast(&self, message: Message) -> Result<(), String> {
+        self.outbox.lock().unwrap_or_else(|e| e.into_inner()).push(message);
+        Ok(())
+    }
+
+    async fn send_pheromone(&self, pheromone: Pheromone) -> Result<(), String> {
+        self.broadcast(Message::PheromoneBroadcast {
+            pheromone,
+            sender: self.node_id,
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::node_state::NodeState;
+    use crate::core::types::{ConsensusValue, DEFAULT_ROUND};
+    use tokio::sync::RwLock;
+    use tokio::time::{sleep, Duration};
+
+    #[tokio::test]
+    async fn test_broadcast_reaches_other_nodes_only() {
+        let network = InMemoryNetwork::new();
+        let mut states = Vec::new();
+        let mut transports = Vec::new();
+        for node_id in 1..=3 {
+            let state = Arc::new(RwLock::new(NodeState::new(node_id)));
+            let transport = network.transport(node_id, state.clone());
+            transport.start().await.unwrap();
+            states.push(state);
+            transports.push(transport);
+        }
+
+        let value = ConsensusValue::from_string("hello");
+        transports[0]
+            .broadcast(Message::ConsensusAnnouncement { node_id: 1, round: DEFAULT_ROUND, value, signature: None })
+            .await
+            .unwrap();
+
+        for _ in 0..50 {
+            if states[1].read().await.last_seen.contains_key(&1)
+                && states[2].read().await.last_seen.contains_key(&1)
+            {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(states[1].read().await.last_seen.contains_key(&1));
+        assert!(states[2].read().await.last_seen.contains_key(&1));
+        assert!(!states[0].read().await.last_seen.contains_key(&1));
+    }
+
+    #[tokio::test]
+    async fn test_traffic_counted_by_kind() {
+        let network = InMemoryNetwork::new();
+        let sender = Arc::new(RwLock::new(NodeState::new(1)));
+        let receiver = Arc::new(RwLock::new(NodeState::new(2)));
+        let transport = network.transport(1, sender.clone());
+        network.transport(2, receiver.clone()).start().await.unwrap();
+
+        let heartbeat = Message::Heartbeat { node_id: 1, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None };
+        let value = ConsensusValue::from_string("counted");
+        transport.broadcast(heartbeat.clone()).await.unwrap();
+        transport.broadcast(heartbeat).await.unwrap();
+        transport
+            .broadcast(Message::ConsensusAnnouncement { node_id: 1, round: DEFAULT_ROUND, value, signature: None })
+            .await
+            .unwrap();
+
+        for _ in 0..50 {
+            if receiver.read().await.stats.messages_received == 3 {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        let expected = [("ConsensusAnnouncement".to_string(), 1), ("Heartbeat".to_string(), 2)].into();
+        assert_eq!(sender.read().await.stats.sent_by_kind, expected);
+        let stats = sender.read().await.stats.clone();
+        assert_eq!(stats.sent_by_kind.values().sum::<u64>(), stats.messages_sent);
+        assert_eq!(receiver.read().await.stats.received_by_kind, expected);
+        assert!(sender.read().await.stats.received_by_kind.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rejected_announcement_broadcasts_dissent() {
+        use crate::core::config::DissentPolicy;
+        use crate::core::events::NodeEvent;
+        use crate::core::types::ValueKind;
+
+        let network = InMemoryNetwork::new();
+        let announcer = Arc::new(RwLock::new(NodeState::new(1)));
+        let mut strict = NodeState::new(2);
+        strict.config.value_kind = Some(ValueKind::Number);
+        strict.config.dissent_policy = DissentPolicy::Broadcast;
+        let strict = Arc::new(RwLock::new(strict));
+
+        let announcer_transport = network.transport(1, announcer.clone());
+        announcer_transport.start().await.unwrap();
+        network.transport(2, strict).start().await.unwrap();
+
+        let value = ConsensusValue::from_string("not a number");
+        announcer_transport
+            .broadcast(Message::ConsensusAnnouncement { node_id: 1, round: DEFAULT_ROUND, value: value.clone(), signature: None })
+            .await
+            .unwrap();
+
+        let mut events = Vec::new();
+        for _ in 0..50 {
+            events.extend(
+                announcer.write().await
+                    .drain_events()
+                    .into_iter()
+                    .filter(|event| matches!(event, NodeEvent::DissentReceived { .. })),
+            );
+            if !events.is_empty() {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(matches!(
+            events.as_slice(),
+            [NodeEvent::DissentReceived { node_id: 2, value: disputed, .. }] if *disputed == value
+        ));
+    }
+}
@@ -1,6 +1,70 @@
-use crate::core::types::{NodeId, ConsensusValue};
+use crate::core::types::{NodeId, ConsensusValue, RoundId, DEFAULT_ROUND};
 use crate::core::pheromone::Pheromone;
+use crate::crypto::registry::KeyRegistry;
+use crate::crypto::signing::{verify_signature, KeyPairWrapper, Signature};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use bincode::Options;
 use serde::{Serialize, Deserialize};
+use std::io::{Read, Write};
+
+/// Header byte marking an uncompressed payload in `Message::encode` output
+pub const HEADER_UNCOMPRESSED: u8 = 0xFE;
+
+/// Header byte marking a zlib-compressed payload in `Message::encode` output
+pub const HEADER_COMPRESSED: u8 = 0xFF;
+
+/// Header byte marking a topic-tagged datagram: a one-byte topic length
+/// and the UTF-8 topic follow, then the message as `encode` produced it
+pub const HEADER_TOPIC: u8 = 0xFD;
+
+/// Longest topic, in bytes, a datagram can be tagged with
+pub const MAX_TOPIC_LEN: usize = u8::MAX as usize;
+
+/// Serialized size above which messages are compressed by default
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 1024;
+
+/// Largest payload a compressed message may inflate to
+pub const MAX_DECOMPRESSED_SIZE: usize = 1 << 20;
+
+/// Most pheromones a single decoded message may carry
+pub const MAX_MESSAGE_PHEROMONES: usize = 4096;
+
+/// Most neighbors a `NeighborDiscovery` may advertise
+pub const MAX_ADVERTISED_NEIGHBORS: usize = 4096;
+
+/// Largest payload that fits in a single UDP datagram
+pub const MAX_DATAGRAM_SIZE: usize = 65507;
+
+/// Wire format for messages
+///
+/// A `PheromoneBroadcast` carrying a real Ed25519 signature is about 510
+/// bytes as JSON (byte arrays are written as decimal lists) and 140 bytes
+/// as bincode, roughly a 3.5x reduction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SerializationFormat {
+    /// Human-readable JSON (default)
+    #[default]
+    Json,
+
+    /// Compact binary encoding
+    Bincode,
+}
+
+impl SerializationFormat {
+    /// Guess the format of an encoded message
+    ///
+    /// JSON messages are objects and start with `{`, whereas bincode starts
+    /// with a little-endian variant index, whose first byte is never `{`
+    /// (nor any header byte).
+    pub fn detect(data: &[u8]) -> Self {
+        match data.first() {
+            Some(b'{') => SerializationFormat::Json,
+            _ => SerializationFormat::Bincode,
+        }
+    }
+}
 
 /// Message types in the network
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +74,12 @@ pub enum Message {
         pheromone: Pheromone,
         sender: NodeId,
     },
+
+    /// Several pheromones coalesced into one datagram
+    PheromoneBatch {
+        pheromones: Vec<Pheromone>,
+        sender: NodeId,
+    },
     
     /// Ant agent movement
     AntMovement {
@@ -17,52 +87,517 @@ pub enum Message {
         from_node: NodeId,
         to_node: NodeId,
         carried_pheromone: Option<Pheromone>,
+        /// Further nodes the ant may be relayed to; the receiver adopts
+        /// the ant only while this is above 0
+        #[serde(default)]
+        ttl: u8,
+        /// Pheromones the ant carries besides `carried_pheromone`, kept
+        /// apart so receivers expecting a single pheromone still read it
+        #[serde(default)]
+        extra_pheromones: Vec<Pheromone>,
     },
     
     /// Neighbor discovery
     NeighborDiscovery {
         node_id: NodeId,
         neighbors: Vec<NodeId>,
+        /// Port of the sender's TCP listener, if it accepts unicast
+        #[serde(default)]
+        tcp_port: Option<u16>,
+        #[serde(default)]
+        signature: Option<Signature>,
     },
     
     /// Consensus announcement
     ConsensusAnnouncement {
         node_id: NodeId,
+        #[serde(default)]
+        round: RoundId,
         value: ConsensusValue,
+        #[serde(default)]
+        signature: Option<Signature>,
     },
     
+    /// Pheromones a node holds, sent to bring a joining node up to date
+    ConsensusResponse {
+        node_id: NodeId,
+        pheromones: Vec<Pheromone>,
+    },
+
+    /// Objection to a consensus announcement for a value the sender rejects
+    Dissent {
+        node_id: NodeId,
+        value: ConsensusValue,
+        reason: String,
+        #[serde(default)]
+        signature: Option<Signature>,
+    },
+
     /// Heartbeat message
     Heartbeat {
         node_id: NodeId,
         timestamp: u64,
+        /// Sender's clock in milliseconds when sent, echoed back by
+        /// neighbors so the sender can time the round trip
+        #[serde(default)]
+        sent_ms: u64,
+        /// The latest heartbeat heard from each neighbor since the last one
+        #[serde(default)]
+        echoes: Vec<HeartbeatEcho>,
+        #[serde(default)]
+        signature: Option<Signature>,
+    },
+
+    /// A node retracting its support for a value it proposed
+    Withdraw {
+        node_id: NodeId,
+        #[serde(default)]
+        round: RoundId,
+        value: ConsensusValue,
+        timestamp: u64,
+        #[serde(default)]
+        signature: Option<Signature>,
+    },
+
+    /// A node leaving the network on graceful shutdown
+    Leave {
+        node_id: NodeId,
+        /// When the node left, covered by the signature so a captured
+        /// farewell goes stale
+        #[serde(default)]
+        timestamp: u64,
+        #[serde(default)]
+        signature: Option<Signature>,
     },
 }
 
+/// A neighbor's heartbeat echoed back to it, from which it measures the
+/// round trip to the echoing node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeartbeatEcho {
+    /// Node whose heartbeat is echoed
+    pub node_id: NodeId,
+
+    /// The `sent_ms` that heartbeat carried
+    pub sent_ms: u64,
+
+    /// Milliseconds between receiving that heartbeat and sending this one,
+    /// which the round trip excludes
+    pub held_ms: u64,
+}
+
 impl Message {
     /// Serialize message to bytes
     pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
-        serde_json::to_vec(self)
-            .map_err(|e| format!("Serialization error: {}", e))
+        self.to_bytes_with(SerializationFormat::Json)
     }
 
-    /// Deserialize message from bytes
+    /// Deserialize message from bytes, transparently handling bincode and
+    /// compressed payloads as produced by `encode`
     pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
-        serde_json::from_slice(data)
-            .map_err(|e| format!("Deserialization error: {}", e))
+        Self::from_bytes_auto(data)
+    }
+
+    /// Serialize message to bytes in the given format
+    pub fn to_bytes_with(&self, format: SerializationFormat) -> Result<Vec<u8>, String> {
+        match format {
+            SerializationFormat::Json => serde_json::to_vec(self)
+                .map_err(|e| format!("Serialization error: {}", e)),
+            SerializationFormat::Bincode => bincode::serialize(self)
+                .map_err(|e| format!("Serialization error: {}", e)),
+        }
+    }
+
+    /// Deserialize message from bytes in the given format
+    ///
+    /// Never panics on malformed input: bincode length prefixes can't claim
+    /// more than `MAX_DECOMPRESSED_SIZE` bytes, and a message that decodes
+    /// but fails `check_bounds` is an error like any other.
+    pub fn from_bytes_with(format: SerializationFormat, data: &[u8]) -> Result<Self, String> {
+        let message: Self = match format {
+            SerializationFormat::Json => serde_json::from_slice(data)
+                .map_err(|e| format!("Deserialization error: {}", e))?,
+            SerializationFormat::Bincode => bincode::options()
+                .with_fixint_encoding()
+                .allow_trailing_bytes()
+                .with_limit(MAX_DECOMPRESSED_SIZE as u64)
+                .deserialize(data)
+                .map_err(|e| format!("Deserialization error: {}", e))?,
+        };
+        message.check_bounds()?;
+        Ok(message)
+    }
+
+    /// Reject decoded messages that would leave degenerate state behind:
+    /// pheromones with a non-finite or negative intensity, or more
+    /// pheromones or advertised neighbors than the caps allow
+    pub fn check_bounds(&self) -> Result<(), String> {
+        let pheromones: Vec<&Pheromone> = match self {
+            Message::PheromoneBroadcast { pheromone, .. } => vec![pheromone],
+            Message::PheromoneBatch { pheromones, .. } => pheromones.iter().collect(),
+            Message::AntMovement { carried_pheromone, extra_pheromones, .. } => {
+                carried_pheromone.iter().chain(extra_pheromones).collect()
+            }
+            Message::ConsensusResponse { pheromones, .. } => pheromones.iter().collect(),
+            Message::NeighborDiscovery { neighbors, .. } => {
+                if neighbors.len() > MAX_ADVERTISED_NEIGHBORS {
+                    return Err(format!("Too many advertised neighbors: {}", neighbors.len()));
+                }
+                Vec::new()
+            }
+            Message::Heartbeat { echoes, .. } => {
+                if echoes.len() > MAX_ADVERTISED_NEIGHBORS {
+                    return Err(format!("Too many heartbeat echoes: {}", echoes.len()));
+                }
+                Vec::new()
+            }
+            _ => Vec::new(),
+        };
+
+        if pheromones.len() > MAX_MESSAGE_PHEROMONES {
+            return Err(format!("Too many pheromones: {}", pheromones.len()));
+        }
+        if let Some(pheromone) = pheromones.iter().find(|p| !p.has_valid_intensity()) {
+            return Err(format!("Invalid pheromone intensity: {}", pheromone.intensity));
+        }
+        Ok(())
+    }
+
+    /// Deserialize message from bytes, detecting the format and any
+    /// compression header; a topic tag is skipped
+    pub fn from_bytes_auto(data: &[u8]) -> Result<Self, String> {
+        let (_, data) = split_topic(data)?;
+        match data.split_first() {
+            Some((&HEADER_COMPRESSED, payload)) => {
+                let payload = decompress(payload)?;
+                Self::from_bytes_with(SerializationFormat::detect(&payload), &payload)
+            }
+            Some((&HEADER_UNCOMPRESSED, payload)) => {
+                Self::from_bytes_with(SerializationFormat::detect(payload), payload)
+            }
+            _ => Self::from_bytes_with(SerializationFormat::detect(data), data),
+        }
+    }
+
+    /// Serialize for the wire, optionally compressing
+    ///
+    /// With `compress_above` set, the payload is prefixed with a one-byte
+    /// header and zlib-compressed only when its serialized size exceeds the
+    /// threshold, so small messages don't pay the compression overhead.
+    pub fn encode(
+        &self,
+        format: SerializationFormat,
+        compress_above: Option<usize>,
+    ) -> Result<Vec<u8>, String> {
+        let bytes = self.to_bytes_with(format)?;
+
+        let Some(threshold) = compress_above else {
+            return Ok(bytes);
+        };
+
+        if bytes.len() <= threshold {
+            let mut framed = Vec::with_capacity(bytes.len() + 1);
+            framed.push(HEADER_UNCOMPRESSED);
+            framed.extend_from_slice(&bytes);
+            return Ok(framed);
+        }
+
+        let mut encoder = ZlibEncoder::new(vec![HEADER_COMPRESSED], Compression::default());
+        encoder.write_all(&bytes)
+            .map_err(|e| format!("Compression error: {}", e))?;
+        encoder.finish()
+            .map_err(|e| format!("Compression error: {}", e))
+    }
+
+    /// Encode as `encode` does, refusing a message too large for one UDP
+    /// datagram
+    ///
+    /// Oversized pheromone sets belong in `pheromone_batches`; other
+    /// oversized messages must go over TCP.
+    pub fn encode_datagram(
+        &self,
+        format: SerializationFormat,
+        compress_above: Option<usize>,
+    ) -> Result<Vec<u8>, String> {
+        self.encode_datagram_on(None, format, compress_above)
+    }
+
+    /// Encode as `encode_datagram` does, tagged with `topic` when given so
+    /// receivers not subscribed to it drop the datagram
+    pub fn encode_datagram_on(
+        &self,
+        topic: Option<&str>,
+        format: SerializationFormat,
+        compress_above: Option<usize>,
+    ) -> Result<Vec<u8>, String> {
+        let mut bytes = self.encode(format, compress_above)?;
+        if let Some(topic) = topic {
+            bytes = tag_topic(topic, &bytes)?;
+        }
+        if bytes.len() > MAX_DATAGRAM_SIZE {
+            return Err(format!(
+                "{} message is {} bytes, over the {} byte UDP payload limit; batch pheromones with send_pheromone_batch or deliver it over TCP with send_to",
+                self.kind(), bytes.len(), MAX_DATAGRAM_SIZE
+            ));
+        }
+        Ok(bytes)
+    }
+
+    /// Coalesce pheromones into as few `PheromoneBatch` messages as fit
+    /// within `max_size` bytes each once encoded
+    ///
+    /// Batches that encode too large are halved until they fit; a single
+    /// pheromone that alone exceeds `max_size` is an error.
+    pub fn pheromone_batches(
+        pheromones: Vec<Pheromone>,
+        sender: NodeId,
+        format: SerializationFormat,
+        compress_above: Option<usize>,
+        max_size: usize,
+    ) -> Result<Vec<Message>, String> {
+        let mut batches = Vec::new();
+        let mut pending = vec![pheromones];
+
+        while let Some(pheromones) = pending.pop() {
+            if pheromones.is_empty() {
+                continue;
+            }
+
+            let batch = Message::PheromoneBatch { pheromones, sender };
+            let size = batch.encode(format, compress_above)?.len();
+            match batch {
+                batch if size <= max_size => batches.push(batch),
+                Message::PheromoneBatch { mut pheromones, .. } if pheromones.len() > 1 => {
+                    // Push the second half first so batches keep the input order
+                    let second = pheromones.split_off(pheromones.len() / 2);
+                    pending.push(second);
+                    pending.push(pheromones);
+                }
+                _ => return Err(format!("Pheromone of {} bytes exceeds the {} byte limit", size, max_size)),
+            }
+        }
+
+        Ok(batches)
+    }
+
+    /// Sign the message with the sender's key
+    ///
+    /// Only `NeighborDiscovery`, `ConsensusAnnouncement`, `Dissent`,
+    /// `Withdraw`, `Heartbeat` and `Leave` carry a message signature; other variants are returned unchanged
+    /// since the pheromones they carry are signed individually.
+    pub fn sign(mut self, key_pair: &KeyPairWrapper) -> Self {
+        if let Some(bytes) = self.signing_bytes() {
+            let signed = Some(key_pair.sign(&bytes));
+            match &mut self {
+                Message::NeighborDiscovery { signature, .. }
+                | Message::ConsensusAnnouncement { signature, .. }
+                | Message::Dissent { signature, .. }
+                | Message::Withdraw { signature, .. }
+                | Message::Heartbeat { signature, .. }
+                | Message::Leave { signature, .. } => *signature = signed,
+                _ => {}
+            }
+        }
+        self
+    }
+
+    /// Check the message signature against the sender's registered key
+    ///
+    /// Variants without a message signature are always authentic; signed
+    /// variants fail when the signature is missing, invalid, or the sender
+    /// has no registered key.
+    pub fn is_authenticated(&self, registry: &KeyRegistry) -> bool {
+        let Some(bytes) = self.signing_bytes() else {
+            return true;
+        };
+
+        let signature = match self {
+            Message::NeighborDiscovery { signature, .. }
+            | Message::ConsensusAnnouncement { signature, .. }
+            | Message::Dissent { signature, .. }
+            | Message::Withdraw { signature, .. }
+            | Message::Heartbeat { signature, .. }
+            | Message::Leave { signature, .. } => signature.as_ref(),
+            _ => None,
+        };
+
+        match (signature, self.sender().and_then(|sender| registry.get(sender))) {
+            (Some(signature), Some(public_key)) => {
+                verify_signature(&bytes, signature, public_key).unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the message carries a message signature that verifies
+    /// against its sender's registered key; variants without one never do
+    pub fn is_signed_by_sender(&self, registry: &KeyRegistry) -> bool {
+        self.signing_bytes().is_some() && self.is_authenticated(registry)
+    }
+
+    /// Canonical bytes covered by the message signature, for variants that
+    /// carry one
+    fn signing_bytes(&self) -> Option<Vec<u8>> {
+        let mut bytes = Vec::new();
+        match self {
+            Message::NeighborDiscovery { node_id, neighbors, tcp_port, .. } => {
+                bytes.push(1);
+                bytes.extend_from_slice(&node_id.to_be_bytes());
+                bytes.extend_from_slice(&(neighbors.len() as u32).to_be_bytes());
+                for neighbor in neighbors {
+                    bytes.extend_from_slice(&neighbor.to_be_bytes());
+                }
+                if let Some(port) = tcp_port {
+                    bytes.extend_from_slice(&port.to_be_bytes());
+                }
+            }
+            Message::ConsensusAnnouncement { node_id, round, value, .. } => {
+                bytes.push(2);
+                bytes.extend_from_slice(&node_id.to_be_bytes());
+                bytes.extend_from_slice(&value.hash);
+                bytes.push(value.algo.id());
+                bytes.push(value.kind.id());
+                if *round != DEFAULT_ROUND {
+                    bytes.extend_from_slice(&round.to_be_bytes());
+                }
+            }
+            Message::Heartbeat { node_id, timestamp, sent_ms, echoes, .. } => {
+                bytes.push(3);
+                bytes.extend_from_slice(&node_id.to_be_bytes());
+                bytes.extend_from_slice(&timestamp.to_be_bytes());
+                bytes.extend_from_slice(&sent_ms.to_be_bytes());
+                for echo in echoes {
+                    bytes.extend_from_slice(&echo.node_id.to_be_bytes());
+                    bytes.extend_from_slice(&echo.sent_ms.to_be_bytes());
+                    bytes.extend_from_slice(&echo.held_ms.to_be_bytes());
+                }
+            }
+            Message::Dissent { node_id, value, reason, .. } => {
+                bytes.push(4);
+                bytes.extend_from_slice(&node_id.to_be_bytes());
+                bytes.extend_from_slice(&value.hash);
+                bytes.push(value.algo.id());
+                bytes.push(value.kind.id());
+                bytes.extend_from_slice(reason.as_bytes());
+            }
+            Message::Withdraw { node_id, round, value, timestamp, .. } => {
+                bytes.push(5);
+                bytes.extend_from_slice(&node_id.to_be_bytes());
+                bytes.extend_from_slice(&round.to_be_bytes());
+                bytes.extend_from_slice(&value.hash);
+                bytes.push(value.algo.id());
+                bytes.push(value.kind.id());
+                bytes.extend_from_slice(&timestamp.to_be_bytes());
+            }
+            Message::Leave { node_id, timestamp, .. } => {
+                bytes.push(6);
+                bytes.extend_from_slice(&node_id.to_be_bytes());
+                bytes.extend_from_slice(&timestamp.to_be_bytes());
+            }
+            _ => return None,
+        }
+        Some(bytes)
     }
 
     /// Get the sender node ID
     pub fn sender(&self) -> Option<NodeId> {
         match self {
             Message::PheromoneBroadcast { sender, .. } => Some(*sender),
+            Message::PheromoneBatch { sender, .. } => Some(*sender),
             Message::AntMovement { from_node, .. } => Some(*from_node),
             Message::NeighborDiscovery { node_id, .. } => Some(*node_id),
             Message::ConsensusAnnouncement { node_id, .. } => Some(*node_id),
+            Message::ConsensusResponse { node_id, .. } => Some(*node_id),
+            Message::Dissent { node_id, .. } => Some(*node_id),
+            Message::Withdraw { node_id, .. } => Some(*node_id),
             Message::Heartbeat { node_id, .. } => Some(*node_id),
+            Message::Leave { node_id, .. } => Some(*node_id),
+        }
+    }
+
+    /// Consensus round the message concerns, if it is about a single one
+    pub fn round(&self) -> Option<RoundId> {
+        match self {
+            Message::PheromoneBroadcast { pheromone, .. } => Some(pheromone.round),
+            Message::AntMovement { carried_pheromone, .. } => carried_pheromone.as_ref().map(|p| p.round),
+            Message::ConsensusAnnouncement { round, .. } => Some(*round),
+            Message::Withdraw { round, .. } => Some(*round),
+            _ => None,
+        }
+    }
+
+    /// When the sender stamped the message (clock seconds), for the
+    /// variants whose signature covers a timestamp
+    pub fn timestamp(&self) -> Option<u64> {
+        match self {
+            Message::Heartbeat { timestamp, .. }
+            | Message::Withdraw { timestamp, .. }
+            | Message::Leave { timestamp, .. } => Some(*timestamp),
+            _ => None,
+        }
+    }
+
+    /// Name of the message's variant, e.g. for log fields
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Message::PheromoneBroadcast { .. } => "PheromoneBroadcast",
+            Message::PheromoneBatch { .. } => "PheromoneBatch",
+            Message::AntMovement { .. } => "AntMovement",
+            Message::NeighborDiscovery { .. } => "NeighborDiscovery",
+            Message::ConsensusAnnouncement { .. } => "ConsensusAnnouncement",
+            Message::ConsensusResponse { .. } => "ConsensusResponse",
+            Message::Dissent { .. } => "Dissent",
+            Message::Withdraw { .. } => "Withdraw",
+            Message::Heartbeat { .. } => "Heartbeat",
+            Message::Leave { .. } => "Leave",
         }
     }
 }
 
+/// Prefix encoded message `bytes` with a `HEADER_TOPIC` tag for `topic`
+pub fn tag_topic(topic: &str, bytes: &[u8]) -> Result<Vec<u8>, String> {
+    if topic.len() > MAX_TOPIC_LEN {
+        return Err(format!("Topic is {} bytes, over the {} byte limit", topic.len(), MAX_TOPIC_LEN));
+    }
+
+    let mut tagged = Vec::with_capacity(2 + topic.len() + bytes.len());
+    tagged.push(HEADER_TOPIC);
+    tagged.push(topic.len() as u8);
+    tagged.extend_from_slice(topic.as_bytes());
+    tagged.extend_from_slice(bytes);
+    Ok(tagged)
+}
+
+/// Split a datagram into its topic tag, if it has one, and the encoded
+/// message behind it
+pub fn split_topic(data: &[u8]) -> Result<(Option<&str>, &[u8]), String> {
+    let Some((&HEADER_TOPIC, rest)) = data.split_first() else {
+        return Ok((None, data));
+    };
+
+    let (&len, rest) = rest.split_first().ok_or("Truncated topic tag")?;
+    if rest.len() < len as usize {
+        return Err("Truncated topic tag".to_string());
+    }
+    let (topic, payload) = rest.split_at(len as usize);
+    let topic = std::str::from_utf8(topic).map_err(|e| format!("Invalid topic: {}", e))?;
+    Ok((Some(topic), payload))
+}
+
+/// Inflate a zlib payload, refusing to expand past `MAX_DECOMPRESSED_SIZE`
+fn decompress(payload: &[u8]) -> Result<Vec<u8>, String> {
+    let mut inflated = Vec::new();
+    ZlibDecoder::new(payload)
+        .take(MAX_DECOMPRESSED_SIZE as u64 + 1)
+        .read_to_end(&mut inflated)
+        .map_err(|e| format!("Decompression error: {}", e))?;
+
+    if inflated.len() > MAX_DECOMPRESSED_SIZE {
+        return Err("Decompressed message too large".to_string());
+    }
+    Ok(inflated)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,7 +606,7 @@ mod tests {
     #[test]
     fn test_message_serialization() {
         let value = ConsensusValue::from_string("test");
-        let private_key = vec![0u8; 32];
+        let private_key = Vec::new();
         let pheromone = Pheromone::new(value.clone(), 1, &private_key).unwrap();
         
         let message = Message::PheromoneBroadcast {
@@ -89,5 +624,176 @@ mod tests {
             _ => panic!("Wrong message type"),
         }
     }
-}
 
+    fn every_variant() -> Vec<Message> {
+        let mut node = crate::core::node_state::NodeState::new(1);
+        let value = ConsensusValue::from_string("test");
+        let pheromone = node.emit_pheromone(value.clone()).unwrap();
+
+        vec![
+            Message::PheromoneBroadcast { pheromone: pheromone.clone(), sender: 1 },
+            Message::PheromoneBatch { pheromones: vec![pheromone.clone(); 2], sender: 1 },
+            Message::AntMovement {
+                ant_id: 7,
+                from_node: 1,
+                to_node: 2,
+                carried_pheromone: Some(pheromone.clone()),
+                ttl: 3,
+                extra_pheromones: vec![pheromone.clone()],
+            },
+            Message::AntMovement { ant_id: 8, from_node: 1, to_node: 3, carried_pheromone: None, ttl: 0, extra_pheromones: vec![] },
+            Message::NeighborDiscovery { node_id: 1, neighbors: vec![2, 3], tcp_port: Some(7000), signature: None },
+            Message::ConsensusAnnouncement { node_id: 1, round: DEFAULT_ROUND, value: value.clone(), signature: Some(vec![7; 64]) },
+            Message::Dissent { node_id: 1, value: value.clone(), reason: "wrong kind".to_string(), signature: None },
+            Message::ConsensusResponse { node_id: 1, pheromones: vec![pheromone] },
+            Message::Heartbeat { node_id: 1, timestamp: 42, sent_ms: 0, echoes: vec![], signature: None },
+            Message::Withdraw { node_id: 1, round: DEFAULT_ROUND, value, timestamp: 42, signature: None },
+            Message::Leave { node_id: 1, timestamp: 42, signature: None },
+        ]
+    }
+
+    #[test]
+    fn test_bincode_round_trip_every_variant() {
+        for message in every_variant() {
+            let bytes = message.to_bytes_with(SerializationFormat::Bincode).unwrap();
+            assert_eq!(SerializationFormat::detect(&bytes), SerializationFormat::Bincode);
+
+            let decoded = Message::from_bytes_auto(&bytes).unwrap();
+            assert_eq!(decoded.to_bytes().unwrap(), message.to_bytes().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_bincode_is_smaller_than_json() {
+        let message = every_variant().remove(0);
+        let json = message.to_bytes().unwrap();
+        let bincode = message.to_bytes_with(SerializationFormat::Bincode).unwrap();
+
+        assert_eq!(SerializationFormat::detect(&json), SerializationFormat::Json);
+        assert!(bincode.len() * 3 < json.len(), "json {} vs bincode {}", json.len(), bincode.len());
+    }
+
+    #[test]
+    fn test_compressed_and_plain_messages_decode() {
+        let small = Message::Heartbeat { node_id: 1, timestamp: 42, sent_ms: 0, echoes: vec![], signature: None };
+        let large = Message::NeighborDiscovery { node_id: 1, neighbors: (0..2000).collect(), tcp_port: None, signature: None };
+
+        for format in [SerializationFormat::Json, SerializationFormat::Bincode] {
+            let plain = small.encode(format, Some(DEFAULT_COMPRESSION_THRESHOLD)).unwrap();
+            assert_eq!(plain[0], HEADER_UNCOMPRESSED);
+            assert_eq!(Message::from_bytes(&plain).unwrap().to_bytes().unwrap(), small.to_bytes().unwrap());
+
+            let packed = large.encode(format, Some(DEFAULT_COMPRESSION_THRESHOLD)).unwrap();
+            assert_eq!(packed[0], HEADER_COMPRESSED);
+            assert_eq!(Message::from_bytes(&packed).unwrap().to_bytes().unwrap(), large.to_bytes().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_large_message_shrinks() {
+        let large = Message::NeighborDiscovery { node_id: 1, neighbors: (0..2000).collect(), tcp_port: None, signature: None };
+        let raw = large.to_bytes().unwrap();
+        let packed = large.encode(SerializationFormat::Json, Some(DEFAULT_COMPRESSION_THRESHOLD)).unwrap();
+
+        assert!(packed.len() * 2 < raw.len(), "raw {} vs compressed {}", raw.len(), packed.len());
+    }
+
+    #[test]
+    fn test_decompression_bomb_rejected() {
+        let mut encoder = ZlibEncoder::new(vec![HEADER_COMPRESSED], Compression::default());
+        encoder.write_all(&vec![b' '; MAX_DECOMPRESSED_SIZE + 1]).unwrap();
+        let bomb = encoder.finish().unwrap();
+
+        assert!(Message::from_bytes(&bomb).is_err());
+    }
+
+    #[test]
+    fn test_pheromone_batches_split_to_fit() {
+        let mut node = crate::core::node_state::NodeState::new(1);
+        let pheromones: Vec<_> = (0..50)
+            .map(|i| node.emit_pheromone(ConsensusValue::from_string(&format!("value-{}", i))).unwrap())
+            .collect();
+
+        let batches = Message::pheromone_batches(
+            pheromones.clone(), 1, SerializationFormat::Bincode, None, 2000,
+        ).unwrap();
+        assert!(batches.len() > 1);
+
+        let mut rejoined = Vec::new();
+        for batch in batches {
+            assert!(batch.to_bytes_with(SerializationFormat::Bincode).unwrap().len() <= 2000);
+            let Message::PheromoneBatch { pheromones, .. } = batch else { panic!("Wrong message type") };
+            rejoined.extend(pheromones);
+        }
+        let signatures = |ps: &[Pheromone]| ps.iter().map(|p| p.signature.clone()).collect::<Vec<_>>();
+        assert_eq!(signatures(&rejoined), signatures(&pheromones));
+
+        assert!(Message::pheromone_batches(pheromones, 1, SerializationFormat::Bincode, None, 10).is_err());
+    }
+
+    #[test]
+    fn test_message_signatures() {
+        let node = crate::core::node_state::NodeState::new(1);
+        let mut registry = KeyRegistry::new();
+        registry.register(1, node.public_key());
+
+        let heartbeat = Message::Heartbeat { node_id: 1, timestamp: 42, sent_ms: 0, echoes: vec![], signature: None };
+        assert!(!heartbeat.is_authenticated(&registry));
+
+        let signed = heartbeat.sign(&node.key_pair);
+        assert!(signed.is_authenticated(&registry));
+
+        // Tampering with a signed field invalidates the signature
+        let Message::Heartbeat { signature, .. } = signed else { unreachable!() };
+        let tampered = Message::Heartbeat { node_id: 1, timestamp: 43, sent_ms: 0, echoes: vec![], signature };
+        assert!(!tampered.is_authenticated(&registry));
+    }
+
+    #[test]
+    fn test_degenerate_messages_rejected() {
+        let mut node = crate::core::node_state::NodeState::new(1);
+        let mut pheromone = node.emit_pheromone(ConsensusValue::from_string("nan")).unwrap();
+        pheromone.intensity = f64::NAN;
+
+        let broadcast = Message::PheromoneBroadcast { pheromone: pheromone.clone(), sender: 1 };
+        let bytes = broadcast.to_bytes_with(SerializationFormat::Bincode).unwrap();
+        assert!(Message::from_bytes(&bytes).unwrap_err().contains("intensity"));
+
+        // JSON has no NaN, and an overflowing literal doesn't parse
+        let json = String::from_utf8(broadcast.to_bytes().unwrap()).unwrap().replace("null", "1e999");
+        assert!(Message::from_bytes(json.as_bytes()).is_err());
+
+        let crowded = Message::NeighborDiscovery {
+            node_id: 1,
+            neighbors: (0..=MAX_ADVERTISED_NEIGHBORS as NodeId).collect(),
+            tcp_port: None,
+            signature: None,
+        };
+        assert!(Message::from_bytes(&crowded.to_bytes().unwrap()).is_err());
+
+        // A bincode length prefix claiming far more data than follows
+        let mut bytes = every_variant()[1].to_bytes_with(SerializationFormat::Bincode).unwrap();
+        bytes[4..12].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(Message::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_from_bytes_never_panics_on_garbage() {
+        use crate::utils::random::{RngSource, SeededRngSource};
+
+        let rng = SeededRngSource::new(581);
+        let valid = every_variant()
+            .into_iter()
+            .map(|message| message.to_bytes_with(SerializationFormat::Bincode).unwrap())
+            .collect::<Vec<_>>();
+
+        for round in 0..2000 {
+            let mut bytes = valid[round % valid.len()].clone();
+            for _ in 0..=rng.gen_index(8) {
+                let index = rng.gen_index(bytes.len());
+                bytes[index] = (rng.next_f64() * 256.0) as u8;
+            }
+            let _ = Message::from_bytes(&bytes);
+        }
+    }
+}
@@ -1,8 +1,25 @@
 pub mod multicast;
 pub mod message;
 pub mod discovery;
+pub mod transport;
+pub mod memory;
+pub mod seen_cache;
+pub mod tcp;
+pub mod retry;
+pub mod heartbeat;
+pub mod outbound;
+pub mod sim;
+pub mod apply;
 
 pub use multicast::NetworkManager;
-pub use message::Message;
+pub use apply::{apply_message, ApplyOutcome};
+pub use message::{HeartbeatEcho, Message, SerializationFormat, DEFAULT_COMPRESSION_THRESHOLD};
 pub use discovery::NeighborDiscovery;
+pub use transport::Transport;
+pub use memory::{InMemoryNetwork, InMemoryTransport, NoopTransport};
+pub use sim::{SimNetwork, SimTransport};
+pub use seen_cache::SeenCache;
+pub use retry::RetryPolicy;
+pub use outbound::{OutboundQueue, OverflowPolicy, DEFAULT_OUTBOUND_CAPACITY};
+pub use heartbeat::{spawn_heartbeats, DEFAULT_HEARTBEAT_INTERVAL, DEFAULT_HEARTBEAT_JITTER};
 
@@ -1,18 +1,89 @@
-use crate::core::node_state::{NodeState, SharedNodeState};
-use crate::network::message::Message;
-use std::net::SocketAddr;
-use tokio::net::UdpSocket;
-use tokio::sync::mpsc;
-use tokio::time::{interval, Duration};
-use tracing::{info, error, debug};
+use crate::core::node_state::SharedNodeState;
+use crate::core::pheromone::Pheromone;
+use crate::crypto::hash_sha256;
+use crate::network::apply::{accept_batch, apply_message, vouch_for_sender};
+use crate::network::message::{split_topic, tag_topic, Message, SerializationFormat, MAX_DATAGRAM_SIZE, MAX_TOPIC_LEN};
+use crate::network::outbound::{OutboundQueue, OverflowPolicy, DEFAULT_OUTBOUND_CAPACITY};
+use crate::core::types::NodeId;
+use crate::network::seen_cache::{SeenCache, DEFAULT_SEEN_CACHE_CAPACITY, DEFAULT_SEEN_CACHE_TTL};
+use crate::network::heartbeat::{spawn_heartbeats, DEFAULT_HEARTBEAT_INTERVAL, DEFAULT_HEARTBEAT_JITTER};
+use crate::network::retry::{send_with_retry, RetryPolicy};
+use crate::network::tcp::{read_frame, write_frame};
+use crate::network::transport::Transport;
+use async_trait::async_trait;
+use socket2::SockRef;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::sync::watch;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::Duration;
+use tracing::{info, error, debug, warn};
+
+/// How long received pheromone broadcasts are held so their signatures can
+/// be verified together
+pub const PHEROMONE_BATCH_WINDOW: Duration = Duration::from_millis(5);
+
+/// Pheromone broadcasts that trigger verification before the window ends
+pub const MAX_PHEROMONE_BATCH: usize = 64;
+
+/// Receive buffer size, one byte over the largest datagram a peer can
+/// legitimately send, so a read that fills it signals truncation
+const RECEIVE_BUFFER_SIZE: usize = MAX_DATAGRAM_SIZE + 1;
 
 /// Network manager for UDP multicast communication
+///
+/// Clones share one bounded outbound queue, so every clone can
+/// `broadcast`. Only the first call to `start` on any clone spawns the
+/// single sender task that drains the queue; when the queue is full,
+/// `broadcast` applies the configured `OverflowPolicy`. Any clone can
+/// `shutdown` the tasks `start` spawned.
+///
+/// A manager made with `with_topics` joins one group per topic and tags
+/// what it broadcasts with its first topic (`on_topic` gives a handle for
+/// another). Traffic on each topic, over multicast or TCP, is handled by
+/// the node state `with_topic_state` routed it to (the manager's own by
+/// default); datagrams and frames tagged with a topic it hasn't joined (or
+/// untagged ones) are dropped, so separate consensus domains never see
+/// each other's traffic.
+#[derive(Clone)]
 pub struct NetworkManager {
     multicast_addr: SocketAddr,
+    groups: Vec<TopicGroup>,
+    route: usize,
     local_port: u16,
     node_state: SharedNodeState,
-    sender: mpsc::Sender<Message>,
-    receiver: mpsc::Receiver<Message>,
+    outbound: Arc<OutboundQueue<(usize, Message)>>,
+    started: Arc<AtomicBool>,
+    format: SerializationFormat,
+    compress_above: Option<usize>,
+    seen_cache_capacity: usize,
+    seen_cache_ttl: Duration,
+    ipv6_interface: u32,
+    interface: Option<Ipv4Addr>,
+    tcp_port: Option<u16>,
+    retry_policy: RetryPolicy,
+    heartbeat_interval: Option<Duration>,
+    heartbeat_jitter: f64,
+    shutdown: Arc<watch::Sender<bool>>,
+    tasks: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+/// A multicast group a manager has joined, the topic its traffic is tagged
+/// with and the node state that traffic is handled by
+#[derive(Clone)]
+struct TopicGroup {
+    topic: Option<String>,
+    addr: SocketAddr,
+    state: SharedNodeState,
+}
+
+impl TopicGroup {
+    /// Whether a datagram or frame tagged `topic` belongs to this group
+    fn carries(&self, topic: Option<&str>) -> bool {
+        self.topic.as_deref() == topic
+    }
 }
 
 impl NetworkManager {
@@ -22,94 +93,290 @@ impl NetworkManager {
         local_port: u16,
         node_state: SharedNodeState,
     ) -> Result<Self, String> {
-        let (tx, rx) = mpsc::channel(1000);
-
         Ok(Self {
             multicast_addr,
+            groups: vec![TopicGroup { topic: None, addr: multicast_addr, state: node_state.clone() }],
+            route: 0,
             local_port,
             node_state,
-            sender: tx,
-            receiver: rx,
+            outbound: Arc::new(OutboundQueue::new(DEFAULT_OUTBOUND_CAPACITY, OverflowPolicy::default())),
+            started: Arc::new(AtomicBool::new(false)),
+            format: SerializationFormat::default(),
+            compress_above: None,
+            seen_cache_capacity: DEFAULT_SEEN_CACHE_CAPACITY,
+            seen_cache_ttl: DEFAULT_SEEN_CACHE_TTL,
+            ipv6_interface: 0,
+            interface: None,
+            tcp_port: None,
+            retry_policy: RetryPolicy::default(),
+            heartbeat_interval: Some(DEFAULT_HEARTBEAT_INTERVAL),
+            heartbeat_jitter: DEFAULT_HEARTBEAT_JITTER,
+            shutdown: Arc::new(watch::channel(false).0),
+            tasks: Arc::new(Mutex::new(Vec::new())),
         })
     }
 
+    /// Create a network manager subscribed to one multicast group per topic
+    ///
+    /// Broadcasts are tagged with the first topic and sent to its group.
+    /// Topics must be distinct, at most `MAX_TOPIC_LEN` bytes, and their
+    /// groups of one address family and on `local_port`, the one port the
+    /// receiver listens on.
+    pub async fn with_topics(
+        groups: Vec<(String, SocketAddr)>,
+        local_port: u16,
+        node_state: SharedNodeState,
+    ) -> Result<Self, String> {
+        let (_, primary) = groups.first().ok_or("At least one topic group is required")?;
+        for (index, (topic, addr)) in groups.iter().enumerate() {
+            if topic.len() > MAX_TOPIC_LEN {
+                return Err(format!("Topic {:?} is over the {} byte limit", topic, MAX_TOPIC_LEN));
+            }
+            if groups[..index].iter().any(|(other, _)| other == topic) {
+                return Err(format!("Topic {:?} is listed twice", topic));
+            }
+            if addr.is_ipv4() != primary.is_ipv4() {
+                return Err(format!("Group {} for topic {:?} is not in the same address family as {}", addr, topic, primary));
+            }
+            if addr.port() != local_port {
+                return Err(format!("Group {} for topic {:?} is not on the local port {}", addr, topic, local_port));
+            }
+        }
+
+        let mut manager = Self::new(*primary, local_port, node_state).await?;
+        manager.groups = groups
+            .into_iter()
+            .map(|(topic, addr)| TopicGroup { topic: Some(topic), addr, state: manager.node_state.clone() })
+            .collect();
+        Ok(manager)
+    }
+
+    /// Handle traffic on `topic` with `state` instead of this manager's
+    /// node state, so one manager can serve several consensus domains
+    pub fn with_topic_state(mut self, topic: &str, state: SharedNodeState) -> Result<Self, String> {
+        let index = self.topic_index(topic)?;
+        self.groups[index].state = state.clone();
+        if index == self.route {
+            self.node_state = state;
+        }
+        Ok(self)
+    }
+
+    /// A clone that broadcasts on `topic` (and sends unicast tagged with
+    /// it) on behalf of the node state that topic is routed to
+    ///
+    /// The clone shares this manager's queue and tasks; start only one.
+    pub fn on_topic(&self, topic: &str) -> Result<Self, String> {
+        let index = self.topic_index(topic)?;
+        let mut handle = self.clone();
+        handle.route = index;
+        handle.node_state = self.groups[index].state.clone();
+        Ok(handle)
+    }
+
+    fn topic_index(&self, topic: &str) -> Result<usize, String> {
+        self.groups
+            .iter()
+            .position(|group| group.carries(Some(topic)))
+            .ok_or_else(|| format!("Not subscribed to topic {:?}", topic))
+    }
+
+    /// Topics this manager is subscribed to, the one it broadcasts on
+    /// first; empty for a manager made with `new`
+    pub fn topics(&self) -> Vec<&str> {
+        self.groups.iter().filter_map(|group| group.topic.as_deref()).collect()
+    }
+
+    /// Topic broadcasts are tagged with, if any
+    fn topic(&self) -> Option<&str> {
+        self.groups[self.route].topic.as_deref()
+    }
+
+    /// Send messages in the given wire format; incoming messages are
+    /// decoded in whichever format they arrive in
+    pub fn with_format(mut self, format: SerializationFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Size the cache used to drop repeated datagrams: up to `capacity`
+    /// digests, each suppressing repeats for `ttl`
+    pub fn with_seen_cache(mut self, capacity: usize, ttl: Duration) -> Self {
+        self.seen_cache_capacity = capacity;
+        self.seen_cache_ttl = ttl;
+        self
+    }
+
+    /// Interface index used to join an IPv6 multicast group (0 lets the
+    /// OS choose, which link-local `ff02::` groups may not allow)
+    pub fn with_ipv6_interface(mut self, interface: u32) -> Self {
+        self.ipv6_interface = interface;
+        self
+    }
+
+    /// Local IPv4 address of the interface used to join an IPv4 group and
+    /// send to it (None lets the OS choose, which on multi-homed hosts may
+    /// be the wrong NIC)
+    pub fn with_interface(mut self, interface: Option<Ipv4Addr>) -> Self {
+        self.interface = interface;
+        self
+    }
+
+    /// Compress outgoing messages whose serialized size exceeds `threshold`
+    /// bytes (e.g. `DEFAULT_COMPRESSION_THRESHOLD`)
+    pub fn with_compression(mut self, threshold: usize) -> Self {
+        self.compress_above = Some(threshold);
+        self
+    }
+
+    /// Accept reliable unicast messages over TCP on `port`, advertised to
+    /// peers through neighbor discovery
+    pub fn with_tcp_port(mut self, port: u16) -> Self {
+        self.tcp_port = Some(port);
+        self
+    }
+
+    /// Retry transient multicast send failures according to `policy`
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Queue up to `capacity` outbound messages, applying `policy` to
+    /// broadcasts made while the queue is full
+    pub fn with_outbound_queue(mut self, capacity: usize, policy: OverflowPolicy) -> Self {
+        self.outbound = Arc::new(OutboundQueue::new(capacity, policy));
+        self
+    }
+
+    /// Send a heartbeat every `interval`, or never with `None`
+    pub fn with_heartbeat_interval(mut self, interval: Option<Duration>) -> Self {
+        self.heartbeat_interval = interval;
+        self
+    }
+
+    /// Vary each gap between heartbeats randomly by up to ± `fraction` of
+    /// the interval
+    pub fn with_heartbeat_jitter(mut self, fraction: f64) -> Self {
+        self.heartbeat_jitter = fraction;
+        self
+    }
+
+    /// Interval between heartbeats, if they are enabled
+    pub fn heartbeat_interval(&self) -> Option<Duration> {
+        self.heartbeat_interval
+    }
+
+    /// Port of the TCP listener, if unicast is enabled
+    pub fn tcp_port(&self) -> Option<u16> {
+        self.tcp_port
+    }
+
     /// Start the network manager
+    ///
+    /// Only one clone can start the manager; subsequent calls fail because
+    /// the outbound queue already has a sender task draining it. Each node
+    /// state traffic is routed to heartbeats, and says goodbye on shutdown,
+    /// on the first topic routed to it.
     pub async fn start(&self) -> Result<(), String> {
         let multicast_addr = self.multicast_addr;
+        let groups = self.groups.clone();
+        let receiver_groups = self.groups.clone();
         let local_port = self.local_port;
-        let node_state = self.node_state.clone();
-        let mut receiver = self.receiver.clone();
-        let sender = self.sender.clone();
+        let wire_format = self.format;
+        let compress_above = self.compress_above;
+        let mut seen = SeenCache::new(self.seen_cache_capacity, self.seen_cache_ttl);
+        if self.started.swap(true, Ordering::SeqCst) {
+            return Err("Network manager already started".to_string());
+        }
+
+        let ipv6_interface = self.ipv6_interface;
+        let interface = self.interface;
+        let outbound = self.outbound.clone();
+        let reply_queue = self.outbound.clone();
+        let retry_policy = self.retry_policy;
+        let members = self.members();
+
+        if let Some(port) = self.tcp_port {
+            self.listen_tcp(SocketAddr::new(Self::unspecified_addr(multicast_addr, 0).ip(), port)).await?;
+        }
+
+        let mut receiver_shutdown = self.shutdown.subscribe();
+        let mut sender_shutdown = self.shutdown.subscribe();
 
         // Spawn receiver task
-        tokio::spawn(async move {
-            let socket = match UdpSocket::bind(format!("0.0.0.0:{}", local_port)).await {
+        let receiver_task = tokio::spawn(async move {
+            let groups = receiver_groups;
+            let socket = match Self::bind_multicast(multicast_addr, local_port, interface, ipv6_interface).await {
                 Ok(s) => s,
                 Err(e) => {
-                    error!("Failed to bind socket: {}", e);
+                    error!("Failed to join multicast group {}: {}", multicast_addr, e);
                     return;
                 }
             };
-
-            // Convert to std socket for multicast operations
-            let std_socket = match socket.into_std() {
-                Ok(s) => s,
-                Err(e) => {
-                    error!("Failed to convert to std socket: {}", e);
-                    return;
+            for group in &groups[1..] {
+                if let Err(e) = Self::join_multicast(&socket, group.addr, interface, ipv6_interface) {
+                    error!("Failed to join multicast group {}: {}", group.addr, e);
                 }
-            };
-            
-            // Join multicast group
-            if let Err(e) = std_socket.join_multicast_v4(
-                multicast_addr.ip(),
-                "0.0.0.0".parse().unwrap(),
-            ) {
-                error!("Failed to join multicast group: {}", e);
-                return;
             }
 
-            // Convert back to tokio socket
-            let socket = match UdpSocket::from_std(std_socket) {
-                Ok(s) => s,
-                Err(e) => {
-                    error!("Failed to convert back to tokio socket: {}", e);
-                    return;
-                }
-            };
-
             info!("Network receiver started on port {}", local_port);
 
-            let mut buf = [0u8; 65507]; // Max UDP packet size
+            let mut buf = [0u8; RECEIVE_BUFFER_SIZE];
+            let mut pending = vec![Vec::new(); groups.len()];
+            let flush = tokio::time::sleep(PHEROMONE_BATCH_WINDOW);
+            tokio::pin!(flush);
 
             loop {
-                match socket.recv_from(&mut buf).await {
-                    Ok((size, addr)) => {
-                        debug!("Received {} bytes from {}", size, addr);
-                        
-                        match Message::from_bytes(&buf[..size]) {
-                            Ok(message) => {
-                                // Process message
-                                if let Err(e) = Self::handle_message(&message, &node_state).await {
-                                    error!("Error handling message: {}", e);
-                                }
+                let buffered: usize = pending.iter().map(Vec::len).sum();
+                tokio::select! {
+                    received = socket.recv_from(&mut buf) => match received {
+                        Ok((size, addr)) => {
+                            debug!("Received {} bytes from {}", size, addr);
+                            let reply = Self::process_datagram(&buf[..size], addr, &groups, &mut seen, &mut pending).await;
+                            let now_buffered: usize = pending.iter().map(Vec::len).sum();
+
+                            // The first buffered pheromone opens a new window
+                            if buffered == 0 && now_buffered > 0 {
+                                flush.as_mut().reset(tokio::time::Instant::now() + PHEROMONE_BATCH_WINDOW);
                             }
-                            Err(e) => {
-                                error!("Failed to deserialize message: {}", e);
+                            if now_buffered >= MAX_PHEROMONE_BATCH {
+                                Self::flush_all(&mut pending, &groups).await;
                             }
+
+                            if let Some((route, reply)) = reply {
+                                if Self::enqueue(&reply_queue, &groups[route].state, route, reply).await.is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            error!("Receive error: {}", e);
+                            tokio::time::sleep(Duration::from_millis(100)).await;
                         }
+                    },
+                    _ = &mut flush, if buffered > 0 => {
+                        Self::flush_all(&mut pending, &groups).await;
                     }
-                    Err(e) => {
-                        error!("Receive error: {}", e);
-                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    _ = receiver_shutdown.changed() => {
+                        Self::flush_all(&mut pending, &groups).await;
+                        break;
                     }
                 }
             }
+
+            // Withdraw the memberships now rather than leave routers to age them out
+            for group in groups {
+                match Self::leave_multicast(&socket, group.addr, interface, ipv6_interface) {
+                    Ok(()) => info!("Left multicast group {}", group.addr),
+                    Err(e) => warn!("Failed to leave multicast group {}: {}", group.addr, e),
+                }
+            }
         });
 
         // Spawn sender task
-        tokio::spawn(async move {
-            let socket = match UdpSocket::bind("0.0.0.0:0").await {
+        let sender_task = tokio::spawn(async move {
+            let socket = match Self::bind_sender(multicast_addr, interface).await {
                 Ok(s) => s,
                 Err(e) => {
                     error!("Failed to bind sender socket: {}", e);
@@ -119,125 +386,451 @@ impl NetworkManager {
 
             info!("Network sender started");
 
-            while let Some(message) = receiver.recv().await {
-                match message.to_bytes() {
-                    Ok(bytes) => {
-                        if let Err(e) = socket.send_to(&bytes, multicast_addr).await {
-                            error!("Failed to send message: {}", e);
-                        } else {
-                            debug!("Sent message to {}", multicast_addr);
-                        }
-                    }
-                    Err(e) => {
-                        error!("Failed to serialize message: {}", e);
-                    }
-                }
-            }
-        });
-
-        // Send periodic heartbeat
-        let node_state_clone = self.node_state.clone();
-        let sender_clone = self.sender.clone();
-        tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(5));
-            
             loop {
-                interval.tick().await;
-                
-                let node_id = {
-                    let state = node_state_clone.read().await;
-                    state.id
+                let (route, message) = tokio::select! {
+                    message = outbound.pop() => match message {
+                        Some(message) => message,
+                        None => break,
+                    },
+                    _ = sender_shutdown.changed() => break,
                 };
 
-                let heartbeat = Message::Heartbeat {
-                    node_id,
-                    timestamp: crate::utils::current_timestamp(),
-                };
+                Self::send_datagram(&socket, &message, &groups[route], wire_format, compress_above, retry_policy).await;
+            }
 
-                if sender_clone.send(heartbeat).await.is_err() {
-                    break;
-                }
+            // Say goodbye so peers drop this node without waiting for it
+            // to time out
+            for route in members {
+                let group = &groups[route];
+                let leave = {
+                    let state = group.state.read().await;
+                    Message::Leave { node_id: state.id, timestamp: state.clock.now(), signature: None }.sign(&state.key_pair)
+                };
+                Self::send_datagram(&socket, &leave, group, wire_format, compress_above, retry_policy).await;
             }
         });
 
+        self.tasks
+            .lock()
+            .map_err(|_| "Network task lock poisoned".to_string())?
+            .extend([receiver_task, sender_task]);
+
+        // Send periodic heartbeats; they stop once the sender task has gone
+        if let Some(period) = self.heartbeat_interval {
+            for route in self.members() {
+                let mut handle = self.clone();
+                handle.route = route;
+                handle.node_state = self.groups[route].state.clone();
+                let node_state = handle.node_state.clone();
+                spawn_heartbeats(Arc::new(handle), node_state, period, self.heartbeat_jitter);
+            }
+        }
+
         Ok(())
     }
 
-    /// Handle incoming message
-    async fn handle_message(
+    /// Index of the first group routed to each distinct node state
+    fn members(&self) -> Vec<usize> {
+        (0..self.groups.len())
+            .filter(|&route| {
+                let state = &self.groups[route].state;
+                !self.groups[..route].iter().any(|earlier| Arc::ptr_eq(&earlier.state, state))
+            })
+            .collect()
+    }
+
+    /// Encode and send `message` to `group`, tagged with its topic if it
+    /// has one, counting it as sent or dropped on the group's node state
+    async fn send_datagram(
+        socket: &UdpSocket,
         message: &Message,
-        node_state: &SharedNodeState,
-    ) -> Result<(), String> {
-        match message {
-            Message::PheromoneBroadcast { pheromone, sender } => {
-                let mut state = node_state.write().await;
-                
-                // Don't process our own messages
-                if sender == &state.id {
-                    return Ok(());
+        group: &TopicGroup,
+        format: SerializationFormat,
+        compress_above: Option<usize>,
+        retry_policy: RetryPolicy,
+    ) {
+        let node_state = &group.state;
+        match message.encode_datagram_on(group.topic.as_deref(), format, compress_above) {
+            Ok(bytes) => {
+                if let Err(e) = send_with_retry(socket, &bytes, group.addr, retry_policy).await {
+                    error!("Dropping message after failed send: {}", e);
+                    node_state.write().await.stats.messages_dropped += 1;
+                } else {
+                    debug!("Sent message to {}", group.addr);
+                    node_state.write().await.stats.record_sent(message.kind());
                 }
+            }
+            Err(e) => {
+                error!("Failed to serialize message: {}", e);
+                node_state.write().await.stats.messages_dropped += 1;
+            }
+        }
+    }
+
+    /// Stop the tasks spawned by `start`, leaving the multicast group
+    ///
+    /// Buffered pheromones are processed, a signed `Leave` is sent and the
+    /// group membership is dropped before this returns; queued outbound
+    /// messages are discarded and later broadcasts fail.
+    pub async fn shutdown(&self) -> Result<(), String> {
+        self.shutdown.send_replace(true);
+        self.outbound.close();
 
-                // Add sender as neighbor
-                state.add_neighbor(*sender);
-                
-                // Receive pheromone
-                state.receive_pheromone(pheromone.clone());
-                
-                debug!("Received pheromone from node {}", sender);
+        let tasks = std::mem::take(
+            &mut *self.tasks
+                .lock()
+                .map_err(|_| "Network task lock poisoned".to_string())?,
+        );
+        for task in tasks {
+            task.await.map_err(|e| format!("Network task failed: {}", e))?;
+        }
+
+        info!("Network stopped");
+        Ok(())
+    }
+
+    /// Wildcard address of the same family as `multicast_addr`
+    fn unspecified_addr(multicast_addr: SocketAddr, port: u16) -> SocketAddr {
+        match multicast_addr {
+            SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)),
+            SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, port)),
+        }
+    }
+
+    /// Bind the receive socket on the wildcard address of the group's
+    /// family and join the group (IPv4 groups on `interface`, IPv6 groups on
+    /// `ipv6_interface`)
+    async fn bind_multicast(
+        multicast_addr: SocketAddr,
+        local_port: u16,
+        interface: Option<Ipv4Addr>,
+        ipv6_interface: u32,
+    ) -> std::io::Result<UdpSocket> {
+        let socket = UdpSocket::bind(Self::unspecified_addr(multicast_addr, local_port)).await?;
+        Self::join_multicast(&socket, multicast_addr, interface, ipv6_interface)?;
+        Ok(socket)
+    }
+
+    /// Join `multicast_addr`'s group on an already bound socket
+    fn join_multicast(
+        socket: &UdpSocket,
+        multicast_addr: SocketAddr,
+        interface: Option<Ipv4Addr>,
+        ipv6_interface: u32,
+    ) -> std::io::Result<()> {
+        match multicast_addr.ip() {
+            IpAddr::V4(group) => socket.join_multicast_v4(group, interface.unwrap_or(Ipv4Addr::UNSPECIFIED)),
+            IpAddr::V6(group) => socket.join_multicast_v6(&group, ipv6_interface),
+        }
+    }
+
+    /// Leave a group joined by `bind_multicast` or `join_multicast`, on the
+    /// same interface
+    fn leave_multicast(
+        socket: &UdpSocket,
+        multicast_addr: SocketAddr,
+        interface: Option<Ipv4Addr>,
+        ipv6_interface: u32,
+    ) -> std::io::Result<()> {
+        match multicast_addr.ip() {
+            IpAddr::V4(group) => socket.leave_multicast_v4(group, interface.unwrap_or(Ipv4Addr::UNSPECIFIED)),
+            IpAddr::V6(group) => socket.leave_multicast_v6(&group, ipv6_interface),
+        }
+    }
+
+    /// Bind the send socket, routing IPv4 multicast out of `interface` when
+    /// one is given
+    async fn bind_sender(multicast_addr: SocketAddr, interface: Option<Ipv4Addr>) -> std::io::Result<UdpSocket> {
+        let socket = UdpSocket::bind(Self::unspecified_addr(multicast_addr, 0)).await?;
+
+        if let (SocketAddr::V4(_), Some(interface)) = (multicast_addr, interface) {
+            SockRef::from(&socket).set_multicast_if_v4(&interface)?;
+        }
+
+        Ok(socket)
+    }
+
+    /// Decode and handle one received datagram, dropping it if an identical
+    /// datagram was processed within the seen-cache TTL
+    ///
+    /// A datagram filling the whole receive buffer may have been cut short,
+    /// so it is counted and dropped rather than parsed, as is one whose
+    /// topic tag (or lack of one) isn't among `groups`; either is counted on
+    /// the first group's node state. Otherwise the datagram is handled by
+    /// the node state of the group its topic routes to, and pheromone
+    /// broadcasts are added to that group's `pending` entry for
+    /// `flush_pheromones` instead. Returns the reply to broadcast and the
+    /// group to send it on, if handling produced one.
+    async fn process_datagram(
+        bytes: &[u8],
+        source: SocketAddr,
+        groups: &[TopicGroup],
+        seen: &mut SeenCache,
+        pending: &mut [Vec<(Pheromone, NodeId)>],
+    ) -> Option<(usize, Message)> {
+        if bytes.len() >= RECEIVE_BUFFER_SIZE {
+            warn!(size = bytes.len(), %source, "Dropping datagram that filled the receive buffer and may be truncated");
+            groups[0].state.write().await.stats.datagrams_truncated += 1;
+            return None;
+        }
+
+        let (route, payload) = match Self::route(bytes, groups) {
+            Ok(Some(routed)) => routed,
+            Ok(None) => {
+                debug!(%source, "Dropping datagram for a topic this node hasn't joined");
+                groups[0].state.write().await.stats.datagrams_off_topic += 1;
+                return None;
             }
-            
-            Message::AntMovement { ant_id, to_node, carried_pheromone, .. } => {
-                let mut state = node_state.write().await;
-                
-                // If ant arrived at this node
-                if to_node == &state.id {
-                    if let Some(pheromone) = carried_pheromone {
-                        state.receive_pheromone(pheromone.clone());
-                        debug!("Ant {} arrived with pheromone", ant_id);
-                    }
-                }
+            Err(e) => {
+                error!("Failed to deserialize message: {}", e);
+                return None;
             }
-            
-            Message::NeighborDiscovery { node_id, neighbors } => {
-                let mut state = node_state.write().await;
-                
-                if node_id != &state.id {
-                    state.add_neighbor(*node_id);
-                    for neighbor in neighbors {
-                        state.add_neighbor(*neighbor);
+        };
+        let node_state = &groups[route].state;
+
+        if !seen.insert(hash_sha256(bytes)) {
+            debug!("Dropping duplicate datagram");
+            return None;
+        }
+
+        match Message::from_bytes_auto(payload) {
+            Ok(message) => {
+                Self::learn_peer_addr(&message, source, node_state).await;
+
+                if let Message::PheromoneBroadcast { pheromone, sender } = message {
+                    pending[route].push((pheromone, sender));
+                    return None;
+                }
+
+                // Process message
+                match apply_message(&message, node_state).await {
+                    Ok(outcome) => outcome.into_reply().map(|reply| (route, reply)),
+                    Err(e) => {
+                        error!("Error handling message: {}", e);
+                        None
                     }
                 }
             }
-            
-            Message::ConsensusAnnouncement { node_id, value } => {
-                let mut state = node_state.write().await;
-                
-                if node_id != &state.id {
-                    info!("Node {} announced consensus: {}", node_id, value);
-                    // Could trigger consensus verification
-                }
+            Err(e) => {
+                error!("Failed to deserialize message: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Index of the group whose topic tags `bytes`, and the encoded message
+    /// after the tag; `None` if no joined group carries that topic
+    fn route<'a>(bytes: &'a [u8], groups: &[TopicGroup]) -> Result<Option<(usize, &'a [u8])>, String> {
+        let (topic, payload) = split_topic(bytes)?;
+        Ok(groups.iter().position(|group| group.carries(topic)).map(|route| (route, payload)))
+    }
+
+    /// Flush every group's buffered pheromones into its node state
+    async fn flush_all(pending: &mut [Vec<(Pheromone, NodeId)>], groups: &[TopicGroup]) {
+        for (pending, group) in pending.iter_mut().zip(groups) {
+            if !pending.is_empty() {
+                Self::flush_pheromones(pending, &group.state).await;
+            }
+        }
+    }
+
+    /// Handle buffered pheromone broadcasts as `apply_message` would, but
+    /// verify their signatures in one batch
+    async fn flush_pheromones(pending: &mut Vec<(Pheromone, NodeId)>, node_state: &SharedNodeState) {
+        let mut state = node_state.write().await;
+        let mut pheromones = Vec::with_capacity(pending.len());
+
+        for (mut pheromone, sender) in pending.drain(..) {
+            // Our own broadcasts loop back through the multicast group
+            if sender == state.id {
+                continue;
+            }
+
+            state.stats.messages_received += 1;
+            state.stats.record_received("PheromoneBroadcast");
+            pheromone.received_from = Some(sender);
+            pheromones.push(pheromone);
+        }
+
+        // Only a sender whose own pheromone verified is marked seen
+        for (source, sender) in accept_batch(&mut state, pheromones) {
+            if let Some(sender) = sender {
+                vouch_for_sender(&mut state, [source], sender);
+            }
+        }
+    }
+
+    /// Record the unicast address a discovery message advertises: the
+    /// datagram's source IP with the advertised TCP port
+    async fn learn_peer_addr(message: &Message, source: SocketAddr, node_state: &SharedNodeState) {
+        let Message::NeighborDiscovery { node_id, tcp_port: Some(port), .. } = message else {
+            return;
+        };
+
+        let mut state = node_state.write().await;
+        let authentic = !state.config.authenticate_messages || message.is_authenticated(&state.key_registry);
+        if *node_id != state.id && authentic {
+            state.peer_addrs.insert(*node_id, SocketAddr::new(source.ip(), *port));
+        }
+    }
+
+    /// Listen for framed messages over TCP on `addr`, handling each like a
+    /// multicast datagram (routed, or dropped, by its topic tag); returns
+    /// the bound address
+    ///
+    /// The listener and its connections stop on `shutdown`, which releases
+    /// the port.
+    pub async fn listen_tcp(&self, addr: SocketAddr) -> Result<SocketAddr, String> {
+        if *self.shutdown.borrow() {
+            return Err("Network is shut down".to_string());
+        }
+
+        let listener = TcpListener::bind(addr).await
+            .map_err(|e| format!("Failed to bind TCP listener on {}: {}", addr, e))?;
+        let local_addr = listener.local_addr()
+            .map_err(|e| format!("Failed to read TCP listener address: {}", e))?;
+        let groups = self.groups.clone();
+        let outbound = self.outbound.clone();
+        let mut shutdown = self.shutdown.subscribe();
+
+        info!("TCP listener started on {}", local_addr);
+
+        let listener_task = tokio::spawn(async move {
+            let mut connections = JoinSet::new();
+            loop {
+                let (stream, peer) = tokio::select! {
+                    accepted = listener.accept() => match accepted {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            error!("TCP accept error: {}", e);
+                            tokio::time::sleep(Duration::from_millis(100)).await;
+                            continue;
+                        }
+                    },
+                    Some(_) = connections.join_next(), if !connections.is_empty() => continue,
+                    _ = shutdown.changed() => break,
+                };
+
+                let groups = groups.clone();
+                let outbound = outbound.clone();
+                connections.spawn(Self::serve_tcp(stream, peer, groups, outbound));
             }
-            
-            Message::Heartbeat { node_id, .. } => {
-                let mut state = node_state.write().await;
-                
-                if node_id != &state.id {
-                    state.add_neighbor(*node_id);
+
+            // Stop applying frames once the node has said goodbye
+            connections.shutdown().await;
+            info!("TCP listener on {} stopped", local_addr);
+        });
+
+        self.tasks
+            .lock()
+            .map_err(|_| "Network task lock poisoned".to_string())?
+            .push(listener_task);
+
+        Ok(local_addr)
+    }
+
+    /// Apply the framed messages a TCP peer sends until it disconnects
+    async fn serve_tcp(
+        mut stream: TcpStream,
+        peer: SocketAddr,
+        groups: Vec<TopicGroup>,
+        outbound: Arc<OutboundQueue<(usize, Message)>>,
+    ) {
+        loop {
+            let frame = match read_frame(&mut stream).await {
+                Ok(Some(frame)) => frame,
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Dropping TCP connection from {}: {}", peer, e);
+                    break;
                 }
+            };
+
+            let (route, payload) = match Self::route(&frame, &groups) {
+                Ok(Some(routed)) => routed,
+                Ok(None) => {
+                    debug!(%peer, "Dropping TCP frame for a topic this node hasn't joined");
+                    groups[0].state.write().await.stats.datagrams_off_topic += 1;
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to deserialize message from {}: {}", peer, e);
+                    continue;
+                }
+            };
+            let message = match Message::from_bytes_auto(payload) {
+                Ok(message) => message,
+                Err(e) => {
+                    error!("Failed to deserialize message from {}: {}", peer, e);
+                    continue;
+                }
+            };
+
+            let node_state = &groups[route].state;
+            match apply_message(&message, node_state).await {
+                Ok(outcome) => {
+                    if let Some(reply) = outcome.into_reply() {
+                        let _ = Self::enqueue(&outbound, node_state, route, reply).await;
+                    }
+                }
+                Err(e) => error!("Error handling message: {}", e),
             }
         }
+    }
 
+    /// Deliver a message reliably to the node listening at `node_addr`,
+    /// tagged with the topic broadcasts go out on
+    pub async fn send_to(&self, node_addr: SocketAddr, message: Message) -> Result<(), String> {
+        let mut bytes = message.encode(self.format, self.compress_above)?;
+        if let Some(topic) = self.topic() {
+            bytes = tag_topic(topic, &bytes)?;
+        }
+        let mut stream = TcpStream::connect(node_addr).await
+            .map_err(|e| format!("Failed to connect to {}: {}", node_addr, e))?;
+        write_frame(&mut stream, &bytes).await?;
+        self.node_state.write().await.stats.record_sent(message.kind());
         Ok(())
     }
 
+    /// Deliver a message reliably to a node whose address was learned from
+    /// neighbor discovery
+    pub async fn send_to_node(&self, node_id: NodeId, message: Message) -> Result<(), String> {
+        let addr = self.node_state.read().await.peer_addrs.get(&node_id).copied()
+            .ok_or_else(|| format!("No unicast address known for node {}", node_id))?;
+        self.send_to(addr, message).await
+    }
+
     /// Broadcast a message
+    ///
+    /// Fails without queueing if the encoded message won't fit in one
+    /// datagram. If the outbound queue is full, the configured
+    /// `OverflowPolicy` decides whether this waits or a message is dropped.
     pub async fn broadcast(&self, message: Message) -> Result<(), String> {
-        self.sender.send(message).await
-            .map_err(|e| format!("Failed to send message: {}", e))
+        message.encode_datagram_on(self.topic(), self.format, self.compress_above)?;
+        Self::enqueue(&self.outbound, &self.node_state, self.route, message).await
+    }
+
+    /// Queue `message` for the sender task to send on group `route`,
+    /// counting any message the overflow policy discards
+    async fn enqueue(
+        outbound: &OutboundQueue<(usize, Message)>,
+        node_state: &SharedNodeState,
+        route: usize,
+        message: Message,
+    ) -> Result<(), String> {
+        let dropped = outbound.push((route, message))
+            .await
+            .map_err(|e| format!("Failed to send message: {}", e))?;
+
+        if dropped.is_some() {
+            debug!("Outbound queue full; dropped a message ({:?})", outbound.policy());
+            node_state.write().await.stats.outbound_dropped += 1;
+        }
+        Ok(())
     }
 
     /// Send a pheromone
-    pub async fn send_pheromone(&self, pheromone: crate::core::pheromone::Pheromone) -> Result<(), String> {
+    pub async fn send_pheromone(&self, pheromone: Pheromone) -> Result<(), String> {
         let node_id = {
             let state = self.node_state.read().await;
             state.id
@@ -250,17 +843,630 @@ impl NetworkManager {
 
         self.broadcast(message).await
     }
-}
 
-impl Clone for NetworkManager {
-    fn clone(&self) -> Self {
-        Self {
-            multicast_addr: self.multicast_addr,
-            local_port: self.local_port,
-            node_state: self.node_state.clone(),
-            sender: self.sender.clone(),
-            receiver: self.receiver.clone(),
+    /// Send pheromones coalesced into as few datagrams as fit under the
+    /// UDP payload limit
+    pub async fn send_pheromone_batch(&self, pheromones: Vec<Pheromone>) -> Result<(), String> {
+        let node_id = self.node_state.read().await.id;
+        let batches = Message::pheromone_batches(
+            pheromones,
+            node_id,
+            self.format,
+            self.compress_above,
+            MAX_DATAGRAM_SIZE,
+        )?;
+
+        for batch in batches {
+            self.broadcast(batch).await?;
         }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Transport for NetworkManager {
+    async fn start(&self) -> Result<(), String> {
+        NetworkManager::start(self).await
+    }
+
+    async fn broadcast(&self, message: Message) -> Result<(), String> {
+        NetworkManager::broadcast(self, message).await
+    }
+
+    async fn send_pheromone(&self, pheromone: Pheromone) -> Result<(), String> {
+        NetworkManager::send_pheromone(self, pheromone).await
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::DissentPolicy;
+    use crate::core::node_state::NodeState;
+    use crate::core::types::{ConsensusValue, ValueKind, DEFAULT_ROUND};
+    use crate::crypto::signing::KeyPairWrapper;
+    use tokio::sync::RwLock;
+
+    /// `ENODEV`: the host has no interface that can join the group
+    const ENODEV: i32 = 19;
+
+    /// The single untagged group a manager made with `new` joins
+    fn untagged(node_state: &SharedNodeState) -> Vec<TopicGroup> {
+        vec![TopicGroup { topic: None, addr: "239.255.0.1:5000".parse().unwrap(), state: node_state.clone() }]
+    }
+
+    #[tokio::test]
+    async fn test_clone_shares_single_receiver() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let network = NetworkManager::new(
+            "239.255.0.1:5000".parse().unwrap(),
+            0,
+            node_state,
+        ).await.unwrap();
+        let clone = network.clone();
+
+        assert!(network.start().await.is_ok());
+        assert!(clone.start().await.is_err());
+
+        for node_id in 0..10 {
+            let heartbeat = Message::Heartbeat { node_id, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None };
+            clone.broadcast(heartbeat).await.unwrap();
+        }
+
+        // The single sender task drains messages queued through any clone
+        for _ in 0..50 {
+            if clone.outbound.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(clone.outbound.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_oldest_and_counts() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let network = NetworkManager::new(
+            "239.255.0.1:5000".parse().unwrap(),
+            0,
+            node_state.clone(),
+        ).await.unwrap()
+        .with_outbound_queue(2, OverflowPolicy::DropOldest);
+
+        // Not started, so nothing drains the queue
+        for node_id in 1..=5 {
+            let heartbeat = Message::Heartbeat { node_id, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None };
+            network.broadcast(heartbeat).await.unwrap();
+        }
+
+        assert_eq!(node_state.read().await.stats.outbound_dropped, 3);
+        assert_eq!(network.outbound.pop().await.and_then(|(_, m)| m.sender()), Some(4));
+        assert_eq!(network.outbound.pop().await.and_then(|(_, m)| m.sender()), Some(5));
+
+        // After shutdown broadcasts fail instead of queueing
+        network.shutdown().await.unwrap();
+        let heartbeat = Message::Heartbeat { node_id: 6, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None };
+        assert!(network.broadcast(heartbeat).await.is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs an IPv6 multicast-capable interface"]
+    async fn test_join_ipv6_multicast_group() {
+        let group: SocketAddr = "[ff02::114]:0".parse().unwrap();
+
+        let socket = match NetworkManager::bind_multicast(group, 0, None, 0).await {
+            Ok(socket) => socket,
+            Err(e) if e.raw_os_error() == Some(ENODEV) => panic!("No IPv6 multicast interface: {}", e),
+            Err(e) => panic!("Failed to join IPv6 group: {}", e),
+        };
+        assert!(socket.local_addr().unwrap().is_ipv6());
+
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let network = NetworkManager::new(group, 0, node_state).await.unwrap();
+        assert!(network.start().await.is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs multicast on the loopback interface"]
+    async fn test_join_ipv4_group_on_loopback_interface() {
+        let group: SocketAddr = "239.255.0.114:0".parse().unwrap();
+        let interface = Some(Ipv4Addr::LOCALHOST);
+
+        match NetworkManager::bind_multicast(group, 0, interface, 0).await {
+            Ok(socket) => assert!(socket.local_addr().unwrap().is_ipv4()),
+            Err(e) if e.raw_os_error() == Some(ENODEV) => panic!("No multicast on loopback: {}", e),
+            Err(e) => panic!("Failed to join IPv4 group on loopback: {}", e),
+        }
+
+        let sender = NetworkManager::bind_sender(group, interface).await.unwrap();
+        assert_eq!(SockRef::from(&sender).multicast_if_v4().unwrap(), Ipv4Addr::LOCALHOST);
+
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let network = NetworkManager::new(group, 0, node_state).await.unwrap().with_interface(interface);
+        assert!(network.start().await.is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs an IPv4 multicast-capable interface"]
+    async fn test_leave_multicast_group_on_shutdown() {
+        let group: SocketAddr = "239.255.0.115:0".parse().unwrap();
+
+        let socket = match NetworkManager::bind_multicast(group, 0, None, 0).await {
+            Ok(socket) => socket,
+            Err(e) if e.raw_os_error() == Some(ENODEV) => panic!("No IPv4 multicast interface: {}", e),
+            Err(e) => panic!("Failed to join IPv4 group: {}", e),
+        };
+        NetworkManager::leave_multicast(&socket, group, None, 0).unwrap();
+        // Leaving twice fails: the membership is really gone
+        assert!(NetworkManager::leave_multicast(&socket, group, None, 0).is_err());
+
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let network = NetworkManager::new(group, 0, node_state).await.unwrap();
+        network.start().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), network.shutdown())
+            .await
+            .expect("shutdown hung")
+            .unwrap();
+
+        // The sender task is gone, so nothing more can be queued
+        let heartbeat = Message::Heartbeat { node_id: 1, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None };
+        assert!(network.broadcast(heartbeat).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_forged_pheromone_rejected() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let peer = NodeState::new(2);
+        node_state.write().await.register_public_key(2, peer.public_key());
+
+        // Signed by an attacker's key but claiming to come from node 2
+        let attacker = KeyPairWrapper::generate().unwrap();
+        let value = ConsensusValue::from_string("forged");
+        let forged = Pheromone::new(value.clone(), 2, &attacker.private_key_bytes()).unwrap();
+        let message = Message::PheromoneBroadcast { pheromone: forged, sender: 2 };
+
+        apply_message(&message, &node_state).await.unwrap();
+
+        let state = node_state.read().await;
+        assert_eq!(state.stats.pheromones_rejected, 1);
+        assert_eq!(state.stats.pheromones_received, 0);
+        assert!(!state.pheromones.contains_key(&value));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_broadcast_rejected() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let network = NetworkManager::new("239.255.0.1:5000".parse().unwrap(), 0, node_state)
+            .await
+            .unwrap();
+
+        let message = Message::NeighborDiscovery {
+            node_id: 1,
+            neighbors: (0..20_000).collect(),
+            tcp_port: None,
+            signature: None,
+        };
+        let error = network.broadcast(message).await.unwrap_err();
+        assert!(error.contains("UDP payload limit"), "{}", error);
+        assert!(network.outbound.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_buffer_filling_datagram_flagged() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let source = "127.0.0.1:9".parse().unwrap();
+
+        let reply = NetworkManager::process_datagram(
+            &[b'{'; RECEIVE_BUFFER_SIZE], source, &untagged(&node_state), &mut SeenCache::default(), &mut [Vec::new()],
+        ).await;
+
+        assert!(reply.is_none());
+        let state = node_state.read().await;
+        assert_eq!(state.stats.datagrams_truncated, 1);
+        assert_eq!(state.stats.messages_received, 0);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_datagram_processed_once() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let mut peer = NodeState::new(2);
+        node_state.write().await.register_public_key(2, peer.public_key());
+
+        let pheromone = peer.emit_pheromone(ConsensusValue::from_string("echo")).unwrap();
+        let bytes = Message::PheromoneBroadcast { pheromone, sender: 2 }.to_bytes().unwrap();
+        let mut seen = SeenCache::default();
+
+        let source = "127.0.0.1:9".parse().unwrap();
+        let groups = untagged(&node_state);
+        let mut pending = [Vec::new()];
+        assert!(NetworkManager::process_datagram(&bytes, source, &groups, &mut seen, &mut pending).await.is_none());
+        assert!(NetworkManager::process_datagram(&bytes, source, &groups, &mut seen, &mut pending).await.is_none());
+        assert_eq!(pending[0].len(), 1);
+
+        NetworkManager::flush_all(&mut pending, &groups).await;
+        assert!(pending[0].is_empty());
+        assert_eq!(node_state.read().await.stats.pheromones_received, 1);
+    }
+
+    #[tokio::test]
+    async fn test_pheromone_stays_within_its_topic() {
+        let mut publisher = NodeState::new(2);
+        let publisher_state = Arc::new(RwLock::new(NodeState::new(2)));
+        let a = NetworkManager::with_topics(
+            vec![("tenant-a".to_string(), "239.255.1.1:5000".parse().unwrap())], 5000, publisher_state,
+        ).await.unwrap();
+        assert_eq!(a.topics(), ["tenant-a"]);
+
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        node_state.write().await.register_public_key(2, publisher.public_key());
+        let b = NetworkManager::with_topics(
+            vec![("tenant-b".to_string(), "239.255.1.2:5000".parse().unwrap())], 5000, node_state.clone(),
+        ).await.unwrap();
+
+        // Even if topic A's datagram reaches B's socket, B drops it
+        let value = ConsensusValue::from_string("tenant-a only");
+        let message = Message::PheromoneBroadcast { pheromone: publisher.emit_pheromone(value.clone()).unwrap(), sender: 2 };
+        let source = "127.0.0.1:9".parse().unwrap();
+        let mut pending = [Vec::new()];
+        for bytes in [
+            message.encode_datagram_on(a.topic(), a.format, a.compress_above).unwrap(),
+            message.encode_datagram(a.format, a.compress_above).unwrap(),
+        ] {
+            NetworkManager::process_datagram(&bytes, source, &b.groups, &mut SeenCache::default(), &mut pending).await;
+        }
+        NetworkManager::flush_all(&mut pending, &b.groups).await;
+        {
+            let state = node_state.read().await;
+            assert_eq!(state.stats.datagrams_off_topic, 2);
+            assert_eq!(state.stats.messages_received, 0);
+            assert!(!state.pheromones.contains_key(&value));
+        }
+
+        // The same pheromone tagged with B's topic gets through
+        let bytes = message.encode_datagram_on(b.topic(), b.format, b.compress_above).unwrap();
+        NetworkManager::process_datagram(&bytes, source, &b.groups, &mut SeenCache::default(), &mut pending).await;
+        NetworkManager::flush_all(&mut pending, &b.groups).await;
+        assert!(node_state.read().await.pheromones.contains_key(&value));
+    }
+
+    #[tokio::test]
+    async fn test_topics_routed_to_their_states() {
+        let mut publisher = NodeState::new(2);
+        let (first, second) = (Arc::new(RwLock::new(NodeState::new(1))), Arc::new(RwLock::new(NodeState::new(1))));
+        for state in [&first, &second] {
+            state.write().await.register_public_key(2, publisher.public_key());
+        }
+        let groups = vec![
+            ("first".to_string(), "239.255.1.1:5000".parse().unwrap()),
+            ("second".to_string(), "239.255.1.2:5000".parse().unwrap()),
+        ];
+        let network = NetworkManager::with_topics(groups, 5000, first.clone())
+            .await
+            .unwrap()
+            .with_topic_state("second", second.clone())
+            .unwrap();
+        assert!(network.clone().with_topic_state("third", second.clone()).is_err());
+
+        // Each topic's pheromone lands in its own domain only
+        let source = "127.0.0.1:9".parse().unwrap();
+        let mut pending = [Vec::new(), Vec::new()];
+        for (topic, name) in [("first", "one"), ("second", "two")] {
+            let pheromone = publisher.emit_pheromone(ConsensusValue::from_string(name)).unwrap();
+            let bytes = Message::PheromoneBroadcast { pheromone, sender: 2 }
+                .encode_datagram_on(Some(topic), network.format, None)
+                .unwrap();
+            NetworkManager::process_datagram(&bytes, source, &network.groups, &mut SeenCache::default(), &mut pending).await;
+        }
+        NetworkManager::flush_all(&mut pending, &network.groups).await;
+        for (state, held, other) in [(&first, "one", "two"), (&second, "two", "one")] {
+            let state = state.read().await;
+            assert!(state.pheromones.contains_key(&ConsensusValue::from_string(held)));
+            assert!(!state.pheromones.contains_key(&ConsensusValue::from_string(other)));
+        }
+
+        // A handle for the second topic speaks for its state, on its group
+        let handle = network.on_topic("second").unwrap();
+        assert_eq!(handle.topic(), Some("second"));
+        assert!(Arc::ptr_eq(&handle.node_state, &second));
+        handle.broadcast(Message::Heartbeat { node_id: 1, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None }).await.unwrap();
+        assert_eq!(network.outbound.pop().await.map(|(route, _)| route), Some(1));
+        assert_eq!(network.members(), [0, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_frames_routed_by_topic() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let groups = vec![("tenant-a".to_string(), "239.255.1.1:5000".parse().unwrap())];
+        let receiver = NetworkManager::with_topics(groups, 5000, node_state.clone()).await.unwrap();
+        let addr = receiver.listen_tcp("127.0.0.1:0".parse().unwrap()).await.unwrap();
+
+        let sender_state = Arc::new(RwLock::new(NodeState::new(2)));
+        let groups = vec![("tenant-b".to_string(), "239.255.1.2:5000".parse().unwrap())];
+        let other_tenant = NetworkManager::with_topics(groups, 5000, sender_state.clone()).await.unwrap();
+        let groups = vec![("tenant-a".to_string(), "239.255.1.1:5000".parse().unwrap())];
+        let same_tenant = NetworkManager::with_topics(groups, 5000, sender_state).await.unwrap();
+
+        other_tenant.send_to(addr, Message::Heartbeat { node_id: 2, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None }).await.unwrap();
+        same_tenant.send_to(addr, Message::Heartbeat { node_id: 3, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None }).await.unwrap();
+        for _ in 0..50 {
+            if node_state.read().await.last_seen.contains_key(&3) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let state = node_state.read().await;
+        assert!(state.last_seen.contains_key(&3));
+        assert!(!state.last_seen.contains_key(&2));
+        assert_eq!(state.stats.datagrams_off_topic, 1);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_listener_released_on_shutdown() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let network = NetworkManager::new("239.255.0.1:5000".parse().unwrap(), 0, node_state.clone()).await.unwrap();
+        let addr = network.listen_tcp("127.0.0.1:0".parse().unwrap()).await.unwrap();
+
+        // An open connection doesn't hold the listener up either
+        let mut connection = TcpStream::connect(addr).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), network.shutdown())
+            .await
+            .expect("shutdown hung")
+            .unwrap();
+
+        assert!(TcpListener::bind(addr).await.is_ok(), "port still bound after shutdown");
+        assert!(network.listen_tcp("127.0.0.1:0".parse().unwrap()).await.is_err());
+
+        // Frames sent after shutdown are no longer applied
+        let heartbeat = Message::Heartbeat { node_id: 2, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None };
+        let _ = write_frame(&mut connection, &heartbeat.to_bytes().unwrap()).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(node_state.read().await.stats.messages_received, 0);
+    }
+
+    #[tokio::test]
+    async fn test_topic_groups_validated() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let group = |topic: &str, addr: &str| (topic.to_string(), addr.parse().unwrap());
+
+        assert!(NetworkManager::with_topics(vec![], 5000, node_state.clone()).await.is_err());
+        let duplicate = vec![group("a", "239.255.1.1:5000"), group("a", "239.255.1.2:5000")];
+        assert!(NetworkManager::with_topics(duplicate, 5000, node_state.clone()).await.is_err());
+        let mixed = vec![group("a", "239.255.1.1:5000"), group("b", "[ff02::114]:5000")];
+        assert!(NetworkManager::with_topics(mixed, 5000, node_state.clone()).await.is_err());
+        // The receiver only listens on the local port
+        let elsewhere = vec![group("a", "239.255.1.1:5000"), group("b", "239.255.1.2:5001")];
+        assert!(NetworkManager::with_topics(elsewhere, 5000, node_state.clone()).await.is_err());
+
+        let groups = vec![group("a", "239.255.1.1:5000"), group("b", "239.255.1.2:5000")];
+        let network = NetworkManager::with_topics(groups, 5000, node_state).await.unwrap();
+        assert_eq!(network.topics(), ["a", "b"]);
+        assert_eq!(network.multicast_addr, "239.255.1.1:5000".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_buffered_pheromones_verified_as_batch() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let mut peer = NodeState::new(2);
+        node_state.write().await.register_public_key(2, peer.public_key());
+
+        let mut pending: Vec<(Pheromone, NodeId)> = (0..5)
+            .map(|i| (peer.emit_pheromone(ConsensusValue::from_string(&format!("batched-{}", i))).unwrap(), 2))
+            .collect();
+
+        // One forged entry in the middle of the batch
+        let attacker = KeyPairWrapper::generate().unwrap();
+        let forged_value = ConsensusValue::from_string("forged");
+        pending.insert(2, (Pheromone::new(forged_value.clone(), 2, &attacker.private_key_bytes()).unwrap(), 2));
+
+        NetworkManager::flush_pheromones(&mut pending, &node_state).await;
+
+        let state = node_state.read().await;
+        assert_eq!(state.stats.pheromones_received, 5);
+        assert_eq!(state.stats.pheromones_rejected, 1);
+        assert_eq!(state.stats.messages_received, 6);
+        assert!(!state.pheromones.contains_key(&forged_value));
+        assert!(state.neighbors.contains(&2));
+    }
+
+    #[tokio::test]
+    async fn test_forged_announcement_rejected() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let peer = NodeState::new(2);
+        {
+            let mut state = node_state.write().await;
+            state.config.authenticate_messages = true;
+            state.register_public_key(2, peer.public_key());
+        }
+
+        // Signed by an attacker's key but claiming to come from node 2
+        let attacker = KeyPairWrapper::generate().unwrap();
+        let value = ConsensusValue::from_string("fake");
+        let forged = Message::ConsensusAnnouncement { node_id: 2, round: DEFAULT_ROUND, value: value.clone(), signature: None }
+            .sign(&attacker);
+        let unsigned = Message::Heartbeat { node_id: 2, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None };
+
+        apply_message(&forged, &node_state).await.unwrap();
+        apply_message(&unsigned, &node_state).await.unwrap();
+
+        let state = node_state.read().await;
+        assert_eq!(state.stats.messages_rejected, 2);
+        assert!(!state.last_seen.contains_key(&2));
+    }
+
+    #[tokio::test]
+    async fn test_signed_announcement_accepted() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let peer = NodeState::new(2);
+        {
+            let mut state = node_state.write().await;
+            state.config.authenticate_messages = true;
+            state.register_public_key(2, peer.public_key());
+        }
+
+        let value = ConsensusValue::from_string("real");
+        let signed = Message::ConsensusAnnouncement { node_id: 2, round: DEFAULT_ROUND, value, signature: None }
+            .sign(&peer.key_pair);
+
+        apply_message(&signed, &node_state).await.unwrap();
+
+        let state = node_state.read().await;
+        assert_eq!(state.stats.messages_rejected, 0);
+        assert!(state.last_seen.contains_key(&2));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_announcement_dissent_policy() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        node_state.write().await.config.value_kind = Some(ValueKind::Number);
+
+        let value = ConsensusValue::from_string("not a number");
+        let announcement = Message::ConsensusAnnouncement { node_id: 2, round: DEFAULT_ROUND, value: value.clone(), signature: None };
+
+        // Ignored by default
+        let reply = apply_message(&announcement, &node_state).await.unwrap().into_reply();
+        assert!(reply.is_none());
+
+        node_state.write().await.config.dissent_policy = DissentPolicy::Broadcast;
+        let reply = apply_message(&announcement, &node_state).await.unwrap().into_reply();
+
+        let dissent = reply.expect("dissent under the broadcast policy");
+        assert!(dissent.is_authenticated(&node_state.read().await.key_registry));
+        match dissent {
+            Message::Dissent { node_id, value: disputed, .. } => {
+                assert_eq!(node_id, 1);
+                assert_eq!(disputed, value);
+            }
+            other => panic!("expected dissent, got {:?}", other),
+        }
+
+        // A value of the accepted kind draws no dissent
+        let valid = Message::ConsensusAnnouncement {
+            node_id: 2,
+            round: DEFAULT_ROUND,
+            value: ConsensusValue::from_string("42").with_kind(ValueKind::Number),
+            signature: None,
+        };
+        assert!(apply_message(&valid, &node_state).await.unwrap().into_reply().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_unicast_handled_once() {
+        let receiver_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let receiver = NetworkManager::new("239.255.0.1:5000".parse().unwrap(), 0, receiver_state.clone())
+            .await
+            .unwrap();
+        let addr = receiver.listen_tcp("127.0.0.1:0".parse().unwrap()).await.unwrap();
+
+        // Node 2 learns node 1's address from a discovery datagram
+        let sender_state = Arc::new(RwLock::new(NodeState::new(2)));
+        let sender = NetworkManager::new("239.255.0.1:5000".parse().unwrap(), 0, sender_state.clone())
+            .await
+            .unwrap();
+        let discovery = Message::NeighborDiscovery {
+            node_id: 1,
+            neighbors: vec![],
+            tcp_port: Some(addr.port()),
+            signature: None,
+        };
+        let bytes = discovery.to_bytes().unwrap();
+        let udp_source = "127.0.0.1:40000".parse().unwrap();
+        NetworkManager::process_datagram(&bytes, udp_source, &untagged(&sender_state), &mut SeenCache::default(), &mut [Vec::new()]).await;
+        assert_eq!(sender_state.read().await.peer_addrs.get(&1), Some(&addr));
+
+        let heartbeat = Message::Heartbeat { node_id: 2, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None };
+        sender.send_to_node(1, heartbeat).await.unwrap();
+        assert!(sender.send_to_node(9, Message::Heartbeat { node_id: 2, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None }).await.is_err());
+
+        for _ in 0..50 {
+            if receiver_state.read().await.stats.messages_received > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let state = receiver_state.read().await;
+        assert_eq!(state.stats.messages_received, 1);
+        assert!(state.last_seen.contains_key(&2));
+    }
+
+    #[tokio::test]
+    async fn test_valid_pheromone_accepted() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let mut peer = NodeState::new(2);
+        node_state.write().await.register_public_key(2, peer.public_key());
+
+        let value = ConsensusValue::from_string("genuine");
+        let pheromone = peer.emit_pheromone(value.clone()).unwrap();
+        let message = Message::AntMovement {
+            ant_id: 7,
+            from_node: 2,
+            to_node: 1,
+            carried_pheromone: Some(pheromone),
+            ttl: 0,
+            extra_pheromones: vec![],
+        };
+
+        apply_message(&message, &node_state).await.unwrap();
+
+        let state = node_state.read().await;
+        assert_eq!(state.stats.pheromones_rejected, 0);
+        assert!(state.pheromones.contains_key(&value));
+    }
+
+    #[tokio::test]
+    async fn test_intensity_attributed_to_forwarding_neighbor() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let mut origin = NodeState::new(5);
+        {
+            let mut state = node_state.write().await;
+            state.register_public_key(5, origin.public_key());
+            // Relaying someone else's pheromone doesn't make a neighbor, so
+            // the relays are already known, e.g. from their heartbeats
+            state.add_neighbor(2);
+            state.add_neighbor(3);
+        }
+
+        // Node 5's pheromones reach node 1 only through relays 2 and 3
+        let (left, right) = (ConsensusValue::from_string("left"), ConsensusValue::from_string("right"));
+        let via_2 = Message::PheromoneBroadcast { pheromone: origin.emit_pheromone(left.clone()).unwrap(), sender: 2 };
+        let via_3 = Message::ConsensusResponse { node_id: 3, pheromones: vec![origin.emit_pheromone(right.clone()).unwrap()] };
+        apply_message(&via_2, &node_state).await.unwrap();
+        apply_message(&via_3, &node_state).await.unwrap();
+
+        let state = node_state.read().await;
+        assert!(state.intensity_via(2, &left) > 0.0);
+        assert_eq!(state.intensity_via(2, &right), 0.0);
+        assert!(state.intensity_via(3, &right) > 0.0);
+        assert_eq!(state.intensity_via(3, &left), 0.0);
+        assert_eq!(state.intensity_via(5, &left), 0.0);
+
+        assert_eq!(state.neighbor_intensities(Some(&left)), vec![(2, state.intensity_via(2, &left))]);
+        assert_eq!(state.neighbor_intensities(Some(&right)), vec![(3, state.intensity_via(3, &right))]);
+    }
+
+    #[tokio::test]
+    async fn test_pheromone_batch_round_trip() {
+        let node_state = Arc::new(RwLock::new(NodeState::new(1)));
+        let mut peer = NodeState::new(2);
+        node_state.write().await.register_public_key(2, peer.public_key());
+
+        let values: Vec<_> = (0..50).map(|i| ConsensusValue::from_string(&format!("value-{}", i))).collect();
+        let pheromones = values.iter().map(|v| peer.emit_pheromone(v.clone()).unwrap()).collect();
+
+        let batches = Message::pheromone_batches(
+            pheromones, 2, SerializationFormat::default(), None, MAX_DATAGRAM_SIZE,
+        ).unwrap();
+        assert_eq!(batches.len(), 1);
+
+        let bytes = batches[0].to_bytes().unwrap();
+        let message = Message::from_bytes_auto(&bytes).unwrap();
+        apply_message(&message, &node_state).await.unwrap();
+
+        let state = node_state.read().await;
+        assert_eq!(state.stats.pheromones_received, 50);
+        assert_eq!(state.stats.pheromones_rejected, 0);
+        assert!(values.iter().all(|value| state.pheromones.contains_key(value)));
+    }
+}
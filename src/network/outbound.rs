@@ -0,0 +1,217 @@
+use crate::network::message::Message;
+use serde::{Serialize, Deserialize};
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use tokio::sync::Notify;
+
+/// Default number of outbound messages queued for the sender task
+pub const DEFAULT_OUTBOUND_CAPACITY: usize = 1000;
+
+/// What `broadcast` does when the outbound queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OverflowPolicy {
+    /// Wait for the sender task to make room
+    Block,
+
+    /// Discard the message being broadcast
+    DropNewest,
+
+    /// Discard the longest-queued message to make room (default), so
+    /// fresh heartbeats and pheromones win over stale ones
+    #[default]
+    DropOldest,
+}
+
+#[derive(Debug)]
+struct QueueState<T> {
+    messages: VecDeque<T>,
+    closed: bool,
+}
+
+/// Bounded queue between `broadcast` callers and the single sender task,
+/// applying an `OverflowPolicy` when full
+///
+/// Entries are messages unless the sender needs more with each, such as
+/// the topic a message goes out on.
+#[derive(Debug)]
+pub struct OutboundQueue<T = Message> {
+    state: Mutex<QueueState<T>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    not_empty: Notify,
+    not_full: Notify,
+}
+
+impl<T> OutboundQueue<T> {
+    /// Create a queue holding up to `capacity` messages
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        Self {
+            state: Mutex::new(QueueState { messages: VecDeque::new(), closed: false }),
+            capacity: capacity.max(1),
+            policy,
+            not_empty: Notify::new(),
+            not_full: Notify::new(),
+        }
+    }
+
+    /// Policy applied when the queue is full
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Maximum number of queued messages
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of queued messages
+    pub fn len(&self) -> usize {
+        self.lock().messages.len()
+    }
+
+    /// Whether no messages are queued
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Queue `message`, returning the message discarded to honor the
+    /// overflow policy, if any
+    ///
+    /// Only `OverflowPolicy::Block` waits, and only while the queue is full.
+    /// Fails once the queue is closed.
+    pub async fn push(&self, message: T) -> Result<Option<T>, String> {
+        loop {
+            // Registered before checking, so a pop or close in between wakes us
+            let room = self.not_full.notified();
+            tokio::pin!(room);
+            room.as_mut().enable();
+
+            {
+                let mut state = self.lock();
+                if state.closed {
+                    return Err("Outbound queue closed".to_string());
+                }
+
+                let dropped = if state.messages.len() < self.capacity {
+                    None
+                } else {
+                    match self.policy {
+                        OverflowPolicy::Block => None,
+                        OverflowPolicy::DropNewest => return Ok(Some(message)),
+                        OverflowPolicy::DropOldest => state.messages.pop_front(),
+                    }
+                };
+
+                if state.messages.len() < self.capacity {
+                    state.messages.push_back(message);
+                    self.not_empty.notify_one();
+                    return Ok(dropped);
+                }
+            }
+
+            room.await;
+        }
+    }
+
+    /// Take the oldest queued message, waiting for one to arrive; `None`
+    /// once the queue is closed
+    pub async fn pop(&self) -> Option<T> {
+        loop {
+            let arrival = self.not_empty.notified();
+            tokio::pin!(arrival);
+            arrival.as_mut().enable();
+
+            {
+                let mut state = self.lock();
+                if state.closed {
+                    return None;
+                }
+                if let Some(message) = state.messages.pop_front() {
+                    self.not_full.notify_one();
+                    return Some(message);
+                }
+            }
+
+            arrival.await;
+        }
+    }
+
+    /// Discard queued messages and wake every waiter; later pushes fail
+    pub fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        state.messages.clear();
+        self.not_empty.notify_waiters();
+        self.not_full.notify_waiters();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tokio::time::{timeout, Duration};
+
+    fn heartbeat(node_id: u32) -> Message {
+        Message::Heartbeat { node_id, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None }
+    }
+
+    async fn drain(queue: &OutboundQueue) -> Vec<Option<u32>> {
+        let mut senders = Vec::new();
+        while !queue.is_empty() {
+            senders.push(queue.pop().await.unwrap().sender());
+        }
+        senders
+    }
+
+    #[tokio::test]
+    async fn test_drop_policies_on_full_queue() {
+        let newest = OutboundQueue::new(2, OverflowPolicy::DropNewest);
+        let oldest = OutboundQueue::new(2, OverflowPolicy::DropOldest);
+        for queue in [&newest, &oldest] {
+            assert!(queue.push(heartbeat(1)).await.unwrap().is_none());
+            assert!(queue.push(heartbeat(2)).await.unwrap().is_none());
+        }
+
+        let dropped = newest.push(heartbeat(3)).await.unwrap();
+        assert_eq!(dropped.and_then(|m| m.sender()), Some(3));
+        assert_eq!(drain(&newest).await, vec![Some(1), Some(2)]);
+
+        let dropped = oldest.push(heartbeat(3)).await.unwrap();
+        assert_eq!(dropped.and_then(|m| m.sender()), Some(1));
+        assert_eq!(drain(&oldest).await, vec![Some(2), Some(3)]);
+    }
+
+    #[tokio::test]
+    async fn test_block_policy_waits_for_room() {
+        let queue = Arc::new(OutboundQueue::new(1, OverflowPolicy::Block));
+        queue.push(heartbeat(1)).await.unwrap();
+
+        // Full: the push waits rather than dropping anything
+        assert!(timeout(Duration::from_millis(50), queue.push(heartbeat(2))).await.is_err());
+
+        let pusher = {
+            let queue = queue.clone();
+            tokio::spawn(async move { queue.push(heartbeat(3)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(queue.pop().await.and_then(|m| m.sender()), Some(1));
+        assert!(pusher.await.unwrap().unwrap().is_none());
+        assert_eq!(drain(&queue).await, vec![Some(3)]);
+
+        // Closing releases blocked pushers with an error
+        queue.push(heartbeat(4)).await.unwrap();
+        let blocked = {
+            let queue = queue.clone();
+            tokio::spawn(async move { queue.push(heartbeat(5)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        queue.close();
+        assert!(blocked.await.unwrap().is_err());
+        assert!(queue.pop().await.is_none());
+    }
+}
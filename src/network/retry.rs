@@ -0,0 +1,146 @@
+use async_trait::async_trait;
+use std::io;
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
+use tokio::time::{sleep, Duration};
+
+/// Default number of send attempts before a message is dropped
+pub const DEFAULT_MAX_SEND_ATTEMPTS: u32 = 4;
+
+/// Default delay before the first retry; each further retry doubles it
+pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(10);
+
+/// `ENOBUFS`: the kernel's socket buffer is full, which clears on its own
+#[cfg(target_os = "linux")]
+const ENOBUFS: i32 = 105;
+#[cfg(not(target_os = "linux"))]
+const ENOBUFS: i32 = 55;
+
+/// How often, and how patiently, a failed send is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_SEND_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+        }
+    }
+}
+
+/// Socket able to send a datagram, so retry behavior can be tested
+/// without a real network
+#[async_trait]
+pub trait DatagramSocket: Send + Sync {
+    async fn send_to(&self, buf: &[u8], addr: SocketAddr) -> io::Result<usize>;
+}
+
+#[async_trait]
+impl DatagramSocket for UdpSocket {
+    async fn send_to(&self, buf: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        UdpSocket::send_to(self, buf, addr).await
+    }
+}
+
+/// Whether a send error is transient and worth retrying
+pub fn is_retryable(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted | io::ErrorKind::TimedOut
+    ) || error.raw_os_error() == Some(ENOBUFS)
+}
+
+/// Send `buf` to `addr`, retrying transient failures with exponential
+/// backoff; non-retryable errors are returned immediately
+pub async fn send_with_retry<S: DatagramSocket + ?Sized>(
+    socket: &S,
+    buf: &[u8],
+    addr: SocketAddr,
+    policy: RetryPolicy,
+) -> io::Result<()> {
+    let mut backoff = policy.initial_backoff;
+    let mut attempt = 1;
+
+    loop {
+        match socket.send_to(buf, addr).await {
+            Ok(_) => return Ok(()),
+            Err(e) if is_retryable(&e) && attempt < policy.max_attempts => {
+                sleep(backoff).await;
+                backoff = backoff.saturating_mul(2);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Socket that returns scripted errors before succeeding
+    struct FlakySocket {
+        failures: Mutex<Vec<io::ErrorKind>>,
+        attempts: Mutex<u32>,
+    }
+
+    impl FlakySocket {
+        fn new(failures: Vec<io::ErrorKind>) -> Self {
+            Self { failures: Mutex::new(failures), attempts: Mutex::new(0) }
+        }
+
+        fn attempts(&self) -> u32 {
+            *self.attempts.lock().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl DatagramSocket for FlakySocket {
+        async fn send_to(&self, buf: &[u8], _addr: SocketAddr) -> io::Result<usize> {
+            *self.attempts.lock().unwrap() += 1;
+            let mut failures = self.failures.lock().unwrap();
+            if failures.is_empty() {
+                Ok(buf.len())
+            } else {
+                Err(io::Error::from(failures.remove(0)))
+            }
+        }
+    }
+
+    fn addr() -> SocketAddr {
+        "127.0.0.1:5000".parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        let socket = FlakySocket::new(vec![io::ErrorKind::WouldBlock, io::ErrorKind::WouldBlock]);
+
+        send_with_retry(&socket, b"hello", addr(), RetryPolicy::default()).await.unwrap();
+        assert_eq!(socket.attempts(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let socket = FlakySocket::new(vec![io::ErrorKind::WouldBlock; 10]);
+        let policy = RetryPolicy { max_attempts: 3, initial_backoff: Duration::from_millis(1) };
+
+        assert!(send_with_retry(&socket, b"hello", addr(), policy).await.is_err());
+        assert_eq!(socket.attempts(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_failure_fails_fast() {
+        let socket = FlakySocket::new(vec![io::ErrorKind::PermissionDenied]);
+
+        assert!(send_with_retry(&socket, b"hello", addr(), RetryPolicy::default()).await.is_err());
+        assert_eq!(socket.attempts(), 1);
+    }
+}
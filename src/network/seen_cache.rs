@@ -0,0 +1,113 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Default number of message digests remembered
+pub const DEFAULT_SEEN_CACHE_CAPACITY: usize = 4096;
+
+/// Default window within which a repeated message is treated as a duplicate
+pub const DEFAULT_SEEN_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Bounded cache of recently processed message digests
+///
+/// Multicast delivers the same datagram to every group member and ants are
+/// re-broadcast, so a node sees many copies of a message; the cache lets the
+/// receive loop drop repeats seen within the TTL. When full, the oldest
+/// digest is evicted first.
+#[derive(Debug)]
+pub struct SeenCache {
+    entries: HashMap<[u8; 32], Instant>,
+    order: VecDeque<([u8; 32], Instant)>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl SeenCache {
+    /// Create a cache holding up to `capacity` digests for `ttl` each
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: capacity.max(1),
+            ttl,
+        }
+    }
+
+    /// Record `digest`, returning `false` if it was already seen within the TTL
+    pub fn insert(&mut self, digest: [u8; 32]) -> bool {
+        self.insert_at(digest, Instant::now())
+    }
+
+    fn insert_at(&mut self, digest: [u8; 32], now: Instant) -> bool {
+        if let Some(seen) = self.entries.get(&digest) {
+            if now.duration_since(*seen) < self.ttl {
+                return false;
+            }
+        }
+
+        self.entries.insert(digest, now);
+        self.order.push_back((digest, now));
+
+        while self.entries.len() > self.capacity {
+            let Some((oldest, at)) = self.order.pop_front() else {
+                break;
+            };
+            // Skip queue entries superseded by a later re-insertion
+            if self.entries.get(&oldest) == Some(&at) {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        // Keep the queue from growing without bound through re-insertions
+        if self.order.len() > self.capacity * 2 {
+            let entries = &self.entries;
+            self.order.retain(|(digest, at)| entries.get(digest) == Some(at));
+        }
+
+        true
+    }
+
+    /// Number of digests currently remembered
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for SeenCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEEN_CACHE_CAPACITY, DEFAULT_SEEN_CACHE_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeat_within_ttl_is_rejected() {
+        let mut cache = SeenCache::new(8, Duration::from_secs(10));
+        let start = Instant::now();
+
+        assert!(cache.insert_at([1; 32], start));
+        assert!(!cache.insert_at([1; 32], start + Duration::from_secs(5)));
+        assert!(cache.insert_at([1; 32], start + Duration::from_secs(11)));
+    }
+
+    #[test]
+    fn test_oldest_evicted_when_full() {
+        let mut cache = SeenCache::new(2, Duration::from_secs(10));
+        let now = Instant::now();
+
+        cache.insert_at([1; 32], now);
+        cache.insert_at([2; 32], now);
+        cache.insert_at([3; 32], now);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.insert_at([1; 32], now));
+        assert!(!cache.insert_at([3; 32], now));
+    }
+}
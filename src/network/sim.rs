@@ -0,0 +1,258 @@
+use crate::core::node_state::SharedNodeState;
+use crate::core::pheromone::Pheromone;
+use crate::core::types::NodeId;
+use crate::network::message::Message;
+use crate::network::apply::apply_message;
+use crate::network::transport::Transport;
+use crate::utils::random::{RngSource, SeededRngSource};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::error;
+
+/// Message waiting for its delivery time
+struct InFlight {
+    to: NodeId,
+    message: Message,
+}
+
+/// Shared state of a `SimNetwork`
+struct SimState {
+    nodes: BTreeMap<NodeId, SharedNodeState>,
+    /// Messages keyed by delivery time, then send order
+    queue: BTreeMap<(Duration, u64), InFlight>,
+    next_seq: u64,
+    now: Duration,
+    loss: f64,
+    latency: Duration,
+    links: HashMap<(NodeId, NodeId), Duration>,
+    rng: SeededRngSource,
+    dropped: u64,
+}
+
+/// Simulated network for studying convergence under packet loss and
+/// latency
+///
+/// Like `InMemoryNetwork` it routes messages between nodes in-process, but
+/// nothing is delivered in the background: each broadcast is dropped with
+/// the configured probability or queued until its link's latency elapses
+/// on a virtual clock, and messages are only handled when a test calls
+/// `advance`. Loss is drawn from a seeded source, so runs are reproducible.
+#[derive(Clone)]
+pub struct SimNetwork {
+    state: Arc<Mutex<SimState>>,
+}
+
+impl SimNetwork {
+    /// Create a lossless, zero-latency network whose loss is drawn from
+    /// a source seeded with `seed`
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SimState {
+                nodes: BTreeMap::new(),
+                queue: BTreeMap::new(),
+                next_seq: 0,
+                now: Duration::ZERO,
+                loss: 0.0,
+                latency: Duration::ZERO,
+                links: HashMap::new(),
+                rng: SeededRngSource::new(seed),
+                dropped: 0,
+            })),
+        }
+    }
+
+    /// Drop each delivery with probability `loss`
+    pub fn with_loss(self, loss: f64) -> Self {
+        self.lock().loss = loss.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Delay every delivery by `latency` unless its link overrides it
+    pub fn with_latency(self, latency: Duration) -> Self {
+        self.lock().latency = latency;
+        self
+    }
+
+    /// Delay deliveries from `from` to `to` by `latency`
+    pub fn set_link_latency(&self, from: NodeId, to: NodeId, latency: Duration) {
+        self.lock().links.insert((from, to), latency);
+    }
+
+    /// Attach a node to the network, returning its transport
+    pub fn transport(&self, node_id: NodeId, node_state: SharedNodeState) -> SimTransport {
+        self.lock().nodes.insert(node_id, node_state);
+        SimTransport { node_id, network: self.clone() }
+    }
+
+    /// Current virtual time
+    pub fn now(&self) -> Duration {
+        self.lock().now
+    }
+
+    /// Messages sent but not yet delivered
+    pub fn pending(&self) -> usize {
+        self.lock().queue.len()
+    }
+
+    /// Deliveries lost to the configured loss
+    pub fn dropped(&self) -> u64 {
+        self.lock().dropped
+    }
+
+    /// Move the virtual clock forward by `by`, handling every message due
+    /// by then in delivery order; returns how many were delivered
+    ///
+    /// Replies produced while handling are sent from the handling node and
+    /// are delivered in the same call if they fall due in time.
+    pub async fn advance(&self, by: Duration) -> usize {
+        let target = self.lock().now + by;
+        let mut delivered = 0;
+
+        loop {
+            let (to, message, node_state) = {
+                let mut state = self.lock();
+                let Some(entry) = state.queue.first_entry() else {
+                    break;
+                };
+                if entry.key().0 > target {
+                    break;
+                }
+
+                let ((at, _), in_flight) = entry.remove_entry();
+                state.now = at;
+                let Some(node_state) = state.nodes.get(&in_flight.to).cloned() else {
+                    continue;
+                };
+                (in_flight.to, in_flight.message, node_state)
+            };
+
+            delivered += 1;
+            match apply_message(&message, &node_state).await {
+                Ok(outcome) => {
+                    if let Some(reply) = outcome.into_reply() {
+                        self.send(to, reply);
+                    }
+                }
+                Err(e) => error!("Error handling message: {}", e),
+            }
+        }
+
+        self.lock().now = target;
+        delivered
+    }
+
+    /// Queue `message` from `from` to every other node, subject to loss
+    fn send(&self, from: NodeId, message: Message) {
+        let mut state = self.lock();
+        let recipients: Vec<NodeId> = state.nodes.keys().copied().filter(|id| *id != from).collect();
+
+        for to in recipients {
+            if state.rng.next_f64() < state.loss {
+                state.dropped += 1;
+                continue;
+            }
+
+            let latency = state.links.get(&(from, to)).copied().unwrap_or(state.latency);
+            let key = (state.now + latency, state.next_seq);
+            state.next_seq += 1;
+            state.queue.insert(key, InFlight { to, message: message.clone() });
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SimState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Transport queueing messages on a `SimNetwork`
+#[derive(Clone)]
+pub struct SimTransport {
+    node_id: NodeId,
+    network: SimNetwork,
+}
+
+#[async_trait]
+impl Transport for SimTransport {
+    async fn start(&self) -> Result<(), String> {
+        // Delivery is driven by `SimNetwork::advance`
+        Ok(())
+    }
+
+    async fn broadcast(&self, message: Message) -> Result<(), String> {
+        let node_state = self.network.lock().nodes.get(&self.node_id).cloned();
+        if let Some(node_state) = node_state {
+            node_state.write().await.stats.record_sent(message.kind());
+        }
+
+        self.network.send(self.node_id, message);
+        Ok(())
+    }
+
+    async fn send_pheromone(&self, pheromone: Pheromone) -> Result<(), String> {
+        self.broadcast(Message::PheromoneBroadcast {
+            pheromone,
+            sender: self.node_id,
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::node_state::NodeState;
+    use tokio::sync::RwLock;
+
+    fn heartbeat(node_id: NodeId) -> Message {
+        Message::Heartbeat { node_id, timestamp: 0, sent_ms: 0, echoes: vec![], signature: None }
+    }
+
+    #[tokio::test]
+    async fn test_latency_delays_delivery() {
+        let network = SimNetwork::new(1).with_latency(Duration::from_millis(50));
+        let sender = network.transport(1, Arc::new(RwLock::new(NodeState::new(1))));
+        let receiver_state = Arc::new(RwLock::new(NodeState::new(2)));
+        network.transport(2, receiver_state.clone());
+        network.set_link_latency(1, 2, Duration::from_millis(80));
+
+        sender.broadcast(heartbeat(1)).await.unwrap();
+        assert_eq!(network.advance(Duration::from_millis(50)).await, 0);
+        assert!(!receiver_state.read().await.last_seen.contains_key(&1));
+
+        assert_eq!(network.advance(Duration::from_millis(30)).await, 1);
+        assert!(receiver_state.read().await.last_seen.contains_key(&1));
+        assert_eq!(network.now(), Duration::from_millis(80));
+    }
+
+    #[tokio::test]
+    async fn test_loss_drops_deliveries() {
+        let network = SimNetwork::new(1).with_loss(1.0);
+        let sender = network.transport(1, Arc::new(RwLock::new(NodeState::new(1))));
+        network.transport(2, Arc::new(RwLock::new(NodeState::new(2))));
+
+        sender.broadcast(heartbeat(1)).await.unwrap();
+        assert_eq!(network.pending(), 0);
+        assert_eq!(network.dropped(), 1);
+        assert_eq!(network.advance(Duration::from_secs(1)).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_same_seed_drops_the_same_messages() {
+        let run = |seed| async move {
+            let network = SimNetwork::new(seed).with_loss(0.5);
+            let sender = network.transport(1, Arc::new(RwLock::new(NodeState::new(1))));
+            let receiver_state = Arc::new(RwLock::new(NodeState::new(2)));
+            network.transport(2, receiver_state.clone());
+
+            let mut received = Vec::new();
+            for _ in 0..20 {
+                sender.broadcast(heartbeat(1)).await.unwrap();
+                received.push(network.advance(Duration::from_millis(1)).await);
+            }
+            received
+        };
+
+        assert_eq!(run(9).await, run(9).await);
+    }
+}
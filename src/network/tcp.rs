@@ -0,0 +1,65 @@
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Largest frame accepted over a TCP connection
+pub const MAX_FRAME_SIZE: usize = 1 << 20;
+
+/// Write one frame: a big-endian `u32` length followed by the payload
+pub async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, payload: &[u8]) -> Result<(), String> {
+    if payload.len() > MAX_FRAME_SIZE {
+        return Err(format!("Frame of {} bytes exceeds the {} byte limit", payload.len(), MAX_FRAME_SIZE));
+    }
+
+    writer.write_all(&(payload.len() as u32).to_be_bytes()).await
+        .map_err(|e| format!("Failed to write frame: {}", e))?;
+    writer.write_all(payload).await
+        .map_err(|e| format!("Failed to write frame: {}", e))?;
+    writer.flush().await
+        .map_err(|e| format!("Failed to write frame: {}", e))
+}
+
+/// Read one frame, returning `None` when the peer closed the connection
+/// cleanly between frames
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<Vec<u8>>, String> {
+    let mut header = [0u8; 4];
+    match reader.read_exact(&mut header).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(format!("Failed to read frame: {}", e)),
+    }
+
+    let len = u32::from_be_bytes(header) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(format!("Frame of {} bytes exceeds the {} byte limit", len, MAX_FRAME_SIZE));
+    }
+
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload).await
+        .map_err(|e| format!("Failed to read frame: {}", e))?;
+    Ok(Some(payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_frames_round_trip() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+
+        write_frame(&mut client, b"first").await.unwrap();
+        write_frame(&mut client, b"").await.unwrap();
+        drop(client);
+
+        assert_eq!(read_frame(&mut server).await.unwrap(), Some(b"first".to_vec()));
+        assert_eq!(read_frame(&mut server).await.unwrap(), Some(Vec::new()));
+        assert_eq!(read_frame(&mut server).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_frame_rejected() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        client.write_all(&(MAX_FRAME_SIZE as u32 + 1).to_be_bytes()).await.unwrap();
+
+        assert!(read_frame(&mut server).await.is_err());
+    }
+}
@@ -0,0 +1,20 @@
+use crate::core::pheromone::Pheromone;
+use crate::network::message::Message;
+use async_trait::async_trait;
+
+/// Message transport between nodes
+///
+/// Implemented by `NetworkManager` for UDP multicast and by
+/// `InMemoryTransport` for in-process nodes. Incoming messages are applied
+/// to the node state by the transport itself once `start` has been called.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Start receiving messages and delivering queued outbound ones
+    async fn start(&self) -> Result<(), String>;
+
+    /// Broadcast a message to all reachable nodes
+    async fn broadcast(&self, message: Message) -> Result<(), String>;
+
+    /// Broadcast a pheromone on behalf of this node
+    async fn send_pheromone(&self, pheromone: Pheromone) -> Result<(), String>;
+}
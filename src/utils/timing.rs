@@ -1,5 +1,60 @@
+use crate::core::types::Timestamp;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Source of wall-clock time for a node, in seconds since epoch
+///
+/// Injecting the clock lets tests move time forward deterministically for
+/// anything that compares timestamps (expiry, drift, neighbor timeouts).
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// Current time in seconds since epoch
+    fn now(&self) -> Timestamp;
+}
+
+/// Shared handle to a clock
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Clock backed by the system time
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        current_timestamp()
+    }
+}
+
+/// Clock that only moves when told to, for tests
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: AtomicU64,
+}
+
+impl ManualClock {
+    /// Create a clock reading `now`
+    pub fn new(now: Timestamp) -> Self {
+        Self { now: AtomicU64::new(now) }
+    }
+
+    /// Set the current time
+    pub fn set(&self, now: Timestamp) {
+        self.now.store(now, Ordering::Relaxed);
+    }
+
+    /// Move the clock forward by `secs`
+    pub fn advance(&self, secs: u64) {
+        self.now.fetch_add(secs, Ordering::Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Timestamp {
+        self.now.load(Ordering::Relaxed)
+    }
+}
+
 /// Get current timestamp in seconds since epoch
 pub fn current_timestamp() -> u64 {
     SystemTime::now()
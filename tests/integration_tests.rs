@@ -30,7 +30,7 @@ fn test_node_state_operations() {
 #[test]
 fn test_pheromone_evaporation() {
     let value = ConsensusValue::from_string("test");
-    let private_key = vec![0u8; 32];
+    let private_key = KeyPairWrapper::generate().unwrap().private_key_bytes();
     
     let mut pheromone = Pheromone::new(value, 1, &private_key).unwrap();
     let initial_intensity = pheromone.intensity;
@@ -69,7 +69,7 @@ fn test_message_serialization() {
     use antcolony_consensus::network::Message;
     
     let value = ConsensusValue::from_string("test");
-    let private_key = vec![0u8; 32];
+    let private_key = KeyPairWrapper::generate().unwrap().private_key_bytes();
     let pheromone = Pheromone::new(value.clone(), 1, &private_key).unwrap();
     
     let message = Message::PheromoneBroadcast {
@@ -88,3 +88,462 @@ fn test_message_serialization() {
     }
 }
 
+
+#[test]
+fn test_consensus_value_hash_algorithms() {
+    use antcolony_consensus::crypto::HashAlgo;
+
+    let sha256 = ConsensusValue::from_bytes(b"test");
+    let sha3 = ConsensusValue::from_bytes_with(HashAlgo::Sha3_256, b"test");
+    let blake3 = ConsensusValue::from_bytes_with(HashAlgo::Blake3, b"test");
+
+    // from_bytes stays SHA-256 for backward compatibility
+    assert_eq!(sha256, ConsensusValue::from_bytes_with(HashAlgo::Sha256, b"test"));
+    assert_ne!(sha256.hash, sha3.hash);
+    assert_ne!(sha256.hash, blake3.hash);
+
+    // Equal digests under different algorithms are different values
+    let relabelled = ConsensusValue { algo: HashAlgo::Blake3, ..sha256.clone() };
+    assert_ne!(sha256, relabelled);
+}
+
+/// Start `n` nodes sharing `config` on a fresh `InMemoryNetwork`, every
+/// node knowing every other node's key
+async fn cluster(
+    n: NodeId,
+    config: ConsensusConfig,
+) -> (Vec<SharedNodeState>, Vec<antcolony_consensus::consensus::ConsensusEngine>) {
+    let network = antcolony_consensus::network::InMemoryNetwork::new();
+    cluster_on(n, config, |id, state| std::sync::Arc::new(network.transport(id, state))).await
+}
+
+/// Start `n` nodes sharing `config` on the transports `transport` makes,
+/// every node knowing every other node's key
+async fn cluster_on(
+    n: NodeId,
+    config: ConsensusConfig,
+    transport: impl Fn(NodeId, SharedNodeState) -> std::sync::Arc<dyn antcolony_consensus::network::Transport>,
+) -> (Vec<SharedNodeState>, Vec<antcolony_consensus::consensus::ConsensusEngine>) {
+    use antcolony_consensus::consensus::ConsensusEngine;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    let states: Vec<SharedNodeState> = (1..=n)
+        .map(|id| Arc::new(RwLock::new(NodeState::with_config(id, config.clone()))))
+        .collect();
+
+    for state in &states {
+        let (id, key) = {
+            let state = state.read().await;
+            (state.id, state.public_key())
+        };
+        for other in &states {
+            other.write().await.register_public_key(id, key.clone());
+        }
+    }
+
+    let mut engines = Vec::new();
+    for state in &states {
+        let id = state.read().await.id;
+        let transport = transport(id, state.clone());
+        transport.start().await.unwrap();
+        engines.push(ConsensusEngine::new(state.clone(), transport));
+    }
+
+    (states, engines)
+}
+
+#[tokio::test]
+async fn test_in_memory_nodes_converge() {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let config = ConsensusConfig { step_interval_ms: 10, ..ConsensusConfig::default() };
+    let (_, engines) = cluster(5, config).await;
+
+    let engines: Vec<_> = engines.into_iter().map(Arc::new).collect();
+    for engine in &engines {
+        let runner = engine.clone();
+        tokio::spawn(async move { runner.run().await });
+    }
+
+    let value = ConsensusValue::from_string("in-memory");
+    engines[0].propose(value.clone()).await.unwrap();
+
+    let converged = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let mut all = true;
+            for engine in &engines {
+                all &= engine.get_consensus().await.as_ref() == Some(&value);
+            }
+            if all {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+
+    assert!(converged.is_ok(), "nodes did not converge");
+}
+
+#[tokio::test]
+async fn test_subscriber_sees_consensus_reached() {
+    use antcolony_consensus::consensus::ConsensusEvent;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let config = ConsensusConfig { step_interval_ms: 10, ..ConsensusConfig::default() };
+    let (_, engines) = cluster(3, config).await;
+
+    let engines: Vec<_> = engines.into_iter().map(Arc::new).collect();
+    for engine in &engines {
+        let runner = engine.clone();
+        tokio::spawn(async move { runner.run().await });
+    }
+
+    // Subscribe once the engines are already running
+    let mut proposer_events = engines[0].subscribe();
+    let mut peer_events = engines[2].subscribe();
+
+    let value = ConsensusValue::from_string("subscribed");
+    engines[0].propose(value.clone()).await.unwrap();
+
+    assert_eq!(
+        proposer_events.recv().await.unwrap(),
+        ConsensusEvent::ValueProposed { value: value.clone() }
+    );
+
+    let reached = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let ConsensusEvent::ConsensusReached { value } = peer_events.recv().await.unwrap() {
+                return value;
+            }
+        }
+    })
+    .await
+    .expect("no ConsensusReached event");
+
+    assert_eq!(reached, value);
+}
+
+#[tokio::test]
+async fn test_lossy_network_converges_in_bounded_steps() {
+    use antcolony_consensus::network::SimNetwork;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const NODES: NodeId = 10;
+    const MAX_STEPS: usize = 20;
+
+    // Every node votes for the value, but needs six of them to agree
+    let config = ConsensusConfig { min_quorum: 6, ..ConsensusConfig::default() };
+    let step_interval = config.step_interval();
+    let network = SimNetwork::new(42)
+        .with_loss(0.2)
+        .with_latency(Duration::from_millis(30));
+
+    let (states, engines) = cluster_on(NODES, config, |id, state| Arc::new(network.transport(id, state))).await;
+    for state in &states {
+        let mut state = state.write().await;
+        let own = state.id;
+        for id in (1..=NODES).filter(|id| *id != own) {
+            state.add_neighbor(id);
+        }
+    }
+
+    // Uneven links, some slower than a step
+    for from in 1..=NODES {
+        for to in 1..=NODES {
+            network.set_link_latency(from, to, Duration::from_millis(u64::from((from * 7 + to * 13) % 150 + 10)));
+        }
+    }
+
+    let value = ConsensusValue::from_string("lossy");
+    for engine in &engines {
+        engine.propose(value.clone()).await.unwrap();
+    }
+
+    let mut converged_after = None;
+    for step in 1..=MAX_STEPS {
+        for engine in &engines {
+            engine.step().await.unwrap();
+        }
+        network.advance(step_interval).await;
+
+        let mut all = true;
+        for engine in &engines {
+            all &= engine.get_consensus().await.as_ref() == Some(&value);
+        }
+        if all {
+            converged_after = Some(step);
+            break;
+        }
+    }
+
+    assert!(network.dropped() > 0);
+    assert!(converged_after.is_some(), "no consensus within {} virtual steps", MAX_STEPS);
+}
+
+#[cfg(feature = "http")]
+#[tokio::test]
+async fn test_http_api_records_proposal() {
+    use antcolony_consensus::api::{self, ProposeResponse, TrailReport};
+    use antcolony_consensus::consensus::ConsensusEngine;
+    use antcolony_consensus::network::{InMemoryNetwork, Transport};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio::sync::RwLock;
+
+    /// Send one HTTP/1.1 request, returning the status code and body
+    async fn request(addr: std::net::SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method, path, body.len(), body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, body.to_string())
+    }
+
+    let network = InMemoryNetwork::new();
+    let state: SharedNodeState = Arc::new(RwLock::new(NodeState::new(1)));
+    let transport: Arc<dyn Transport> = Arc::new(network.transport(1, state.clone()));
+    transport.start().await.unwrap();
+    let engine = Arc::new(ConsensusEngine::new(state, transport));
+
+    let addr = api::serve(engine, "127.0.0.1:0".parse().unwrap()).await.unwrap();
+
+    let (status, body) = request(addr, "POST", "/propose", r#"{"value":"over-http"}"#).await;
+    assert_eq!(status, 202);
+    let proposed: ProposeResponse = serde_json::from_str(&body).unwrap();
+    assert_eq!(proposed.value, ConsensusValue::from_string("over-http").to_hex());
+
+    let (status, body) = request(addr, "GET", "/stats", "").await;
+    assert_eq!(status, 200);
+    let stats = NodeStats::from_json(&body).unwrap();
+    assert_eq!(stats.pheromones_emitted, 1);
+
+    let (_, body) = request(addr, "GET", "/trails", "").await;
+    let trails: Vec<TrailReport> = serde_json::from_str(&body).unwrap();
+    assert_eq!(trails.len(), 1);
+    assert_eq!(trails[0].value, proposed.value);
+
+    let (status, _) = request(addr, "POST", "/propose", "not json").await;
+    assert!((400..500).contains(&status));
+}
+
+#[tokio::test]
+async fn test_dry_run_decides_at_exact_step() {
+    use antcolony_consensus::consensus::{ConsensusEngine, ConsensusEvent};
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    let config = ConsensusConfig { min_quorum: 3, ..ConsensusConfig::default() };
+    let state: SharedNodeState = Arc::new(RwLock::new(NodeState::with_config(1, config)));
+    let engine = ConsensusEngine::dry_run(state.clone(), 7).await;
+    let mut events = engine.subscribe();
+
+    let value = ConsensusValue::from_string("dry-run");
+    let mut peers: Vec<NodeState> = (2..=4).map(NodeState::new).collect();
+    for peer in &peers {
+        state.write().await.register_public_key(peer.id, peer.public_key());
+    }
+
+    // One backer arrives per step; the third brings quorum
+    let mut decided_at = None;
+    for (step, peer) in peers.iter_mut().enumerate() {
+        assert!(engine.inject_pheromone(peer.emit_pheromone(value.clone()).unwrap(), None).await);
+        if let Some(decided) = engine.step_once().await.unwrap() {
+            assert_eq!(decided, value);
+            decided_at.get_or_insert(step + 1);
+        }
+    }
+
+    assert_eq!(decided_at, Some(3));
+    assert_eq!(engine.steps(), 3);
+    assert_eq!(engine.get_consensus().await, Some(value.clone()));
+    assert_eq!(events.try_recv().unwrap(), ConsensusEvent::PheromoneReceived { value: value.clone(), source: 2 });
+    let reached = std::iter::from_fn(|| events.try_recv().ok())
+        .any(|event| event == ConsensusEvent::ConsensusReached { value: value.clone() });
+    assert!(reached);
+}
+
+#[tokio::test]
+async fn test_cluster_converges_only_after_agreement() {
+    use antcolony_consensus::consensus::ConsensusEngine;
+    use std::time::Duration;
+
+    let config = ConsensusConfig { min_quorum: 2, finality_steps: Some(2), ..ConsensusConfig::default() };
+    let (states, engines) = cluster(3, config).await;
+
+    async fn step_all(engines: &[ConsensusEngine], steps: usize) {
+        for _ in 0..steps {
+            for engine in engines {
+                engine.step().await.unwrap();
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    async fn converged(engines: &[ConsensusEngine]) -> Vec<bool> {
+        let mut converged = Vec::new();
+        for engine in engines {
+            converged.push(engine.is_converged().await);
+        }
+        converged
+    }
+
+    // Two single-backer values contend; neither reaches quorum
+    let (agreed, rival) = (ConsensusValue::from_string("agreed"), ConsensusValue::from_string("rival"));
+    engines[0].propose(agreed.clone()).await.unwrap();
+    engines[1].propose(rival).await.unwrap();
+    step_all(&engines, 10).await;
+    assert_eq!(converged(&engines).await, vec![false; 3]);
+
+    // A second backer gives one value quorum; every node commits it
+    engines[2].propose(agreed.clone()).await.unwrap();
+    let mut steps = 0;
+    while converged(&engines).await != vec![true; 3] {
+        assert!(steps < 50, "cluster did not converge");
+        step_all(&engines, 1).await;
+        steps += 1;
+    }
+    for state in &states {
+        assert_eq!(state.read().await.committed_value, Some(agreed.clone()));
+    }
+
+    let settled = engines[0].run_until_converged(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(settled, Some(agreed));
+}
+
+#[tokio::test]
+async fn test_spans_carry_node_context() {
+    use antcolony_consensus::consensus::ConsensusEngine;
+    use antcolony_consensus::network::{InMemoryNetwork, Transport};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::sync::RwLock;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id};
+    use tracing::{Event, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// Fields recorded on a span or event, values in `Debug` form
+    #[derive(Default)]
+    struct Fields(HashMap<String, String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    /// Span name with its fields
+    type SpanRecord = (&'static str, HashMap<String, String>);
+
+    /// Event message with the name of the span it fired in
+    type EventRecord = (String, Option<&'static str>);
+
+    /// Records every new span's fields, and the span each event fired in
+    #[derive(Clone, Default)]
+    struct Capture {
+        spans: Arc<Mutex<Vec<SpanRecord>>>,
+        events: Arc<Mutex<Vec<EventRecord>>>,
+    }
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Capture {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            self.spans.lock().unwrap().push((attrs.metadata().name(), fields.0));
+        }
+
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            let span = ctx.event_span(event).map(|span| span.name());
+            self.events.lock().unwrap().push((fields.0.remove("message").unwrap_or_default(), span));
+        }
+    }
+
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+    let network = InMemoryNetwork::new();
+    let states: Vec<SharedNodeState> = (1..=2)
+        .map(|id| Arc::new(RwLock::new(NodeState::new(id))))
+        .collect();
+    let mut engines = Vec::new();
+    for state in &states {
+        let id = state.read().await.id;
+        let transport: Arc<dyn Transport> = Arc::new(network.transport(id, state.clone()));
+        transport.start().await.unwrap();
+        engines.push(ConsensusEngine::new(state.clone(), transport));
+    }
+    let key = states[0].read().await.public_key();
+    states[1].write().await.register_public_key(1, key);
+    states[0].write().await.add_neighbor(2);
+
+    engines[0].propose(ConsensusValue::from_string("traced")).await.unwrap();
+    for _ in 0..3 {
+        for engine in &engines {
+            engine.step().await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let spans = capture.spans.lock().unwrap();
+    for name in ["consensus_step", "network_receive", "ant_move"] {
+        let found: Vec<_> = spans.iter().filter(|(span, _)| *span == name).collect();
+        assert!(!found.is_empty(), "no {} span", name);
+        assert!(found.iter().all(|(_, fields)| fields.contains_key("node_id")), "{} without node_id", name);
+    }
+
+    // Node 2 handled node 1's pheromone in its own context
+    assert!(spans.iter().any(|(name, fields)| {
+        *name == "network_receive"
+            && fields.get("node_id").map(String::as_str) == Some("2")
+            && fields.get("sender").map(String::as_str) == Some("1")
+            && fields.get("round_id").map(String::as_str) == Some("0")
+    }));
+    assert!(spans.iter().any(|(name, fields)| *name == "ant_move" && fields.contains_key("ant_id")));
+
+    let events = capture.events.lock().unwrap();
+    assert!(events.iter().any(|(message, span)| message == "Ant moved" && *span == Some("ant_move")));
+}
+
+#[tokio::test]
+async fn test_value_validator_rejects_malformed_proposals() {
+    use antcolony_consensus::consensus::ConsensusEngine;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    let state: SharedNodeState = Arc::new(RwLock::new(NodeState::new(1)));
+    let engine = ConsensusEngine::dry_run(state.clone(), 7).await;
+    engine.set_value_validator(ValueValidator::utf8(16)).await;
+
+    let too_long = ConsensusValue::with_payload(b"far more than sixteen bytes");
+    let not_utf8 = ConsensusValue::with_payload(&[0xc3, 0x28]);
+    assert!(engine.propose(too_long.clone()).await.unwrap_err().contains("over the 16 byte limit"));
+    assert!(engine.propose(not_utf8).await.unwrap_err().contains("not UTF-8"));
+    assert!(!state.read().await.pheromones.contains_key(&too_long));
+
+    let conforming = ConsensusValue::with_payload("block 42".as_bytes());
+    engine.propose(conforming.clone()).await.unwrap();
+    assert!(state.read().await.pheromones.contains_key(&conforming));
+
+    // Received pheromones carrying a malformed payload are dropped too
+    let mut peer = NodeState::new(2);
+    state.write().await.register_public_key(2, peer.public_key());
+    let pheromone = peer.emit_pheromone(too_long.clone()).unwrap();
+    assert!(!engine.inject_pheromone(pheromone, Some(2)).await);
+}